@@ -0,0 +1,154 @@
+//! Reads release notes from `CHANGELOG.md`
+
+use crate::SemVer;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+pub const CHANGELOG_FILE_NAME: &str = "CHANGELOG.md";
+
+#[derive(Debug)]
+pub enum ChangelogError {
+    IOError(std::io::Error),
+    SectionNotFound(SemVer),
+}
+
+impl Display for ChangelogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IOError(e) => write!(f, "could not read {}: {}", CHANGELOG_FILE_NAME, e),
+            Self::SectionNotFound(version) => write!(
+                f,
+                "{} does not have a section for version {}",
+                CHANGELOG_FILE_NAME, version
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChangelogError {}
+
+/// Reads the section of the given version from the changelog file of the given directory.
+pub fn read_section<P: AsRef<Path>>(dir: P, version: SemVer) -> Result<String, ChangelogError> {
+    let contents = fs::read_to_string(dir.as_ref().join(CHANGELOG_FILE_NAME))
+        .map_err(ChangelogError::IOError)?;
+    extract_section(&contents, version).ok_or(ChangelogError::SectionNotFound(version))
+}
+
+/// Extracts the body of the section of the given version.
+/// The section starts with a heading like `## 1.2.3`, `## [1.2.3] - 2022-02-15`
+/// or `## v1.2.3` and ends at the next heading of the same level.
+/// Comments inside the section are kept, but the last section does not get
+/// the footer of the file (e.g. `<!-- generated by git-cliff -->`).
+pub fn extract_section(contents: &str, version: SemVer) -> Option<String> {
+    let mut lines = contents.lines().peekable();
+    lines.find(|line| is_version_heading(line, version))?;
+    let mut body: Vec<&str> = Vec::new();
+    while let Some(line) = lines.next_if(|line| !line.starts_with("## ")) {
+        body.push(line);
+    }
+    if lines.peek().is_none() {
+        while let Some(last) = body.last().map(|line| line.trim()) {
+            if last.is_empty() || (last.starts_with("<!--") && last.ends_with("-->")) {
+                body.pop();
+            } else {
+                break;
+            }
+        }
+    }
+    let body = body.join("\n");
+    let body = body.trim();
+    if body.is_empty() {
+        None
+    } else {
+        Some(body.to_owned())
+    }
+}
+
+fn is_version_heading(line: &str, version: SemVer) -> bool {
+    match line.strip_prefix("## ") {
+        Some(heading) => {
+            let heading = heading.trim_start_matches('[');
+            let heading = heading.strip_prefix('v').unwrap_or(heading);
+            let version_as_str = version.to_string();
+            match heading.strip_prefix(&version_as_str) {
+                Some(rest) => rest.is_empty() || rest.starts_with(']') || rest.starts_with(' '),
+                None => false,
+            }
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str = r#"# Changelog
+All notable changes to this project will be documented in this file.
+
+## [0.2.0] - 2022-03-01
+
+### Features
+
+- Support Cargo.lock
+
+## [0.1.10] - 2022-02-20
+
+### Bug Fixes
+
+- Fix typo
+
+## [0.1.1] - 2022-02-15
+
+### Dependencies
+
+- Upgrade to clap 3
+
+<!-- generated by git-cliff -->
+"#;
+
+    #[test]
+    fn test_extract_section() {
+        assert_eq!(
+            extract_section(CHANGELOG, SemVer::new(0, 2, 0)).unwrap(),
+            "### Features\n\n- Support Cargo.lock"
+        );
+    }
+
+    #[test]
+    fn test_extract_section_last() {
+        assert_eq!(
+            extract_section(CHANGELOG, SemVer::new(0, 1, 1)).unwrap(),
+            "### Dependencies\n\n- Upgrade to clap 3"
+        );
+    }
+
+    #[test]
+    fn test_extract_section_plain_heading() {
+        let changelog = "## v1.0.0\n\n- First\n\n## 0.9.0\n\n- Beta\n";
+        assert_eq!(
+            extract_section(changelog, SemVer::new(1, 0, 0)).unwrap(),
+            "- First"
+        );
+        assert_eq!(
+            extract_section(changelog, SemVer::new(0, 9, 0)).unwrap(),
+            "- Beta"
+        );
+    }
+
+    #[test]
+    fn test_extract_section_not_found() {
+        assert!(extract_section(CHANGELOG, SemVer::new(0, 1, 0)).is_none());
+        assert!(extract_section(CHANGELOG, SemVer::new(0, 3, 0)).is_none());
+    }
+
+    #[test]
+    fn test_extract_section_keeps_comments() {
+        let changelog = "## 1.0.0\n\n<!-- highlights -->\n- First\n\n## 0.9.0\n\n- Beta\n";
+        assert_eq!(
+            extract_section(changelog, SemVer::new(1, 0, 0)).unwrap(),
+            "<!-- highlights -->\n- First"
+        );
+    }
+}
@@ -1,76 +1,1097 @@
-use crate::sem_ver::SemVerComponent;
 use std::str::FromStr;
+use yart::effective_config::{ConfigFormat, EffectiveConfig, Source, Value};
+use yart::logger::LogLevel;
+use yart::plan::PlanFormat;
+use yart::progress::ProgressFormat;
+use yart::sem_ver::{SemVer, SemVerComponent};
+use yart::updater::FileKind;
 extern crate clap;
-use clap::{App, Arg};
+use clap::{App, Arg, ArgMatches, ErrorKind, ValueSource};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Specifies how the next version is determined.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TargetVersion {
+    /// Bump the given component of the current version.
+    Bump(SemVerComponent),
+
+    /// Use the given version as-is.
+    Explicit(SemVer),
+}
+
+/// The options of the `bump-file` subcommand.
+pub struct BumpFileOptions {
+    pub kind: FileKind,
+    pub version: TargetVersion,
+    pub on_overflow: OnOverflow,
+}
+
+/// What happens when a bumped component would exceed its maximum.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OnOverflow {
+    /// Abort the release.
+    Error,
+
+    /// Keep the current version, with a warning.
+    Saturate,
+}
+
+/// Where the current version is read from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TagSource {
+    /// The biggest of all tags.
+    Tags,
+
+    /// The most recent tag reachable from HEAD, according to `git describe`.
+    Describe,
+}
 
 pub struct CliOptions {
-    pub version: SemVerComponent,
+    /// Always present, unless `--validate-only` was given.
+    pub version: Option<TargetVersion>,
+    pub allow_same_version: bool,
+    pub on_overflow: OnOverflow,
     pub dir: String,
-    pub message: String,
+    pub message: Option<String>,
+    pub message_prefix: Option<String>,
+    pub commit_trailers: Vec<String>,
+    pub signoff: bool,
+    pub date_format: String,
     pub dry_run: bool,
+    pub output_dir: Option<PathBuf>,
+    pub verify: bool,
+    pub no_color: bool,
     pub no_push: bool,
-    pub verbose: bool,
+    pub no_push_tags: bool,
+    pub push_retries: u32,
+    pub lightweight: bool,
+    pub max_tag_message_len: Option<usize>,
+    pub tag_from_changelog: bool,
+    pub tag_body_file: Option<PathBuf>,
+    pub require_changes: bool,
+    pub strict: bool,
+    pub keep_going: bool,
+    pub skip_if_no_commits: bool,
+    pub check: bool,
+    /// The Conventional Commit types that make a release due for `--check` (empty for any commit).
+    pub check_types: Vec<String>,
+    pub branch: Option<String>,
+    pub pre_commit_hook: Option<String>,
+    pub post_tag_hook: Option<String>,
+    pub tag_only: bool,
+    pub next_dev_version: Option<SemVerComponent>,
+    pub plan_only: bool,
+    pub validate_only: bool,
+    pub plan_format: PlanFormat,
+    pub bump_version_code: bool,
+    pub vb6_auto_revision: bool,
+    pub force: bool,
+    pub stamp_date: bool,
+    pub build_counter: bool,
+    pub build_number: Option<u64>,
+    pub maven_property: Option<String>,
+    pub update_scm_tag: bool,
+    pub update_values_image_tag: bool,
+    pub values_files: Vec<PathBuf>,
+    pub skip_unpublished: bool,
+    pub max_depth: usize,
+    pub resume: bool,
+    pub list_changed: bool,
+    pub null_separated: bool,
+    pub stdin_tags: bool,
+    pub source: TagSource,
+    pub no_git: bool,
+    pub lenient_tags: bool,
+    pub files: Vec<PathBuf>,
+    pub file_kind: Option<FileKind>,
+    pub commit_extra_paths_from: Option<PathBuf>,
+    pub log_level: LogLevel,
+    pub progress: Option<ProgressFormat>,
+    #[cfg(feature = "github")]
+    pub github_release: bool,
+    /// Present when the `bump-file` subcommand is given.
+    pub bump_file: Option<BumpFileOptions>,
+    pub dump_config: Option<ConfigFormat>,
+    /// The resolved values of the options, printed by `--dump-config`.
+    pub effective_config: EffectiveConfig,
 }
 
 impl CliOptions {
     pub fn parse() -> Self {
-        let matches = App::new("yart")
-            .version("0.1.0")
-            .author("Nikolaos Georgiou <nikolaos.georgiou@gmail.com>")
-            .about("Yet another release tool")
-            .arg(
-                Arg::new("version")
-                    .short('v')
-                    .help("Specify the target SemVer version")
-                    .required(true)
-                    .takes_value(true)
-                    .possible_value("major")
-                    .possible_value("minor")
-                    .possible_value("patch"),
-            )
-            .arg(
-                Arg::new("dir")
-                    .long("dir")
-                    .help("The working directory of the git repository")
-                    .required(false)
-                    .default_value(".")
-                    .takes_value(true),
-            )
-            .arg(
-                Arg::new("message")
-                    .short('m')
-                    .long("message")
-                    .help("A custom message for the git commit")
-                    .required(false)
-                    .default_value("")
-                    .takes_value(true),
-            )
-            .arg(
-                Arg::new("dry-run")
-                    .long("dry-run")
-                    .help("Do not actually modify anything")
-                    .required(false),
-            )
-            .arg(
-                Arg::new("no-push")
-                    .long("no-push")
-                    .help("Do not push changes to the remote repository")
-                    .required(false),
-            )
-            .arg(
-                Arg::new("verbose")
-                    .long("verbose")
-                    .help("Increase logging verbosity")
-                    .required(false),
-            )
-            .get_matches();
-        Self {
-            version: SemVerComponent::from_str(matches.value_of("version").unwrap()).unwrap(),
+        Self::try_parse_from(std::env::args_os()).unwrap_or_else(|err| err.exit())
+    }
+
+    fn try_parse_from<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = app().try_get_matches_from(args)?;
+        let version = resolve_target_version(&matches)?;
+        let bump_file = match matches.subcommand_matches("bump-file") {
+            Some(bump_file_matches) => Some(BumpFileOptions {
+                kind: FileKind::from_str(bump_file_matches.value_of("as").unwrap()).unwrap(),
+                version: resolve_target_version(bump_file_matches)?
+                    .expect("clap requires -v unless --set-version is present"),
+                on_overflow: resolve_on_overflow(bump_file_matches),
+            }),
+            None => None,
+        };
+        Ok(Self {
+            version,
+            allow_same_version: matches.is_present("allow-same-version"),
+            on_overflow: resolve_on_overflow(&matches),
             dir: matches.value_of("dir").unwrap().to_string(),
-            message: matches.value_of("message").unwrap().to_string(),
+            message: matches.value_of("message").map(str::to_owned),
+            message_prefix: matches.value_of("message-prefix").map(str::to_owned),
+            date_format: matches.value_of("date-format").unwrap().to_string(),
             dry_run: matches.is_present("dry-run"),
+            output_dir: matches.value_of("output-dir").map(PathBuf::from),
+            verify: matches.is_present("verify"),
+            no_color: matches.is_present("no-color"),
             no_push: matches.is_present("no-push"),
-            verbose: matches.is_present("verbose"),
+            no_push_tags: matches.is_present("no-push-tags"),
+            push_retries: u32::from_str(matches.value_of("push-retries").unwrap()).unwrap(),
+            lightweight: matches.is_present("lightweight"),
+            max_tag_message_len: matches
+                .value_of("max-tag-message-len")
+                .map(|value| usize::from_str(value).unwrap()),
+            tag_from_changelog: matches.is_present("tag-from-changelog"),
+            tag_body_file: matches.value_of("tag-body-file").map(PathBuf::from),
+            strict: matches.is_present("strict"),
+            keep_going: matches.is_present("keep-going"),
+            skip_if_no_commits: matches.is_present("skip-if-no-commits"),
+            check: matches.is_present("check"),
+            check_types: matches
+                .values_of("check-types")
+                .map(|values| values.map(str::to_owned).collect())
+                .unwrap_or_default(),
+            branch: matches.value_of("branch").map(str::to_owned),
+            pre_commit_hook: matches.value_of("pre-commit-hook").map(str::to_owned),
+            post_tag_hook: matches.value_of("post-tag-hook").map(str::to_owned),
+            tag_only: matches.is_present("tag-only"),
+            plan_only: matches.is_present("plan-only"),
+            validate_only: matches.is_present("validate-only"),
+            plan_format: PlanFormat::from_str(matches.value_of("plan-format").unwrap()).unwrap(),
+            next_dev_version: matches
+                .value_of("next-dev-version")
+                .map(|component| SemVerComponent::from_str(component).unwrap()),
+            require_changes: matches.is_present("require-changes"),
+            bump_version_code: matches.is_present("bump-version-code"),
+            vb6_auto_revision: matches.is_present("vb6-auto-revision"),
+            force: matches.is_present("force"),
+            stamp_date: matches.is_present("stamp-date"),
+            build_counter: matches.is_present("build-counter"),
+            build_number: matches
+                .value_of("build-number")
+                .map(|value| u64::from_str(value).unwrap()),
+            maven_property: matches.value_of("maven-property").map(str::to_owned),
+            update_scm_tag: matches.is_present("update-scm-tag"),
+            update_values_image_tag: matches.is_present("update-values-image-tag"),
+            values_files: matches
+                .values_of("values-file")
+                .map(|values| values.map(PathBuf::from).collect())
+                .unwrap_or_default(),
+            skip_unpublished: matches.is_present("skip-unpublished"),
+            max_depth: usize::from_str(matches.value_of("max-depth").unwrap()).unwrap(),
+            resume: matches.is_present("resume"),
+            list_changed: matches.is_present("list-changed"),
+            null_separated: matches.is_present("null"),
+            stdin_tags: matches.is_present("stdin-tags"),
+            source: if matches.value_of("source") == Some("describe") {
+                TagSource::Describe
+            } else {
+                TagSource::Tags
+            },
+            no_git: matches.is_present("no-git"),
+            lenient_tags: matches.is_present("lenient-tags"),
+            files: matches
+                .values_of("file")
+                .map(|values| values.map(PathBuf::from).collect())
+                .unwrap_or_default(),
+            commit_trailers: matches
+                .values_of("commit-trailer")
+                .map(|values| values.map(str::to_owned).collect())
+                .unwrap_or_default(),
+            signoff: matches.is_present("signoff"),
+            file_kind: matches
+                .value_of("as")
+                .map(|value| FileKind::from_str(value).unwrap()),
+            commit_extra_paths_from: matches
+                .value_of("commit-extra-paths-from")
+                .map(PathBuf::from),
+            // --verbose is a shortcut for the most detailed level
+            log_level: if matches.is_present("verbose") {
+                LogLevel::Debug
+            } else {
+                LogLevel::from_str(matches.value_of("log-level").unwrap()).unwrap()
+            },
+            progress: matches
+                .value_of("progress")
+                .map(|format| ProgressFormat::from_str(format).unwrap()),
+            #[cfg(feature = "github")]
+            github_release: matches.is_present("github-release"),
+            bump_file,
+            dump_config: matches
+                .value_of("dump-config")
+                .map(|format| ConfigFormat::from_str(format).unwrap()),
+            effective_config: effective_config(&matches),
+        })
+    }
+}
+
+fn resolve_on_overflow(matches: &ArgMatches) -> OnOverflow {
+    if matches.value_of("on-overflow") == Some("saturate") {
+        OnOverflow::Saturate
+    } else {
+        OnOverflow::Error
+    }
+}
+
+/// Checks that a trailer has the `Key: value` form of git trailers.
+fn validate_trailer(trailer: &str) -> Result<(), String> {
+    match trailer.split_once(':') {
+        Some((key, value))
+            if !key.is_empty()
+                && !value.trim().is_empty()
+                && key
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '-') =>
+        {
+            Ok(())
+        }
+        _ => Err(format!(
+            "expected a trailer like \"Key: value\", found {}",
+            trailer
+        )),
+    }
+}
+
+/// Collects the value of every option along with where it comes from.
+fn effective_config(matches: &ArgMatches) -> EffectiveConfig {
+    let mut result = EffectiveConfig::default();
+    for arg in app().get_arguments() {
+        let name = arg.get_id();
+        // every option has an environment variable, unlike --help and --version
+        if name == "dump-config" || arg.get_env().is_none() {
+            continue;
         }
+        let source = match matches.value_source(name) {
+            Some(ValueSource::CommandLine) => Source::CommandLine,
+            Some(ValueSource::EnvVariable) => Source::Env,
+            _ => Source::Default,
+        };
+        let value = if !arg.is_takes_value_set() {
+            Some(Value::Bool(matches.is_present(name)))
+        } else if arg.is_multiple_occurrences_set() {
+            Some(Value::List(
+                matches
+                    .values_of(name)
+                    .map(|values| values.map(str::to_owned).collect())
+                    .unwrap_or_default(),
+            ))
+        } else {
+            matches
+                .value_of(name)
+                .map(|value| Value::String(value.to_owned()))
+        };
+        result.push(name, value, source);
+    }
+    result
+}
+
+/// Resolves the target version from `-v` and `--set-version`.
+///
+/// Only one of them may be given. If one comes from the command line
+/// and the other from an environment variable, the command line wins.
+fn resolve_target_version(matches: &ArgMatches) -> Result<Option<TargetVersion>, clap::Error> {
+    let bump = matches.value_of("version").map(|value| {
+        let component = SemVerComponent::from_str(value).unwrap();
+        (
+            TargetVersion::Bump(component),
+            matches.value_source("version"),
+        )
+    });
+    let explicit = matches.value_of("set-version").map(|value| {
+        let version = SemVer::from_str(value).unwrap();
+        (
+            TargetVersion::Explicit(version),
+            matches.value_source("set-version"),
+        )
+    });
+    match (bump, explicit) {
+        (Some((bump, bump_source)), Some((explicit, explicit_source))) => {
+            if bump_source == explicit_source {
+                Err(app().error(
+                    ErrorKind::ArgumentConflict,
+                    "-v and --set-version cannot be used together",
+                ))
+            } else if bump_source == Some(ValueSource::CommandLine) {
+                Ok(Some(bump))
+            } else {
+                Ok(Some(explicit))
+            }
+        }
+        (Some((bump, _)), None) => Ok(Some(bump)),
+        (None, Some((explicit, _))) => Ok(Some(explicit)),
+        // clap requires -v unless --set-version, --validate-only or bump-file is present
+        (None, None) => Ok(None),
+    }
+}
+
+/// Builds the command line interface.
+///
+/// Every option can also be given with a `YART_*` environment variable
+/// (e.g. `YART_BUMP=minor` instead of `-v minor`).
+/// Flags given on the command line take precedence over environment variables.
+fn app() -> App<'static> {
+    let app = App::new("yart")
+        .version("0.1.0")
+        .author("Nikolaos Georgiou <nikolaos.georgiou@gmail.com>")
+        .about("Yet another release tool")
+        // the options of a release are not required by bump-file
+        .subcommand_negates_reqs(true)
+        .subcommand(bump_file_app())
+        .arg(
+            Arg::new("version")
+                .short('v')
+                .help("Specify the target SemVer version")
+                .env("YART_BUMP")
+                .required_unless_present_any(["set-version", "validate-only", "dump-config", "check"])
+                .takes_value(true)
+                .possible_value("major")
+                .possible_value("minor")
+                .possible_value("patch"),
+        )
+        .arg(
+            Arg::new("set-version")
+                .long("set-version")
+                .help("Use an explicit SemVer version instead of bumping a component")
+                .env("YART_SET_VERSION")
+                .required(false)
+                .takes_value(true)
+                .validator(SemVer::from_str),
+        )
+        .arg(
+            Arg::new("allow-same-version")
+                .long("allow-same-version")
+                .help("Allow --set-version to re-release the current version")
+                .env("YART_ALLOW_SAME_VERSION")
+                .required(false)
+                .requires("set-version"),
+        )
+        .arg(
+            Arg::new("on-overflow")
+                .long("on-overflow")
+                .help("What to do if -v would bump a component past 65535")
+                .env("YART_ON_OVERFLOW")
+                .required(false)
+                .takes_value(true)
+                .possible_value("error")
+                .possible_value("saturate")
+                .default_value("error"),
+        )
+        .arg(
+            Arg::new("dir")
+                .long("dir")
+                .help("The working directory of the git repository")
+                .env("YART_DIR")
+                .required(false)
+                .default_value(".")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("message")
+                .short('m')
+                .long("message")
+                .help("The complete message of the git commit and tag ({version} is replaced with the new version)")
+                .env("YART_MESSAGE")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("message-prefix")
+                .long("message-prefix")
+                .help("The message of the git commit and tag, followed by the new version (default: \"Releasing version\")")
+                .env("YART_MESSAGE_PREFIX")
+                .required(false)
+                .takes_value(true)
+                .conflicts_with("message"),
+        )
+        .arg(
+            Arg::new("commit-trailer")
+                .long("commit-trailer")
+                .help("Add a trailer (e.g. \"Release-Version: {version}\") to the commit message (repeatable)")
+                .env("YART_COMMIT_TRAILER")
+                .required(false)
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .validator(validate_trailer),
+        )
+        .arg(
+            Arg::new("signoff")
+                .long("signoff")
+                .help("Add a Signed-off-by trailer with the user.name and user.email of git config")
+                .env("YART_SIGNOFF")
+                .required(false),
+        )
+        .arg(
+            Arg::new("date-format")
+                .long("date-format")
+                .help("The strftime-like format of the {date} placeholder")
+                .env("YART_DATE_FORMAT")
+                .required(false)
+                .default_value(yart::date::DEFAULT_DATE_FORMAT)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Do not actually modify anything")
+                .env("YART_DRY_RUN")
+                .required(false),
+        )
+        .arg(
+            Arg::new("output-dir")
+                .long("output-dir")
+                .help("In dry-run, also write the changed files under this directory, keeping their relative paths")
+                .env("YART_OUTPUT_DIR")
+                .required(false)
+                .takes_value(true)
+                .requires("dry-run"),
+        )
+        .arg(
+            Arg::new("verify")
+                .long("verify")
+                .help("Check that the written files indicate the new version before committing")
+                .env("YART_VERIFY")
+                .required(false)
+                .conflicts_with("dry-run"),
+        )
+        .arg(
+            Arg::new("no-color")
+                .long("no-color")
+                .help("Do not color the changes printed in dry-run")
+                .env("YART_NO_COLOR")
+                .required(false),
+        )
+        .arg(
+            Arg::new("no-push")
+                .long("no-push")
+                .help("Do not push changes to the remote repository")
+                .env("YART_NO_PUSH")
+                .required(false),
+        )
+        .arg(
+            Arg::new("no-push-tags")
+                .long("no-push-tags")
+                .help("Push the release commit, but keep the tag local")
+                .env("YART_NO_PUSH_TAGS")
+                .required(false)
+                .conflicts_with("no-push"),
+        )
+        .arg(
+            Arg::new("push-retries")
+                .long("push-retries")
+                .help("How many times to pull, rebase and push again if pushing fails")
+                .env("YART_PUSH_RETRIES")
+                .required(false)
+                .default_value("0")
+                .takes_value(true)
+                .validator(u32::from_str),
+        )
+        .arg(
+            Arg::new("lightweight")
+                .long("lightweight")
+                .help("Create a lightweight tag instead of an annotated one")
+                .env("YART_LIGHTWEIGHT")
+                .required(false),
+        )
+        .arg(
+            Arg::new("max-tag-message-len")
+                .long("max-tag-message-len")
+                .help("Truncate the tag message to this many bytes, ending it with an ellipsis")
+                .env("YART_MAX_TAG_MESSAGE_LEN")
+                .required(false)
+                .takes_value(true)
+                .validator(usize::from_str)
+                .conflicts_with("lightweight"),
+        )
+        .arg(
+            Arg::new("tag-from-changelog")
+                .long("tag-from-changelog")
+                .help("Use the section of the new version in CHANGELOG.md as the tag message")
+                .env("YART_TAG_FROM_CHANGELOG")
+                .required(false)
+                .conflicts_with("lightweight"),
+        )
+        .arg(
+            Arg::new("tag-body-file")
+                .long("tag-body-file")
+                .help("Append the contents of the given file to the tag message, keeping its formatting")
+                .env("YART_TAG_BODY_FILE")
+                .required(false)
+                .takes_value(true)
+                .conflicts_with_all(&["lightweight", "tag-from-changelog"]),
+        )
+        .arg(
+            Arg::new("require-changes")
+                .long("require-changes")
+                .help("Abort the release if no project files were changed")
+                .env("YART_REQUIRE_CHANGES")
+                .required(false),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .help("Abort the release if a known manifest (e.g. package.json) would not be updated")
+                .env("YART_STRICT")
+                .required(false)
+                .conflicts_with_all(&["file", "resume"]),
+        )
+        .arg(
+            Arg::new("keep-going")
+                .long("keep-going")
+                .help("Keep updating the other files when one of them fails, then report the failures")
+                .env("YART_KEEP_GOING")
+                .required(false)
+                .conflicts_with("tag-only"),
+        )
+        .arg(
+            Arg::new("skip-if-no-commits")
+                .long("skip-if-no-commits")
+                .help("Exit without releasing if there are no commits since the latest tag")
+                .env("YART_SKIP_IF_NO_COMMITS")
+                .required(false)
+                .conflicts_with_all(&["stdin-tags", "no-git"]),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .help("Exit with 0 if a release is due (there are commits since the latest tag) and 3 if not, without changing anything")
+                .env("YART_CHECK")
+                .required(false)
+                .conflicts_with_all(&["stdin-tags", "no-git", "validate-only", "dump-config"]),
+        )
+        .arg(
+            Arg::new("check-types")
+                .long("check-types")
+                .help("Only Conventional Commits of these types (e.g. feat,fix) make a release due for --check")
+                .env("YART_CHECK_TYPES")
+                .required(false)
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .use_value_delimiter(true)
+                .requires("check"),
+        )
+        .arg(
+            Arg::new("branch")
+                .long("branch")
+                .help("Abort the release unless the current branch is this one (@default for the default branch of origin)")
+                .env("YART_BRANCH")
+                .required(false)
+                .takes_value(true)
+                .conflicts_with("no-git"),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("Release even if the branch is behind its upstream")
+                .env("YART_FORCE")
+                .required(false)
+                .conflicts_with("no-git"),
+        )
+        .arg(
+            Arg::new("pre-commit-hook")
+                .long("pre-commit-hook")
+                .help("Run this command before the release commit and commit the files it changes")
+                .env("YART_PRE_COMMIT_HOOK")
+                .required(false)
+                .takes_value(true)
+                .conflicts_with_all(&["tag-only", "no-git"]),
+        )
+        .arg(
+            Arg::new("post-tag-hook")
+                .long("post-tag-hook")
+                .help("Run this command after the release is tagged")
+                .env("YART_POST_TAG_HOOK")
+                .required(false)
+                .takes_value(true)
+                .conflicts_with("no-git"),
+        )
+        .arg(
+            Arg::new("tag-only")
+                .long("tag-only")
+                .help("Tag the current HEAD with the next version, without reading or changing any files")
+                .env("YART_TAG_ONLY")
+                .required(false)
+                .conflicts_with_all(&[
+                    "file",
+                    "strict",
+                    "resume",
+                    "verify",
+                    "require-changes",
+                    "stamp-date",
+                    "build-counter",
+                    "commit-extra-paths-from",
+                    "no-git",
+                ]),
+        )
+        .arg(
+            Arg::new("next-dev-version")
+                .long("next-dev-version")
+                .help("After tagging, commit the next -SNAPSHOT version of sbt builds, bumping the given component (default: minor)")
+                .env("YART_NEXT_DEV_VERSION")
+                .required(false)
+                .takes_value(true)
+                .min_values(0)
+                .default_missing_value("minor")
+                .possible_value("major")
+                .possible_value("minor")
+                .possible_value("patch")
+                .conflicts_with_all(&["tag-only", "no-git"]),
+        )
+        .arg(
+            Arg::new("plan-only")
+                .long("plan-only")
+                .help("Print the plan of the release (versions, tag, files that would change) to stdout and stop")
+                .env("YART_PLAN_ONLY")
+                .required(false)
+                .conflicts_with_all(&["tag-only", "resume", "list-changed"]),
+        )
+        .arg(
+            Arg::new("validate-only")
+                .long("validate-only")
+                .help("Check that all files indicate the same version and stop, without releasing")
+                .env("YART_VALIDATE_ONLY")
+                .required(false)
+                .conflicts_with_all(&["tag-only", "plan-only", "resume", "list-changed"]),
+        )
+        .arg(
+            Arg::new("plan-format")
+                .long("plan-format")
+                .help("The format of the plan printed by --plan-only")
+                .env("YART_PLAN_FORMAT")
+                .required(false)
+                .takes_value(true)
+                .possible_values(PlanFormat::NAMES)
+                .default_value("text"),
+        )
+        .arg(
+            Arg::new("bump-version-code")
+                .long("bump-version-code")
+                .help("Increment the versionCode of Android apps")
+                .env("YART_BUMP_VERSION_CODE")
+                .required(false),
+        )
+        .arg(
+            Arg::new("vb6-auto-revision")
+                .long("vb6-auto-revision")
+                .help("Increment the RevisionVer of VB6 projects instead of setting it to the patch")
+                .env("YART_VB6_AUTO_REVISION")
+                .required(false),
+        )
+        .arg(
+            Arg::new("stamp-date")
+                .long("stamp-date")
+                .help("Write the current date into the build date file (default: BUILD_DATE)")
+                .env("YART_STAMP_DATE")
+                .required(false),
+        )
+        .arg(
+            Arg::new("build-counter")
+                .long("build-counter")
+                .help("Increment the build counter in .yart-build and use it for the {build} placeholder")
+                .env("YART_BUILD_COUNTER")
+                .required(false),
+        )
+        .arg(
+            Arg::new("build-number")
+                .long("build-number")
+                .help("Set the build number of Dart packages instead of incrementing it")
+                .env("YART_BUILD_NUMBER")
+                .required(false)
+                .takes_value(true)
+                .validator(u64::from_str),
+        )
+        .arg(
+            Arg::new("maven-property")
+                .long("maven-property")
+                .help("Update this property of pom.xml (e.g. revision) instead of the project version")
+                .env("YART_MAVEN_PROPERTY")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("update-scm-tag")
+                .long("update-scm-tag")
+                .help("Also set the scm tag of pom.xml to the new tag")
+                .env("YART_UPDATE_SCM_TAG")
+                .required(false),
+        )
+        .arg(
+            Arg::new("update-values-image-tag")
+                .long("update-values-image-tag")
+                .help("Also set the image tag of the values.yaml of Helm charts to the new version")
+                .env("YART_UPDATE_VALUES_IMAGE_TAG")
+                .required(false),
+        )
+        .arg(
+            Arg::new("values-file")
+                .long("values-file")
+                .help("Set the image tag of this values file instead of values.yaml (repeatable)")
+                .env("YART_VALUES_FILE")
+                .required(false)
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .requires("update-values-image-tag"),
+        )
+        .arg(
+            Arg::new("skip-unpublished")
+                .long("skip-unpublished")
+                .help("Do not change the version of crates with publish = false")
+                .env("YART_SKIP_UNPUBLISHED")
+                .required(false),
+        )
+        .arg(
+            Arg::new("max-depth")
+                .long("max-depth")
+                .help("How many folders deep to search for .csproj and .nuspec files (0: only the root folder)")
+                .env("YART_MAX_DEPTH")
+                .required(false)
+                .takes_value(true)
+                .default_value("0")
+                .validator(usize::from_str),
+        )
+        .arg(
+            Arg::new("resume")
+                .long("resume")
+                .help("Resume a release whose files were already committed, only tag and push")
+                .env("YART_RESUME")
+                .required(false),
+        )
+        .arg(
+            Arg::new("list-changed")
+                .long("list-changed")
+                .help("Print the changed files, one per line (in dry-run, the files that would change)")
+                .env("YART_LIST_CHANGED")
+                .required(false),
+        )
+        .arg(
+            Arg::new("null")
+                .short('0')
+                .help("Separate the files of --list-changed with NUL instead of newline")
+                .required(false)
+                .requires("list-changed"),
+        )
+        .arg(
+            Arg::new("stdin-tags")
+                .long("stdin-tags")
+                .help("Read the tags from stdin instead of git, to compute the next version")
+                .env("YART_STDIN_TAGS")
+                .required(false),
+        )
+        .arg(
+            Arg::new("source")
+                .long("source")
+                .help("Read the current version from the biggest tag or from the tag that git describe finds")
+                .env("YART_SOURCE")
+                .required(false)
+                .takes_value(true)
+                .possible_value("tags")
+                .possible_value("describe")
+                .default_value("tags")
+                .conflicts_with("stdin-tags"),
+        )
+        .arg(
+            Arg::new("no-git")
+                .long("no-git")
+                .help("Do not run git at all: update the files, but do not stage, commit, tag or push")
+                .env("YART_NO_GIT")
+                .required(false)
+                .requires("stdin-tags"),
+        )
+        .arg(
+            Arg::new("lenient-tags")
+                .long("lenient-tags")
+                .help("Accept tags like v1.2, v1.2.3.4 and v1.2.3- when finding the current version")
+                .env("YART_LENIENT_TAGS")
+                .required(false),
+        )
+        .arg(
+            Arg::new("file")
+                .long("file")
+                .help("Update only the given file instead of finding the supported files (repeatable)")
+                .env("YART_FILE")
+                .required(false)
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("commit-extra-paths-from")
+                .long("commit-extra-paths-from")
+                .help("Also commit the paths listed in the given file (one per line, relative to the repository root)")
+                .env("YART_COMMIT_EXTRA_PATHS_FROM")
+                .required(false)
+                .takes_value(true)
+                .conflicts_with("no-git"),
+        )
+        .arg(
+            Arg::new("as")
+                .long("as")
+                .help("The format of the files given with --file, instead of inferring it from their names")
+                .env("YART_AS")
+                .required(false)
+                .takes_value(true)
+                .possible_values(FileKind::NAMES)
+                .requires("file"),
+        )
+        .arg(
+            Arg::new("dump-config")
+                .long("dump-config")
+                .help("Print the effective configuration in the given format (default: toml) and exit")
+                .env("YART_DUMP_CONFIG")
+                .required(false)
+                .takes_value(true)
+                .min_values(0)
+                .default_missing_value("toml")
+                .possible_values(ConfigFormat::NAMES),
+        )
+        .arg(
+            Arg::new("verbose")
+                .long("verbose")
+                .help("Increase logging verbosity (same as --log-level debug)")
+                .env("YART_VERBOSE")
+                .required(false),
+        )
+        .arg(
+            Arg::new("log-level")
+                .long("log-level")
+                .help("The level of the messages printed to stderr")
+                .takes_value(true)
+                .possible_values(LogLevel::NAMES)
+                .default_value("info")
+                .env("YART_LOG_LEVEL")
+                .required(false),
+        )
+        .arg(
+            Arg::new("progress")
+                .long("progress")
+                .help("Write progress events to stderr in the given format, one per line")
+                .takes_value(true)
+                .possible_values(ProgressFormat::NAMES)
+                .env("YART_PROGRESS")
+                .required(false),
+        );
+    #[cfg(feature = "github")]
+    let app = app.arg(
+        Arg::new("github-release")
+            .long("github-release")
+            .help("Create a GitHub release for the new tag (requires GITHUB_TOKEN)")
+            .env("YART_GITHUB_RELEASE")
+            .required(false)
+            .conflicts_with_all(&["no-push", "no-push-tags"]),
+    );
+    app
+}
+
+/// Builds the `bump-file` subcommand, which updates a file read from stdin.
+fn bump_file_app() -> App<'static> {
+    App::new("bump-file")
+        .about("Read a file from stdin, update its version and write it to stdout")
+        .arg(
+            Arg::new("as")
+                .long("as")
+                .help("The format of the file")
+                .env("YART_AS")
+                .required(true)
+                .takes_value(true)
+                .possible_values(FileKind::NAMES),
+        )
+        .arg(
+            Arg::new("version")
+                .short('v')
+                .help("Bump this component of the version of the file")
+                .env("YART_BUMP")
+                .required_unless_present("set-version")
+                .takes_value(true)
+                .possible_value("major")
+                .possible_value("minor")
+                .possible_value("patch"),
+        )
+        .arg(
+            Arg::new("set-version")
+                .long("set-version")
+                .help("Use an explicit SemVer version instead of bumping a component")
+                .env("YART_SET_VERSION")
+                .required(false)
+                .takes_value(true)
+                .validator(SemVer::from_str),
+        )
+        .arg(
+            Arg::new("on-overflow")
+                .long("on-overflow")
+                .help("What to do if -v would bump a component past 65535")
+                .env("YART_ON_OVERFLOW")
+                .required(false)
+                .takes_value(true)
+                .possible_value("error")
+                .possible_value("saturate")
+                .default_value("error"),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::sync::{Mutex, MutexGuard};
+    use yart::effective_config::Setting;
+
+    fn parse_from(args: &[&str]) -> Result<CliOptions, clap::Error> {
+        let mut full_args = vec!["yart"];
+        full_args.extend_from_slice(args);
+        CliOptions::try_parse_from(full_args)
+    }
+
+    #[test]
+    fn test_validate_trailer() {
+        assert!(validate_trailer("Release-Version: {version}").is_ok());
+        assert!(validate_trailer("Reviewed-by:jane").is_ok());
+        assert!(validate_trailer("Release Version: 1.2.3").is_err());
+        assert!(validate_trailer("Release-Version:").is_err());
+        assert!(validate_trailer(": 1.2.3").is_err());
+        assert!(validate_trailer("no trailer").is_err());
+    }
+
+    /// Environment variables are shared between test threads, so every test that
+    /// parses options holds this lock, starting without the variables of the tests.
+    fn lock_env() -> MutexGuard<'static, ()> {
+        static ENV_LOCK: Mutex<()> = Mutex::new(());
+        let guard = ENV_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        for name in ["YART_BUMP", "YART_SET_VERSION", "YART_NO_PUSH", "YART_DIR"] {
+            env::remove_var(name);
+        }
+        guard
+    }
+
+    #[test]
+    fn test_version_is_required() {
+        let _env = lock_env();
+        assert!(parse_from(&[]).is_err(), "-v is required without YART_BUMP");
+        assert_eq!(parse_from(&["--validate-only"]).unwrap().version, None);
+        assert!(parse_from(&["-v", "major", "--set-version", "1.0.0"]).is_err());
+    }
+
+    #[test]
+    fn test_env_fallback_and_precedence() {
+        let _env = lock_env();
+
+        // env var is used when the flag is absent
+        env::set_var("YART_BUMP", "minor");
+        assert_eq!(
+            parse_from(&[]).unwrap().version,
+            Some(TargetVersion::Bump(SemVerComponent::Minor))
+        );
+
+        // flags take precedence
+        assert_eq!(
+            parse_from(&["-v", "major"]).unwrap().version,
+            Some(TargetVersion::Bump(SemVerComponent::Major))
+        );
+        assert_eq!(
+            parse_from(&["--set-version", "1.0.0"]).unwrap().version,
+            Some(TargetVersion::Explicit(SemVer::new(1, 0, 0)))
+        );
+
+        // conflicting env vars
+        env::set_var("YART_SET_VERSION", "1.0.0");
+        assert!(parse_from(&[]).is_err());
+        env::remove_var("YART_BUMP");
+        assert_eq!(
+            parse_from(&["-v", "patch"]).unwrap().version,
+            Some(TargetVersion::Bump(SemVerComponent::Patch))
+        );
+        env::remove_var("YART_SET_VERSION");
+    }
+
+    #[test]
+    fn test_env_fallback_of_other_options() {
+        let _env = lock_env();
+        env::set_var("YART_NO_PUSH", "true");
+        env::set_var("YART_DIR", "from-env");
+        let options = parse_from(&["-v", "patch", "--dir", "from-flag"]).unwrap();
+        assert!(options.no_push);
+        assert_eq!(options.dir, "from-flag");
+        assert_eq!(
+            options.effective_config.get("dir").unwrap(),
+            &Setting {
+                name: "dir".to_owned(),
+                value: Some(Value::String("from-flag".to_owned())),
+                source: Source::CommandLine,
+            }
+        );
+        let setting = options.effective_config.get("no-push").unwrap();
+        assert_eq!(setting.value, Some(Value::Bool(true)));
+        assert_eq!(setting.source, Source::Env);
+        let setting = options.effective_config.get("max-depth").unwrap();
+        assert_eq!(setting.value, Some(Value::String("0".to_owned())));
+        assert_eq!(setting.source, Source::Default);
+        env::set_var("YART_NO_PUSH", "false");
+        let options = parse_from(&["-v", "patch"]).unwrap();
+        assert!(!options.no_push);
+        assert_eq!(options.dir, "from-env");
+        env::remove_var("YART_NO_PUSH");
+        env::remove_var("YART_DIR");
+    }
+
+    #[test]
+    fn test_dump_config() {
+        let _env = lock_env();
+        assert_eq!(
+            parse_from(&["--dump-config"]).unwrap().dump_config,
+            Some(ConfigFormat::Toml)
+        );
+        assert!(parse_from(&["-v", "patch"]).unwrap().dump_config.is_none());
+    }
+
+    #[test]
+    fn test_check_types() {
+        let _env = lock_env();
+        let check = parse_from(&["--check", "--check-types", "feat,fix"]).unwrap();
+        assert!(check.check);
+        assert_eq!(check.check_types, vec!["feat", "fix"]);
+        assert!(parse_from(&["-v", "minor", "--check-types", "feat"]).is_err());
+    }
+
+    #[test]
+    fn test_message_and_message_prefix() {
+        let _env = lock_env();
+        // the complete message or a prefix of the version
+        let options = parse_from(&["-v", "patch", "--message", "chore: {version}"]).unwrap();
+        assert_eq!(options.message.as_deref(), Some("chore: {version}"));
+        assert_eq!(options.message_prefix, None);
+        let options = parse_from(&["-v", "patch", "--message-prefix", "Release"]).unwrap();
+        assert_eq!(options.message, None);
+        assert_eq!(options.message_prefix.as_deref(), Some("Release"));
+        assert!(parse_from(&["-v", "patch", "-m", "a", "--message-prefix", "b"]).is_err());
+    }
+
+    #[test]
+    fn test_bump_file() {
+        let _env = lock_env();
+        // bump-file does not need the options of a release
+        let bump_file = parse_from(&["bump-file", "--as", "cargo", "--set-version", "1.0.0"])
+            .unwrap()
+            .bump_file
+            .unwrap();
+        assert_eq!(bump_file.kind, FileKind::Cargo);
+        assert_eq!(
+            bump_file.version,
+            TargetVersion::Explicit(SemVer::new(1, 0, 0))
+        );
+        assert!(parse_from(&["bump-file", "--as", "cargo"]).is_err());
+        assert!(parse_from(&["-v", "patch"]).unwrap().bump_file.is_none());
     }
 }
@@ -0,0 +1,104 @@
+//! Handles Clojure projects built with Boot (build.boot)
+use crate::files::{ContentProcessor, FileFinder};
+use crate::sem_ver::ExtractedVersion;
+use crate::SemVer;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Updates the version of `build.boot` files, which is conventionally
+/// defined as `(def +version+ "1.2.3")` and used by the `task-options!`.
+pub struct BootUpdater {}
+
+impl FileFinder for BootUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let path = PathBuf::from(dir).join("build.boot");
+        if path.is_file() {
+            Ok(vec![path])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+impl ContentProcessor for BootUpdater {
+    type Err = std::io::Error; // does not really throw error
+
+    /// Updates the first `+version+` definition, leaving the rest of the file as is.
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        let mut result = String::new();
+        let mut found = false;
+        for line in old_contents.split_inclusive('\n') {
+            match find_version(line).filter(|_| !found) {
+                Some((start, end)) => {
+                    found = true;
+                    result.push_str(&line[..start]);
+                    result.push_str(&version.to_string());
+                    result.push_str(&line[end..]);
+                }
+                None => result.push_str(line),
+            }
+        }
+        Ok(result)
+    }
+
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        contents
+            .lines()
+            .find_map(|line| {
+                find_version(line).and_then(|(start, end)| SemVer::from_str(&line[start..end]).ok())
+            })
+            .map(ExtractedVersion::from)
+    }
+}
+
+/// Finds the range of the version string (without the quotes)
+/// of a `(def +version+ "...")` line.
+fn find_version(line: &str) -> Option<(usize, usize)> {
+    let rest = line.trim_start().strip_prefix("(def")?;
+    let rest = rest
+        .strip_prefix(char::is_whitespace)?
+        .trim_start()
+        .strip_prefix("+version+")?;
+    let rest = rest
+        .strip_prefix(char::is_whitespace)?
+        .trim_start()
+        .strip_prefix('"')?;
+    let start = line.len() - rest.len();
+    let end = start + rest.find('"')?;
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process() {
+        let input = r#"(set-env!
+ :source-paths #{"src"}
+ :dependencies '[[org.clojure/clojure "1.10.1"]
+                 [adzerk/bootlaces "0.2.0" :scope "test"]])
+
+(def +version+ "0.1.0")
+
+(task-options!
+ pom {:project 'my/lib
+      :version +version+})
+"#;
+        let expected = input.replace("\"0.1.0\"", "\"0.2.0\"");
+        let result = BootUpdater {}.process(input, SemVer::new(0, 2, 0)).unwrap();
+        assert_eq!(result, expected);
+        assert_eq!(
+            BootUpdater {}.extract_version(input),
+            Some(SemVer::new(0, 1, 0).into())
+        );
+    }
+
+    #[test]
+    fn test_process_other_defs() {
+        let input = "(def +version-suffix+ \"1.0.0\")\r\n(def  +version+  \"1.0.0-SNAPSHOT\") ; the version\r\n(def +version+ \"9.9.9\")\r\n";
+        let expected = "(def +version-suffix+ \"1.0.0\")\r\n(def  +version+  \"1.1.0\") ; the version\r\n(def +version+ \"9.9.9\")\r\n";
+        let result = BootUpdater {}.process(input, SemVer::new(1, 1, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+}
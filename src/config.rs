@@ -0,0 +1,437 @@
+//! Reads the optional `.yart.toml` configuration file
+//!
+//! Only a small subset of TOML is supported:
+//!
+//! ```toml
+//! build_date_file = "BUILD_DATE"
+//! ignore_dirs = ["vendor", "dist"]
+//!
+//! [cargo]
+//! extra_keys = ["package.metadata.docs.version"]
+//!
+//! [[generic]]
+//! file = "app.json"
+//! json_path = "build.version"
+//! ```
+
+use crate::toml_util::strip_toml_comment;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+/// The name of the configuration file, expected at the root directory.
+pub const CONFIG_FILE_NAME: &str = ".yart.toml";
+
+/// The contents of the configuration file.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Config {
+    /// Files that are updated at a configured location.
+    pub generic: Vec<GenericEntry>,
+
+    /// The file that gets the date of the release with `--stamp-date`,
+    /// relative to the root directory.
+    pub build_date_file: Option<String>,
+
+    /// Dotted paths of `Cargo.toml` that are updated
+    /// in addition to the version of `[package]`.
+    pub cargo_extra_keys: Vec<String>,
+
+    /// Folder names that are skipped when searching sub-folders,
+    /// in addition to `.git`, `target` and `node_modules`.
+    pub ignore_dirs: Vec<String>,
+}
+
+/// A file that is updated at a configured location.
+#[derive(Debug, Eq, PartialEq)]
+pub struct GenericEntry {
+    /// The file path, relative to the root directory.
+    pub file: String,
+
+    /// The location of the version within the file.
+    pub location: GenericLocation,
+}
+
+/// The location of the version within a generic file.
+#[derive(Debug, Eq, PartialEq)]
+pub enum GenericLocation {
+    /// A dotted path to a JSON value (e.g. `build.version`).
+    JsonPath(String),
+
+    /// A dotted path to a YAML value (e.g. `image.tag`).
+    YamlPath(String),
+
+    /// A dotted path to a TOML value (e.g. `tool.poetry.version`).
+    TomlPath(String),
+
+    /// Text that is immediately followed by the version
+    /// (e.g. `Current version:` or a badge URL prefix).
+    Marker(String),
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    IOError(std::io::Error),
+    Invalid(usize, String),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IOError(e) => std::fmt::Display::fmt(&e, f),
+            Self::Invalid(line, message) => {
+                write!(f, "{} line {}: {}", CONFIG_FILE_NAME, line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(io_error: std::io::Error) -> Self {
+        Self::IOError(io_error)
+    }
+}
+
+/// Loads the configuration file from the given directory.
+/// If the file does not exist, the default (empty) configuration is returned.
+pub fn load<P: AsRef<Path>>(dir: P) -> Result<Config, ConfigError> {
+    let path = dir.as_ref().join(CONFIG_FILE_NAME);
+    if path.is_file() {
+        parse(&fs::read_to_string(path)?)
+    } else {
+        Ok(Config::default())
+    }
+}
+
+/// The table that the parser is currently in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ConfigState {
+    Root,
+    Cargo,
+    Generic,
+}
+
+#[derive(Default)]
+struct GenericEntryBuilder {
+    line: usize,
+    file: Option<String>,
+    location: Option<GenericLocation>,
+}
+
+impl GenericEntryBuilder {
+    fn build(self) -> Result<GenericEntry, ConfigError> {
+        match (self.file, self.location) {
+            (Some(file), Some(location)) => Ok(GenericEntry { file, location }),
+            (None, _) => Err(ConfigError::Invalid(
+                self.line,
+                "generic entry is missing the file key".to_owned(),
+            )),
+            (_, None) => Err(ConfigError::Invalid(
+                self.line,
+                "generic entry is missing the version location (json_path, yaml_path, toml_path or marker)"
+                    .to_owned(),
+            )),
+        }
+    }
+}
+
+pub fn parse(contents: &str) -> Result<Config, ConfigError> {
+    let mut state = ConfigState::Root;
+    let mut generic_builders = Vec::<GenericEntryBuilder>::new();
+    let mut build_date_file: Option<String> = None;
+    let mut cargo_extra_keys = Vec::<String>::new();
+    let mut ignore_dirs = Vec::<String>::new();
+    for (index, raw_line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = strip_toml_comment(raw_line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            if line == "[[generic]]" {
+                state = ConfigState::Generic;
+                generic_builders.push(GenericEntryBuilder {
+                    line: line_number,
+                    ..GenericEntryBuilder::default()
+                });
+                continue;
+            }
+            if line == "[cargo]" {
+                state = ConfigState::Cargo;
+                continue;
+            }
+            return Err(ConfigError::Invalid(
+                line_number,
+                format!("unsupported section {}", line),
+            ));
+        }
+        let (key, value) = split_key_value(line)
+            .ok_or_else(|| ConfigError::Invalid(line_number, "expected key = value".to_owned()))?;
+        match (state, generic_builders.last_mut()) {
+            (ConfigState::Generic, Some(builder)) => match key {
+                "file" => builder.file = Some(parse_string(line_number, value)?),
+                "json_path" => {
+                    builder.location =
+                        Some(GenericLocation::JsonPath(parse_string(line_number, value)?))
+                }
+                "yaml_path" => {
+                    builder.location =
+                        Some(GenericLocation::YamlPath(parse_string(line_number, value)?))
+                }
+                "toml_path" => {
+                    builder.location =
+                        Some(GenericLocation::TomlPath(parse_string(line_number, value)?))
+                }
+                "marker" => {
+                    let marker = parse_string(line_number, value)?;
+                    if marker.is_empty() {
+                        return Err(ConfigError::Invalid(
+                            line_number,
+                            "marker must not be empty".to_owned(),
+                        ));
+                    }
+                    builder.location = Some(GenericLocation::Marker(marker))
+                }
+                _ => return Err(unknown_key(line_number, key)),
+            },
+            (ConfigState::Cargo, _) if key == "extra_keys" => {
+                cargo_extra_keys = parse_string_array(line_number, value)?
+            }
+            (ConfigState::Root, _) if key == "build_date_file" => {
+                build_date_file = Some(parse_string(line_number, value)?)
+            }
+            (ConfigState::Root, _) if key == "ignore_dirs" => {
+                ignore_dirs = parse_string_array(line_number, value)?
+            }
+            _ => return Err(unknown_key(line_number, key)),
+        }
+    }
+    let generic = generic_builders
+        .into_iter()
+        .map(GenericEntryBuilder::build)
+        .collect::<Result<Vec<GenericEntry>, ConfigError>>()?;
+    Ok(Config {
+        generic,
+        build_date_file,
+        cargo_extra_keys,
+        ignore_dirs,
+    })
+}
+
+fn unknown_key(line_number: usize, key: &str) -> ConfigError {
+    ConfigError::Invalid(line_number, format!("unknown key {}", key))
+}
+
+fn split_key_value(line: &str) -> Option<(&str, &str)> {
+    let idx = line.find('=')?;
+    let (key, value) = line.split_at(idx);
+    let key = key.trim();
+    if key.is_empty() {
+        None
+    } else {
+        Some((key, value[1..].trim()))
+    }
+}
+
+/// Parses a basic (`"..."`) or literal (`'...'`) TOML string.
+fn parse_string(line_number: usize, value: &str) -> Result<String, ConfigError> {
+    let invalid =
+        || ConfigError::Invalid(line_number, format!("expected a string, found {}", value));
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        Ok(value[1..value.len() - 1].to_owned())
+    } else if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        let mut result = String::new();
+        let mut chars = value[1..value.len() - 1].chars();
+        while let Some(ch) = chars.next() {
+            if ch == '\\' {
+                match chars.next() {
+                    Some('n') => result.push('\n'),
+                    Some('t') => result.push('\t'),
+                    Some(escaped @ ('\\' | '"')) => result.push(escaped),
+                    _ => return Err(invalid()),
+                }
+            } else {
+                result.push(ch);
+            }
+        }
+        Ok(result)
+    } else {
+        Err(invalid())
+    }
+}
+
+/// Parses a single-line array of strings (e.g. `["a", 'b']`).
+fn parse_string_array(line_number: usize, value: &str) -> Result<Vec<String>, ConfigError> {
+    let inner = value
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .ok_or_else(|| {
+            ConfigError::Invalid(line_number, format!("expected an array, found {}", value))
+        })?;
+    let mut result = Vec::<String>::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut start = 0;
+    for (idx, ch) in inner.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if ch == '\\' => escaped = true,
+            Some(q) if ch == q => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == ',' => {
+                result.push(parse_string(line_number, inner[start..idx].trim())?);
+                start = idx + 1;
+            }
+            None => {}
+        }
+    }
+    // allow a trailing comma
+    let last = inner[start..].trim();
+    if !last.is_empty() {
+        result.push(parse_string(line_number, last)?);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty() {
+        assert_eq!(parse("").unwrap(), Config::default());
+        assert_eq!(parse("# nothing here\n\n").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_parse_generic() {
+        let input = r#"
+# version of the app
+[[generic]]
+file = "app.json" # the manifest
+json_path = "build.version"
+
+[[generic]]
+file = 'config/manifest#1.json'
+json_path = "version"
+
+[[generic]]
+file = "values.yaml"
+yaml_path = "image.tag"
+
+[[generic]]
+file = "README.md"
+marker = "Current version:"
+
+[[generic]]
+file = "pyproject.toml"
+toml_path = "tool.poetry.version"
+"#;
+        let expected = Config {
+            generic: vec![
+                GenericEntry {
+                    file: "app.json".to_owned(),
+                    location: GenericLocation::JsonPath("build.version".to_owned()),
+                },
+                GenericEntry {
+                    file: "config/manifest#1.json".to_owned(),
+                    location: GenericLocation::JsonPath("version".to_owned()),
+                },
+                GenericEntry {
+                    file: "values.yaml".to_owned(),
+                    location: GenericLocation::YamlPath("image.tag".to_owned()),
+                },
+                GenericEntry {
+                    file: "README.md".to_owned(),
+                    location: GenericLocation::Marker("Current version:".to_owned()),
+                },
+                GenericEntry {
+                    file: "pyproject.toml".to_owned(),
+                    location: GenericLocation::TomlPath("tool.poetry.version".to_owned()),
+                },
+            ],
+            build_date_file: None,
+            cargo_extra_keys: vec![],
+            ignore_dirs: vec![],
+        };
+        assert_eq!(parse(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_build_date_file() {
+        let input = r#"
+build_date_file = "ci/BUILD_DATE"
+
+[[generic]]
+file = "app.json"
+json_path = "version"
+"#;
+        let config = parse(input).unwrap();
+        assert_eq!(config.build_date_file, Some("ci/BUILD_DATE".to_owned()));
+        assert_eq!(config.generic.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_cargo_extra_keys() {
+        let input = r#"
+[cargo]
+extra_keys = ["package.metadata.docs.version", 'badges.version', ]
+"#;
+        assert_eq!(
+            parse(input).unwrap().cargo_extra_keys,
+            vec!["package.metadata.docs.version", "badges.version"]
+        );
+        assert!(matches!(
+            parse("[cargo]\nextra_keys = \"version\"\n"),
+            Err(ConfigError::Invalid(2, _))
+        ));
+    }
+
+    #[test]
+    fn test_parse_ignore_dirs() {
+        assert_eq!(
+            parse("ignore_dirs = [\"vendor\", \"dist\"]\n")
+                .unwrap()
+                .ignore_dirs,
+            vec!["vendor", "dist"]
+        );
+    }
+
+    #[test]
+    fn test_parse_generic_missing_location() {
+        let input = r#"
+[[generic]]
+file = "app.json"
+"#;
+        assert!(matches!(parse(input), Err(ConfigError::Invalid(2, _))));
+    }
+
+    #[test]
+    fn test_parse_generic_empty_marker() {
+        let input = r#"
+[[generic]]
+file = "README.md"
+marker = ""
+"#;
+        assert!(matches!(parse(input), Err(ConfigError::Invalid(4, _))));
+    }
+
+    #[test]
+    fn test_parse_unknown_key() {
+        let input = r#"
+[[generic]]
+name = "app.json"
+"#;
+        assert!(matches!(parse(input), Err(ConfigError::Invalid(3, _))));
+    }
+
+    #[test]
+    fn test_parse_unknown_section() {
+        assert!(matches!(
+            parse("[generic]\n"),
+            Err(ConfigError::Invalid(1, _))
+        ));
+    }
+}
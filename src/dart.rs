@@ -0,0 +1,242 @@
+//! Handles Dart and Flutter packages (pubspec.yaml)
+use crate::files::{ContentProcessor, FileFinder};
+use crate::SemVer;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub enum PubspecError {
+    /// The written version does not parse back to the expected version.
+    InvalidVersion(String),
+
+    /// The build number of the version is not a number (e.g. `1.2.3+abc`).
+    InvalidBuildNumber(String),
+}
+
+impl Display for PubspecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidVersion(line) => {
+                write!(
+                    f,
+                    "pubspec.yaml has an invalid version after update: {}",
+                    line
+                )
+            }
+            Self::InvalidBuildNumber(value) => {
+                write!(f, "pubspec.yaml has a non-numeric build number: {}", value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PubspecError {}
+
+/// Updates the top-level `version` of `pubspec.yaml`.
+///
+/// Flutter apps append a build number to the version (e.g. `1.2.3+5`).
+/// The build number is incremented on every release, unless `build_number`
+/// overrides it. Versions without a build number do not get one,
+/// unless `build_number` is given.
+pub struct PubspecUpdater {
+    pub build_number: Option<u64>,
+}
+
+impl FileFinder for PubspecUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let path = PathBuf::from(dir).join("pubspec.yaml");
+        if path.is_file() {
+            Ok(vec![path])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+impl ContentProcessor for PubspecUpdater {
+    type Err = PubspecError;
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        let mut result = String::new();
+        for line in old_contents.split_inclusive('\n') {
+            match parse_version_line(line) {
+                Some((start, end)) => {
+                    let (_, old_build_number) = split_build_number(&line[start..end])?;
+                    let build_number = self
+                        .build_number
+                        .or_else(|| old_build_number.map(|n| n + 1));
+                    let new_value = match build_number {
+                        Some(n) => format!("{}+{}", version, n),
+                        None => version.to_string(),
+                    };
+                    let new_line = format!("{}{}{}", &line[..start], new_value, &line[end..]);
+                    validate(&new_line, version, build_number)?;
+                    result.push_str(&new_line);
+                }
+                None => result.push_str(line),
+            }
+        }
+        Ok(result)
+    }
+
+    /// The build number is not part of the version.
+    fn is_at_version(&self, contents: &str, version: SemVer) -> bool {
+        contents
+            .split_inclusive('\n')
+            .filter_map(|line| {
+                parse_version_line(line).map(|(start, end)| split_build_number(&line[start..end]))
+            })
+            .all(|split| {
+                split.is_ok_and(|(written_version, _)| {
+                    SemVer::from_str(written_version).ok() == Some(version)
+                })
+            })
+    }
+}
+
+/// Finds the byte span of the value of the top-level `version` key.
+/// The span of a quoted value (e.g. `"1.2.3+4"`) is inside the quotes,
+/// so that they are kept when the value is replaced.
+fn parse_version_line(line: &str) -> Option<(usize, usize)> {
+    let value = line.strip_prefix("version:")?;
+    let trimmed = value.trim_start();
+    if let Some(quote) = trimmed
+        .chars()
+        .next()
+        .filter(|ch| *ch == '"' || *ch == '\'')
+    {
+        let start = line.len() - trimmed.len() + 1;
+        let len = trimmed[1..].find(quote)?;
+        return if len == 0 {
+            None
+        } else {
+            Some((start, start + len))
+        };
+    }
+    let start = line.len() - trimmed.len();
+    let len = trimmed
+        .find(|ch: char| ch.is_whitespace())
+        .unwrap_or(trimmed.len());
+    if len == 0 {
+        None
+    } else {
+        Some((start, start + len))
+    }
+}
+
+/// Splits a version like `1.2.3+5` into the version and the build number.
+fn split_build_number(value: &str) -> Result<(&str, Option<u64>), PubspecError> {
+    match value.split_once('+') {
+        Some((version, build)) => match u64::from_str(build) {
+            Ok(build_number) => Ok((version, Some(build_number))),
+            Err(_) => Err(PubspecError::InvalidBuildNumber(value.to_owned())),
+        },
+        None => Ok((value, None)),
+    }
+}
+
+/// Checks that the written line parses back to the expected version.
+fn validate(line: &str, version: SemVer, build_number: Option<u64>) -> Result<(), PubspecError> {
+    let invalid = || PubspecError::InvalidVersion(line.trim_end().to_owned());
+    let (start, end) = parse_version_line(line).ok_or_else(invalid)?;
+    let (written_version, written_build_number) =
+        split_build_number(&line[start..end]).map_err(|_| invalid())?;
+    if SemVer::from_str(written_version).ok() == Some(version)
+        && written_build_number == build_number
+    {
+        Ok(())
+    } else {
+        Err(invalid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUBSPEC: &str = r#"name: my_app
+description: A new Flutter project.
+publish_to: 'none'
+
+version: 1.2.2+5
+
+environment:
+  sdk: '>=2.18.0 <3.0.0'
+
+dependencies:
+  flutter:
+    sdk: flutter
+  http:
+    version: ^0.13.5
+"#;
+
+    #[test]
+    fn test_auto_increment_build_number() {
+        let updater = PubspecUpdater { build_number: None };
+        let result = updater.process(PUBSPEC, SemVer::new(1, 2, 3)).unwrap();
+        assert_eq!(
+            result,
+            PUBSPEC.replace("version: 1.2.2+5", "version: 1.2.3+6")
+        );
+        let result = updater.process(PUBSPEC, SemVer::new(1, 3, 0)).unwrap();
+        assert_eq!(
+            result,
+            PUBSPEC.replace("version: 1.2.2+5", "version: 1.3.0+6")
+        );
+    }
+
+    #[test]
+    fn test_build_number_override() {
+        let updater = PubspecUpdater {
+            build_number: Some(100),
+        };
+        let result = updater.process(PUBSPEC, SemVer::new(1, 2, 3)).unwrap();
+        assert_eq!(
+            result,
+            PUBSPEC.replace("version: 1.2.2+5", "version: 1.2.3+100")
+        );
+    }
+
+    #[test]
+    fn test_without_build_number() {
+        let updater = PubspecUpdater { build_number: None };
+        let result = updater
+            .process(
+                "name: my_package\nversion: 0.1.0 # the version\n",
+                SemVer::new(0, 2, 0),
+            )
+            .unwrap();
+        assert_eq!(result, "name: my_package\nversion: 0.2.0 # the version\n");
+    }
+
+    #[test]
+    fn test_quoted_version() {
+        let updater = PubspecUpdater { build_number: None };
+        for quote in ['"', '\''] {
+            let input = format!("name: my_app\nversion: {0}1.2.2+5{0}\n", quote);
+            let expected = format!("name: my_app\nversion: {0}1.2.3+6{0}\n", quote);
+            assert_eq!(
+                updater.process(&input, SemVer::new(1, 2, 3)).unwrap(),
+                expected
+            );
+            assert!(updater.is_at_version(&input, SemVer::new(1, 2, 2)));
+        }
+    }
+
+    #[test]
+    fn test_non_numeric_build_number() {
+        let updater = PubspecUpdater { build_number: None };
+        let err = updater
+            .process("name: my_app\nversion: 1.2.2+abc\n", SemVer::new(1, 2, 3))
+            .unwrap_err();
+        assert!(matches!(err, PubspecError::InvalidBuildNumber(ref v) if v == "1.2.2+abc"));
+    }
+
+    #[test]
+    fn test_is_at_version_ignores_build_number() {
+        let updater = PubspecUpdater { build_number: None };
+        assert!(updater.is_at_version(PUBSPEC, SemVer::new(1, 2, 2)));
+        assert!(!updater.is_at_version(PUBSPEC, SemVer::new(1, 2, 3)));
+    }
+}
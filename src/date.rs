@@ -0,0 +1,206 @@
+//! Formats calendar dates without external dependencies
+//!
+//! Dates are always in UTC.
+
+use std::fmt::{Display, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The default date format, ISO 8601 (`YYYY-MM-DD`).
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// A calendar date. It is always a valid date, see [Date::new].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+/// The error of [Date::new] for a date that does not exist (e.g. `2023-02-29`).
+#[derive(Debug, Eq, PartialEq)]
+pub struct InvalidDateError {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Display for InvalidDateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid date {:04}-{:02}-{:02}",
+            self.year, self.month, self.day
+        )
+    }
+}
+
+impl std::error::Error for InvalidDateError {}
+
+impl Date {
+    /// Creates a date, checking that the month is 1 to 12
+    /// and that the month has the day.
+    pub fn new(year: i32, month: u8, day: u8) -> Result<Self, InvalidDateError> {
+        if (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month) {
+            Ok(Self { year, month, day })
+        } else {
+            Err(InvalidDateError { year, month, day })
+        }
+    }
+
+    /// Returns the current date in UTC.
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self::from_days_since_epoch((seconds / 86_400) as i64)
+    }
+
+    /// Converts the number of days since 1970-01-01 to a date.
+    /// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = (year_of_era + era * 400) as i32 + if month <= 2 { 1 } else { 0 };
+        Self { year, month, day }
+    }
+
+    /// Formats the date with a strftime-like format.
+    ///
+    /// Supported specifiers are `%Y` (2024), `%y` (24), `%m` (03), `%d` (07),
+    /// `%e` (7), `%B` (March), `%b` (Mar), `%F` (2024-03-07) and `%%`.
+    /// Other specifiers are kept as-is.
+    pub fn format(&self, format: &str) -> String {
+        let mut result = String::new();
+        let mut chars = format.chars();
+        while let Some(ch) = chars.next() {
+            if ch != '%' {
+                result.push(ch);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => result.push_str(&self.year.to_string()),
+                Some('y') => result.push_str(&format!("{:02}", self.year.rem_euclid(100))),
+                Some('m') => result.push_str(&format!("{:02}", self.month)),
+                Some('d') => result.push_str(&format!("{:02}", self.day)),
+                Some('e') => result.push_str(&self.day.to_string()),
+                Some('B') => result.push_str(self.month_name()),
+                Some('b') => result.push_str(&self.month_name()[..3]),
+                Some('F') => result.push_str(&self.format(DEFAULT_DATE_FORMAT)),
+                Some('%') => result.push('%'),
+                Some(other) => {
+                    result.push('%');
+                    result.push(other);
+                }
+                None => result.push('%'),
+            }
+        }
+        result
+    }
+
+    fn month_name(&self) -> &'static str {
+        MONTH_NAMES[(self.month - 1) as usize]
+    }
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_days_since_epoch() {
+        assert_eq!(
+            Date::from_days_since_epoch(0),
+            Date::new(1970, 1, 1).unwrap()
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(59),
+            Date::new(1970, 3, 1).unwrap()
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(11_016),
+            Date::new(2000, 2, 29).unwrap()
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(19_789),
+            Date::new(2024, 3, 7).unwrap()
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(-1),
+            Date::new(1969, 12, 31).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_new_validates_the_date() {
+        assert!(Date::new(2024, 2, 29).is_ok());
+        assert!(Date::new(2000, 2, 29).is_ok());
+        assert!(Date::new(2024, 12, 31).is_ok());
+        for (year, month, day) in [
+            (2023, 2, 29),
+            (1900, 2, 29),
+            (2024, 0, 1),
+            (2024, 13, 1),
+            (2024, 4, 31),
+            (2024, 1, 0),
+        ] {
+            assert_eq!(
+                Date::new(year, month, day),
+                Err(InvalidDateError { year, month, day })
+            );
+        }
+        assert_eq!(
+            Date::new(2024, 13, 1).unwrap_err().to_string(),
+            "invalid date 2024-13-01"
+        );
+    }
+
+    #[test]
+    fn test_format_default() {
+        assert_eq!(
+            Date::new(2024, 3, 7).unwrap().format(DEFAULT_DATE_FORMAT),
+            "2024-03-07"
+        );
+    }
+
+    #[test]
+    fn test_format_specifiers() {
+        let date = Date::new(2024, 3, 7).unwrap();
+        assert_eq!(date.format("%d/%m/%y"), "07/03/24");
+        assert_eq!(date.format("%B %e, %Y"), "March 7, 2024");
+        assert_eq!(date.format("%e %b %Y"), "7 Mar 2024");
+        assert_eq!(date.format("%F"), "2024-03-07");
+        assert_eq!(date.format("100%% %Q %"), "100% %Q %");
+    }
+}
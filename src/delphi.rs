@@ -1,11 +1,11 @@
-//! Handles Lazarus lpi files
+//! Handles Lazarus lpi and lpk files
 extern crate xml;
 
 use std::io::{Read, Write};
 use std::path::PathBuf;
 
 use crate::files::{ContentProcessor, FileFinder, RootFileFinderByExt};
-use crate::sem_ver::{SemVer, SemVerComponent, SemVerComponentSet};
+use crate::sem_ver::{ExtractedVersion, SemVer, SemVerComponent, SemVerComponentSet};
 use crate::xml_util::{echo, transform_xml, ElementPath, XmlError};
 use xml::attribute::OwnedAttribute;
 use xml::name::OwnedName;
@@ -28,6 +28,132 @@ impl ContentProcessor for LpiUpdater {
     fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
         process_str(old_contents, version)
     }
+
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        extract_version_str(contents).map(ExtractedVersion::from)
+    }
+}
+
+/// Handles versioning found in Lazarus lpk (package) files.
+///
+/// Unlike lpi files, the version is held in the attributes of a single element,
+/// e.g. `<Version Major="1" Minor="2" Release="3"/>`.
+pub struct LpkUpdater {}
+
+impl FileFinder for LpkUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        RootFileFinderByExt::new("lpk").find(dir)
+    }
+}
+
+impl ContentProcessor for LpkUpdater {
+    type Err = XmlError;
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        transform_xml(old_contents, |parser, writer| {
+            do_process_lpk(parser, writer, version)
+        })
+    }
+
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        extract_lpk_version_str(contents).map(ExtractedVersion::from)
+    }
+}
+
+/// The path of the version element of lpk files.
+/// Other `Version` elements (e.g. of the compiler options) are left alone.
+const LPK_VERSION_PATH: [&str; 3] = ["CONFIG", "Package", "Version"];
+
+fn do_process_lpk<R: Read, W: Write>(
+    parser: EventReader<R>,
+    writer: &mut EventWriter<W>,
+    version: SemVer,
+) -> Result<(), XmlError> {
+    let mut element_path = ElementPath::Empty;
+    for result_xml_event in parser {
+        let xml_event = result_xml_event?;
+        match &xml_event {
+            XmlEvent::StartElement {
+                name,
+                attributes,
+                namespace,
+            } => {
+                element_path = element_path.push(&name.local_name);
+                if element_path.matches(&LPK_VERSION_PATH) {
+                    let values: Vec<(&str, String)> = SemVerComponentSet::all()
+                        .map(|component| {
+                            (
+                                sem_ver_component_to_lpk_attribute(component),
+                                version.get_component(component).to_string(),
+                            )
+                        })
+                        .collect();
+                    writer.write(add_or_update_attributes(
+                        name, attributes, namespace, &values,
+                    ))?;
+                } else {
+                    echo(&xml_event, writer)?;
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                element_path = element_path.pop();
+                echo(&xml_event, writer)?;
+            }
+            XmlEvent::Whitespace(_) => {
+                // discarding whitespace because it confuses indentation
+            }
+            _ => {
+                echo(&xml_event, writer)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads the version of the `Version` element of the package.
+/// Like in lpi files, Lazarus leaves out the attributes of components that are zero.
+fn extract_lpk_version_str(contents: &str) -> Option<SemVer> {
+    let mut element_path = ElementPath::Empty;
+    for result_xml_event in EventReader::new(contents.as_bytes()) {
+        match result_xml_event.ok()? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                element_path = element_path.push(&name.local_name);
+                if element_path.matches(&LPK_VERSION_PATH) {
+                    let mut version = SemVer::new(0, 0, 0);
+                    for component in SemVerComponentSet::all() {
+                        let attr_name = sem_ver_component_to_lpk_attribute(component);
+                        if let Some(attribute) = attributes
+                            .iter()
+                            .find(|attribute| attribute.name.local_name == attr_name)
+                        {
+                            let value = attribute.value.parse().ok()?;
+                            match component {
+                                SemVerComponent::Major => version.major = value,
+                                SemVerComponent::Minor => version.minor = value,
+                                SemVerComponent::Patch => version.patch = value,
+                            }
+                        }
+                    }
+                    return Some(version);
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                element_path = element_path.pop();
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn sem_ver_component_to_lpk_attribute(component: SemVerComponent) -> &'static str {
+    match component {
+        SemVerComponent::Major => "Major",
+        SemVerComponent::Minor => "Minor",
+        SemVerComponent::Patch => "Release",
+    }
 }
 
 fn process_str(old_contents: &str, version: SemVer) -> Result<String, XmlError> {
@@ -56,12 +182,11 @@ fn do_process<R: Read, W: Write>(
                     Some(sem_ver_component) => {
                         found_sem_ver_components += sem_ver_component;
                         let value_as_str = version.get_component(sem_ver_component).to_string();
-                        writer.write(add_or_update_attribute(
+                        writer.write(add_or_update_attributes(
                             name,
                             attributes,
                             namespace,
-                            "Value",
-                            &value_as_str,
+                            &[("Value", value_as_str)],
                         ))?;
                     }
                     _ => {
@@ -96,40 +221,88 @@ fn do_process<R: Read, W: Write>(
     Ok(())
 }
 
-fn add_or_update_attribute<'a>(
+/// Reads the version of the `VersionInfo` element.
+/// Lazarus leaves out the elements of components that are zero.
+fn extract_version_str(contents: &str) -> Option<SemVer> {
+    let mut element_path = ElementPath::Empty;
+    let mut found_version_info = false;
+    let mut version = SemVer::new(0, 0, 0);
+    for result_xml_event in EventReader::new(contents.as_bytes()) {
+        match result_xml_event.ok()? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                element_path = element_path.push(&name.local_name);
+                if element_path.matches(&["CONFIG", "ProjectOptions", "VersionInfo"]) {
+                    found_version_info = true;
+                } else if let Some(sem_ver_component) = match_sem_ver_element(&element_path) {
+                    let value = attributes
+                        .iter()
+                        .find(|attribute| attribute.name.local_name == "Value")?
+                        .value
+                        .parse()
+                        .ok()?;
+                    match sem_ver_component {
+                        SemVerComponent::Major => version.major = value,
+                        SemVerComponent::Minor => version.minor = value,
+                        SemVerComponent::Patch => version.patch = value,
+                    }
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                element_path = element_path.pop();
+            }
+            _ => {}
+        }
+    }
+    if found_version_info {
+        Some(version)
+    } else {
+        None
+    }
+}
+
+/// Sets the given attributes of an element, keeping the order of the existing ones.
+/// Missing attributes are added at the end.
+fn add_or_update_attributes<'a>(
     name: &'a OwnedName,
     attributes: &'a Vec<OwnedAttribute>,
     _namespace: &'a Namespace,
-    attr_name: &'a str,
-    value: &'a str,
+    values: &'a [(&'a str, String)],
 ) -> xml::writer::XmlEvent<'a> {
     let mut builder = xml::writer::XmlEvent::start_element(name.borrow());
-    let mut found = false;
+    let mut found = vec![false; values.len()];
     for attribute in attributes {
-        if !found && attribute.name.local_name == attr_name {
-            found = true;
-            builder = builder.attr(attribute.name.borrow(), value);
-        } else {
-            builder = builder.attr(attribute.name.borrow(), &attribute.value);
+        match values
+            .iter()
+            .position(|(attr_name, _)| attribute.name.local_name == *attr_name)
+        {
+            Some(index) if !found[index] => {
+                found[index] = true;
+                builder = builder.attr(attribute.name.borrow(), &values[index].1);
+            }
+            _ => {
+                builder = builder.attr(attribute.name.borrow(), &attribute.value);
+            }
         }
     }
-    if !found {
-        builder = builder.attr(attr_name, value);
+    for ((attr_name, value), found) in values.iter().zip(found) {
+        if !found {
+            builder = builder.attr(*attr_name, value);
+        }
     }
     builder.into()
 }
 
 fn match_sem_ver_element(element_path: &ElementPath) -> Option<SemVerComponent> {
-    SemVerComponentSet::all()
-        .filter(|component| {
-            element_path.matches(&[
-                "CONFIG",
-                "ProjectOptions",
-                "VersionInfo",
-                sem_ver_component_to_element_name(*component),
-            ])
-        })
-        .next()
+    SemVerComponentSet::all().find(|component| {
+        element_path.matches(&[
+            "CONFIG",
+            "ProjectOptions",
+            "VersionInfo",
+            sem_ver_component_to_element_name(*component),
+        ])
+    })
 }
 
 fn sem_ver_component_to_element_name(component: SemVerComponent) -> &'static str {
@@ -212,6 +385,35 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_extract_version() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CONFIG>
+  <ProjectOptions>
+    <Version Value="11"/>
+    <VersionInfo>
+      <MajorVersionNr Value="1"/>
+      <RevisionNr Value="2"/>
+      <BuildNr Value="7"/>
+    </VersionInfo>
+  </ProjectOptions>
+</CONFIG>
+"#;
+        assert_eq!(
+            LpiUpdater {}.extract_version(input),
+            Some(SemVer::new(1, 0, 2).into())
+        );
+        assert_eq!(
+            LpiUpdater {}.extract_version(&input.replace("Value=\"2\"", "Value=\"two\"")),
+            None
+        );
+        assert_eq!(
+            LpiUpdater {}
+                .extract_version("<?xml version=\"1.0\"?>\n<CONFIG><ProjectOptions/></CONFIG>\n"),
+            None
+        );
+    }
+
     #[test]
     fn does_not_affect_elements_outside_version_info() {
         let input = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -224,4 +426,81 @@ mod tests {
         let result = process_str(input, SemVer::new(2, 3, 4)).unwrap();
         assert_eq!(result, input);
     }
+
+    #[test]
+    fn keeps_escaped_attributes() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CONFIG>
+  <ProjectOptions>
+    <Title Value="Tom &amp; Jerry &lt;&quot;Deluxe&quot;&gt;" />
+    <VersionInfo>
+      <MajorVersionNr Value="1" />
+      <MinorVersionNr Value="2" />
+      <RevisionNr Value="3" />
+      <StringTable CompanyName="Hanna &amp; Barbera" ProductVersion="" />
+    </VersionInfo>
+  </ProjectOptions>
+</CONFIG>
+"#;
+        let expected = input
+            .replace("MajorVersionNr Value=\"1\"", "MajorVersionNr Value=\"2\"")
+            .replace("MinorVersionNr Value=\"2\"", "MinorVersionNr Value=\"3\"")
+            .replace("RevisionNr Value=\"3\"", "RevisionNr Value=\"4\"");
+        let result = process_str(input, SemVer::new(2, 3, 4)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn lpk_version() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CONFIG>
+  <Package Version="5">
+    <PathDelim Value="\"/>
+    <Name Value="mypackage"/>
+    <Type Value="RunAndDesignTime"/>
+    <CompilerOptions>
+      <Version Value="11"/>
+      <PathDelim Value="\"/>
+    </CompilerOptions>
+    <Version Major="1" Release="3"/>
+    <RequiredPkgs>
+      <Item>
+        <PackageName Value="LCL"/>
+        <MinVersion Major="2" Valid="True"/>
+      </Item>
+    </RequiredPkgs>
+  </Package>
+</CONFIG>
+"#;
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CONFIG>
+  <Package Version="5">
+    <PathDelim Value="\" />
+    <Name Value="mypackage" />
+    <Type Value="RunAndDesignTime" />
+    <CompilerOptions>
+      <Version Value="11" />
+      <PathDelim Value="\" />
+    </CompilerOptions>
+    <Version Major="1" Release="0" Minor="4" />
+    <RequiredPkgs>
+      <Item>
+        <PackageName Value="LCL" />
+        <MinVersion Major="2" Valid="True" />
+      </Item>
+    </RequiredPkgs>
+  </Package>
+</CONFIG>
+"#;
+        assert_eq!(
+            LpkUpdater {}.extract_version(input),
+            Some(SemVer::new(1, 0, 3).into())
+        );
+        let result = LpkUpdater {}.process(input, SemVer::new(1, 4, 0)).unwrap();
+        assert_eq!(result, expected);
+        assert_eq!(
+            LpkUpdater {}.extract_version(&result),
+            Some(SemVer::new(1, 4, 0).into())
+        );
+    }
 }
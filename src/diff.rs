@@ -0,0 +1,103 @@
+//! Formats minimal line diffs, e.g. to show the changes of a dry run
+
+const RED: &str = "\u{1b}[31m";
+const GREEN: &str = "\u{1b}[32m";
+const CYAN: &str = "\u{1b}[36m";
+const RESET: &str = "\u{1b}[0m";
+
+/// Formats the lines that differ between the old and the new contents.
+///
+/// Version updates rarely add or remove lines, so lines are compared one by one
+/// when both sides have the same number of lines. Otherwise, the block between
+/// the common leading and trailing lines is shown as removed and added.
+///
+/// With `color`, removed lines are red and added lines are green (ANSI codes).
+pub fn format_diff(old_contents: &str, new_contents: &str, color: bool) -> String {
+    let old_lines: Vec<&str> = old_contents.lines().collect();
+    let new_lines: Vec<&str> = new_contents.lines().collect();
+    let mut result = String::new();
+    if old_lines.len() == new_lines.len() {
+        for (index, (old_line, new_line)) in old_lines.iter().zip(new_lines.iter()).enumerate() {
+            if old_line != new_line {
+                push_hunk(&mut result, index, &[old_line], &[new_line], color);
+            }
+        }
+    } else {
+        let prefix = old_lines
+            .iter()
+            .zip(new_lines.iter())
+            .take_while(|(old_line, new_line)| old_line == new_line)
+            .count();
+        let suffix = old_lines[prefix..]
+            .iter()
+            .rev()
+            .zip(new_lines[prefix..].iter().rev())
+            .take_while(|(old_line, new_line)| old_line == new_line)
+            .count();
+        push_hunk(
+            &mut result,
+            prefix,
+            &old_lines[prefix..old_lines.len() - suffix],
+            &new_lines[prefix..new_lines.len() - suffix],
+            color,
+        );
+    }
+    result
+}
+
+fn push_hunk(result: &mut String, index: usize, removed: &[&str], added: &[&str], color: bool) {
+    push_line(result, &format!("@@ line {} @@", index + 1), CYAN, color);
+    for line in removed {
+        push_line(result, &format!("-{}", line), RED, color);
+    }
+    for line in added {
+        push_line(result, &format!("+{}", line), GREEN, color);
+    }
+}
+
+fn push_line(result: &mut String, line: &str, ansi_color: &str, color: bool) {
+    if color {
+        result.push_str(ansi_color);
+        result.push_str(line);
+        result.push_str(RESET);
+    } else {
+        result.push_str(line);
+    }
+    result.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_diff_same_line_count() {
+        let old = "[package]\nname = \"app\"\nversion = \"0.1.0\"\n";
+        let new = "[package]\nname = \"app\"\nversion = \"0.2.0\"\n";
+        assert_eq!(
+            format_diff(old, new, false),
+            "@@ line 3 @@\n-version = \"0.1.0\"\n+version = \"0.2.0\"\n"
+        );
+    }
+
+    #[test]
+    fn test_format_diff_different_line_count() {
+        let old = "a\nb\nc\n";
+        let new = "a\nB1\nB2\nc\n";
+        assert_eq!(format_diff(old, new, false), "@@ line 2 @@\n-b\n+B1\n+B2\n");
+    }
+
+    #[test]
+    fn test_format_diff_without_color_has_no_ansi_codes() {
+        let diff = format_diff("version 1\n", "version 2\n", false);
+        assert!(!diff.contains('\u{1b}'));
+    }
+
+    #[test]
+    fn test_format_diff_with_color() {
+        assert_eq!(
+            format_diff("version 1\n", "version 2\n", true),
+            "\u{1b}[36m@@ line 1 @@\u{1b}[0m\n\u{1b}[31m-version 1\u{1b}[0m\n\u{1b}[32m+version 2\u{1b}[0m\n"
+        );
+    }
+}
@@ -0,0 +1,528 @@
+//! Handles .NET projects (.csproj, AssemblyInfo.vb) and NuGet package manifests (.nuspec)
+extern crate xml;
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::config;
+use crate::files::{ContentProcessor, FileFinder, RecursiveFileFinderByExt};
+use crate::sem_ver::{ExtractedVersion, SemVer};
+use crate::xml_util::{echo, transform_xml, ElementPath, XmlError};
+use xml::reader::{EventReader, XmlEvent};
+use xml::writer::EventWriter;
+
+/// Finds the files of the given extension, skipping the `ignore_dirs` of the configuration.
+fn find_by_ext(dir: &str, extension: &str, max_depth: usize) -> std::io::Result<Vec<PathBuf>> {
+    let config =
+        config::load(dir).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    RecursiveFileFinderByExt::new(&[extension], max_depth)
+        .with_ignore_dirs(&config.ignore_dirs)
+        .find(dir)
+}
+
+/// Updates the `package/metadata/version` element of `.nuspec` files.
+/// The version ranges of `dependencies` are left alone.
+///
+/// Files are searched up to `max_depth` folders deep (0 means only the root folder).
+pub struct NuspecUpdater {
+    pub max_depth: usize,
+}
+
+impl FileFinder for NuspecUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        find_by_ext(dir, "nuspec", self.max_depth)
+    }
+}
+
+impl ContentProcessor for NuspecUpdater {
+    type Err = XmlError;
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        process_str(old_contents, version)
+    }
+}
+
+fn process_str(old_contents: &str, version: SemVer) -> Result<String, XmlError> {
+    transform_xml(old_contents, |parser, writer| {
+        do_process(parser, writer, version)
+    })
+}
+
+fn do_process<R: Read, W: Write>(
+    parser: EventReader<R>,
+    writer: &mut EventWriter<W>,
+    version: SemVer,
+) -> Result<(), XmlError> {
+    let version_as_str = version.to_string();
+    let mut element_path = ElementPath::Empty;
+    let mut replacing = false;
+    for result_xml_event in parser {
+        let xml_event = result_xml_event?;
+        match &xml_event {
+            XmlEvent::StartElement { name, .. } => {
+                element_path = element_path.push(&name.local_name);
+                echo(&xml_event, writer)?;
+                if element_path.matches(&["package", "metadata", "version"]) {
+                    writer.write(xml::writer::XmlEvent::characters(&version_as_str))?;
+                    replacing = true;
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                element_path = element_path.pop();
+                replacing = false;
+                echo(&xml_event, writer)?;
+            }
+            XmlEvent::Characters(_) | XmlEvent::CData(_) if replacing => {
+                // the old version is discarded
+            }
+            XmlEvent::Whitespace(_) => {
+                // discarding whitespace because it confuses indentation
+            }
+            _ => {
+                echo(&xml_event, writer)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Updates the `Version` of SDK-style `.csproj` files.
+///
+/// Only the first property group without a `Condition` attribute is updated,
+/// so that e.g. a `Debug` or `Release` specific version is left alone.
+/// If every property group has a condition, the first one is updated.
+///
+/// Files are searched up to `max_depth` folders deep (0 means only the root folder).
+pub struct CsprojUpdater {
+    pub max_depth: usize,
+}
+
+impl FileFinder for CsprojUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        find_by_ext(dir, "csproj", self.max_depth)
+    }
+}
+
+impl ContentProcessor for CsprojUpdater {
+    type Err = XmlError;
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        let target_group = find_target_property_group(old_contents.as_bytes())?;
+        transform_xml(old_contents, |parser, writer| {
+            process_csproj(parser, writer, version, target_group)
+        })
+    }
+}
+
+/// Finds the index of the property group that holds the version:
+/// the first one without a `Condition`, or else the first one.
+fn find_target_property_group<R: Read>(source: R) -> Result<Option<usize>, XmlError> {
+    let mut element_path = ElementPath::Empty;
+    let mut count = 0;
+    for xml_event in EventReader::new(source) {
+        match xml_event? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                element_path = element_path.push_with_attributes(&name.local_name, &attributes);
+                if element_path.matches(&["Project", "PropertyGroup"]) {
+                    if element_path.matches_with_attr(
+                        &["Project", "PropertyGroup"],
+                        "Condition",
+                        None,
+                    ) {
+                        return Ok(Some(count));
+                    }
+                    count += 1;
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                element_path = element_path.pop();
+            }
+            _ => {}
+        }
+    }
+    Ok(if count > 0 { Some(0) } else { None })
+}
+
+fn process_csproj<R: Read, W: Write>(
+    parser: EventReader<R>,
+    writer: &mut EventWriter<W>,
+    version: SemVer,
+    target_group: Option<usize>,
+) -> Result<(), XmlError> {
+    let version_as_str = version.to_string();
+    let mut element_path = ElementPath::Empty;
+    let mut group_index = 0;
+    let mut in_default_group = false;
+    let mut replacing = false;
+    for result_xml_event in parser {
+        let xml_event = result_xml_event?;
+        match &xml_event {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                element_path = element_path.push_with_attributes(&name.local_name, attributes);
+                if element_path.matches(&["Project", "PropertyGroup"]) {
+                    in_default_group = target_group == Some(group_index);
+                    group_index += 1;
+                }
+                echo(&xml_event, writer)?;
+                if in_default_group
+                    && element_path.matches(&["Project", "PropertyGroup", "Version"])
+                {
+                    writer.write(xml::writer::XmlEvent::characters(&version_as_str))?;
+                    replacing = true;
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                element_path = element_path.pop();
+                replacing = false;
+                echo(&xml_event, writer)?;
+            }
+            XmlEvent::Characters(_) | XmlEvent::CData(_) if replacing => {
+                // the old version is discarded
+            }
+            XmlEvent::Whitespace(_) => {
+                // discarding whitespace because it confuses indentation
+            }
+            _ => {
+                echo(&xml_event, writer)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Updates the version attributes of the `AssemblyInfo.vb` files of VB.NET projects,
+/// e.g. `<Assembly: AssemblyVersion("1.2.3.0")>`:
+///
+/// - `AssemblyVersion` and `AssemblyFileVersion` get the new version.
+///   The fourth part (revision) is the build counter, if there is one (`--build-counter`).
+///   Otherwise, the revision of four part versions is kept.
+/// - `AssemblyInformationalVersion` gets the new version as is.
+///
+/// Visual Studio keeps the file in the `My Project` folder, so files are searched
+/// one folder deeper than `max_depth`.
+pub struct AssemblyInfoVbUpdater {
+    pub max_depth: usize,
+    pub build_counter: Option<u64>,
+}
+
+impl FileFinder for AssemblyInfoVbUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        Ok(find_by_ext(dir, "vb", self.max_depth + 1)?
+            .into_iter()
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|name| name.eq_ignore_ascii_case("AssemblyInfo.vb"))
+            })
+            .collect())
+    }
+}
+
+impl ContentProcessor for AssemblyInfoVbUpdater {
+    type Err = std::io::Error; // does not really throw error
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        let mut result = String::new();
+        for line in old_contents.split_inclusive('\n') {
+            match find_vb_assembly_version(line) {
+                Some((attribute, start, end)) => {
+                    result.push_str(&line[..start]);
+                    result.push_str(&attribute.format(
+                        &line[start..end],
+                        version,
+                        self.build_counter,
+                    ));
+                    result.push_str(&line[end..]);
+                }
+                None => result.push_str(line),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Reads the `AssemblyInformationalVersion`, or else the first three parts
+    /// of the `AssemblyVersion`.
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        let mut assembly_version: Option<SemVer> = None;
+        for line in contents.lines() {
+            if let Some((attribute, start, end)) = find_vb_assembly_version(line) {
+                let value = &line[start..end];
+                match attribute {
+                    AssemblyAttribute::InformationalVersion => {
+                        return SemVer::from_str(value).ok().map(ExtractedVersion::from)
+                    }
+                    AssemblyAttribute::Version if assembly_version.is_none() => {
+                        let parts: Vec<&str> = value.split('.').take(3).collect();
+                        assembly_version = SemVer::from_str(&parts.join(".")).ok();
+                    }
+                    _ => {}
+                }
+            }
+        }
+        assembly_version.map(ExtractedVersion::from)
+    }
+
+    /// AssemblyInfo.vb files of older projects may be saved in Windows-1252.
+    fn supports_legacy_encoding(&self) -> bool {
+        true
+    }
+}
+
+/// The assembly attributes that hold a version.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum AssemblyAttribute {
+    Version,
+    FileVersion,
+    InformationalVersion,
+}
+
+impl AssemblyAttribute {
+    /// Parses the name of the attribute, which may end with `Attribute`.
+    /// VB is case insensitive.
+    fn parse(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        match name.strip_suffix("attribute").unwrap_or(&name) {
+            "assemblyversion" => Some(Self::Version),
+            "assemblyfileversion" => Some(Self::FileVersion),
+            "assemblyinformationalversion" => Some(Self::InformationalVersion),
+            _ => None,
+        }
+    }
+
+    /// Formats the new version. The revision is the build counter if there is one,
+    /// or else the revision of a four part version is kept.
+    fn format(self, old_value: &str, version: SemVer, build_counter: Option<u64>) -> String {
+        if self == Self::InformationalVersion {
+            return version.to_string();
+        }
+        match (build_counter, old_value.split('.').nth(3)) {
+            (Some(build), _) => format!("{}.{}", version, build),
+            (None, Some(revision)) => format!("{}.{}", version, revision),
+            (None, None) => version.to_string(),
+        }
+    }
+}
+
+/// Finds the range of the version (without the quotes) of a line like
+/// `<Assembly: AssemblyVersion("1.2.3.0")>`.
+fn find_vb_assembly_version(line: &str) -> Option<(AssemblyAttribute, usize, usize)> {
+    let rest = line.trim_start().strip_prefix('<')?.trim_start();
+    if !rest.get(..9)?.eq_ignore_ascii_case("assembly:") {
+        return None;
+    }
+    let rest = rest[9..].trim_start();
+    let name_len = rest
+        .find(|ch: char| !ch.is_alphanumeric() && ch != '_')
+        .unwrap_or(rest.len());
+    let attribute = AssemblyAttribute::parse(&rest[..name_len])?;
+    let rest = rest[name_len..]
+        .trim_start()
+        .strip_prefix('(')?
+        .trim_start()
+        .strip_prefix('"')?;
+    let start = line.len() - rest.len();
+    let end = start + rest.find('"')?;
+    Some((attribute, start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::DirUpdater;
+
+    #[test]
+    fn updates_only_package_version() {
+        let input = r#"<?xml version="1.0" encoding="utf-8"?>
+<package xmlns="http://schemas.microsoft.com/packaging/2013/05/nuspec.xsd">
+  <metadata>
+    <id>Contoso.Utility</id>
+    <version>1.1.0</version>
+    <authors>Contoso</authors>
+    <description>Utility functions.</description>
+    <dependencies>
+      <group targetFramework="net6.0">
+        <dependency id="Newtonsoft.Json" version="[13.0.1, 14.0.0)" />
+      </group>
+    </dependencies>
+  </metadata>
+  <files>
+    <file src="bin/Release/net6.0/Contoso.Utility.dll" target="lib/net6.0" />
+  </files>
+</package>
+"#;
+        let expected = input.replace("<version>1.1.0</version>", "<version>1.2.0</version>");
+        let result = process_str(input, SemVer::new(1, 2, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn csproj_skips_conditional_property_groups() {
+        let input = r#"<?xml version="1.0" encoding="utf-8"?>
+<Project Sdk="Microsoft.NET.Sdk">
+  <PropertyGroup>
+    <TargetFramework>net8.0</TargetFramework>
+    <Version>1.1.0</Version>
+  </PropertyGroup>
+  <PropertyGroup Condition="'$(Configuration)' == 'Debug'">
+    <Version>0.0.0-dev</Version>
+  </PropertyGroup>
+</Project>
+"#;
+        // the writer escapes the quotes of attribute values, which MSBuild reads the same way
+        let expected = input
+            .replace("<Version>1.1.0</Version>", "<Version>1.2.0</Version>")
+            .replace(
+                "'$(Configuration)' == 'Debug'",
+                "&apos;$(Configuration)&apos; == &apos;Debug&apos;",
+            );
+        let result = CsprojUpdater { max_depth: 0 }
+            .process(input, SemVer::new(1, 2, 0))
+            .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn csproj_updates_the_first_unconditional_property_group() {
+        let input = r#"<?xml version="1.0" encoding="utf-8"?>
+<Project Sdk="Microsoft.NET.Sdk">
+  <PropertyGroup Condition="$(Configuration) == Debug">
+    <Version>0.0.0-dev</Version>
+  </PropertyGroup>
+  <PropertyGroup Condition="$(Configuration) == Release">
+    <Version>0.0.0-rc</Version>
+  </PropertyGroup>
+  <PropertyGroup>
+    <TargetFramework>net8.0</TargetFramework>
+    <Version>1.1.0</Version>
+  </PropertyGroup>
+  <PropertyGroup>
+    <Version>9.9.9</Version>
+  </PropertyGroup>
+</Project>
+"#;
+        let expected = input.replace("<Version>1.1.0</Version>", "<Version>1.2.0</Version>");
+        let result = CsprojUpdater { max_depth: 0 }
+            .process(input, SemVer::new(1, 2, 0))
+            .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn csproj_updates_the_first_property_group_if_all_are_conditional() {
+        let input = r#"<?xml version="1.0" encoding="utf-8"?>
+<Project Sdk="Microsoft.NET.Sdk">
+  <PropertyGroup Condition="$(Configuration) == Debug">
+    <Version>1.1.0</Version>
+  </PropertyGroup>
+  <PropertyGroup Condition="$(Configuration) == Release">
+    <Version>1.1.0</Version>
+  </PropertyGroup>
+</Project>
+"#;
+        let expected = input.replacen("<Version>1.1.0</Version>", "<Version>1.2.0</Version>", 1);
+        let result = CsprojUpdater { max_depth: 0 }
+            .process(input, SemVer::new(1, 2, 0))
+            .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_assembly_info_vb() {
+        let input = "Imports System\r
+Imports System.Reflection\r
+Imports System.Runtime.InteropServices\r
+\r
+' General Information about an assembly is controlled through the following\r
+' set of attributes.\r
+<Assembly: AssemblyTitle(\"MyApp\")>\r
+<Assembly: AssemblyCompany(\"Tom & Jerry\")>\r
+<Assembly: ComVisible(False)>\r
+\r
+' Version information for an assembly consists of the following four values:\r
+' <Assembly: AssemblyVersion(\"1.0.*\")>\r
+<Assembly: AssemblyVersion(\"1.0.0.0\")>\r
+<assembly: AssemblyFileVersionAttribute( \"1.0.0\" )>\r
+<Assembly: AssemblyInformationalVersion(\"1.0.0\")>\r
+";
+        let expected = input
+            .replace(
+                "AssemblyVersion(\"1.0.0.0\")",
+                "AssemblyVersion(\"1.2.3.0\")",
+            )
+            .replace("( \"1.0.0\" )", "( \"1.2.3\" )")
+            .replace(
+                "InformationalVersion(\"1.0.0\")",
+                "InformationalVersion(\"1.2.3\")",
+            );
+        let updater = AssemblyInfoVbUpdater {
+            max_depth: 0,
+            build_counter: None,
+        };
+        assert_eq!(
+            updater.process(input, SemVer::new(1, 2, 3)).unwrap(),
+            expected
+        );
+        assert_eq!(
+            updater.extract_version(input),
+            Some(SemVer::new(1, 0, 0).into())
+        );
+        assert_eq!(
+            updater.extract_version("<Assembly: AssemblyVersion(\"2.1.0.7\")>\n"),
+            Some(SemVer::new(2, 1, 0).into())
+        );
+    }
+
+    #[test]
+    fn test_assembly_info_vb_build_counter() {
+        let input = "<Assembly: AssemblyVersion(\"1.0.0.0\")>
+<Assembly: AssemblyFileVersion(\"1.0.0\")>
+<Assembly: AssemblyInformationalVersion(\"1.0.0\")>
+";
+        let updater = AssemblyInfoVbUpdater {
+            max_depth: 0,
+            build_counter: Some(42),
+        };
+        assert_eq!(
+            updater.process(input, SemVer::new(1, 2, 3)).unwrap(),
+            "<Assembly: AssemblyVersion(\"1.2.3.42\")>
+<Assembly: AssemblyFileVersion(\"1.2.3.42\")>
+<Assembly: AssemblyInformationalVersion(\"1.2.3\")>
+"
+        );
+    }
+
+    #[test]
+    fn test_assembly_info_vb_in_my_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let my_project = dir.path().join("My Project");
+        std::fs::create_dir(&my_project).unwrap();
+        std::fs::write(
+            my_project.join("AssemblyInfo.vb"),
+            "<Assembly: AssemblyVersion(\"1.0.0.0\")>\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("Module1.vb"),
+            "Module Module1\nEnd Module\n",
+        )
+        .unwrap();
+
+        let result = AssemblyInfoVbUpdater {
+            max_depth: 0,
+            build_counter: None,
+        }
+        .update(dir.path().to_str().unwrap(), SemVer::new(1, 1, 0))
+        .unwrap();
+        assert_eq!(
+            result,
+            vec![(
+                my_project.join("AssemblyInfo.vb"),
+                "<Assembly: AssemblyVersion(\"1.1.0.0\")>\n".into()
+            )]
+        );
+    }
+}
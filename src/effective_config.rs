@@ -0,0 +1,246 @@
+//! The effective configuration, as printed by `--dump-config`
+
+use crate::config::{Config, CONFIG_FILE_NAME};
+use crate::json_util::quote_string;
+use std::str::FromStr;
+
+/// The format of a printed configuration.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ConfigFormat {
+    /// One `key = value` line per setting, with its source as a comment.
+    #[default]
+    Toml,
+
+    /// A single JSON object, with the value and the source of every setting.
+    Json,
+}
+
+impl ConfigFormat {
+    /// The names of the formats, as accepted by [ConfigFormat::from_str].
+    pub const NAMES: [&'static str; 2] = ["toml", "json"];
+}
+
+impl FromStr for ConfigFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "toml" => Ok(Self::Toml),
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Where the value of a setting comes from, from the lowest to the highest precedence.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Source {
+    /// The built-in default (or no value at all).
+    Default,
+
+    /// The `.yart.toml` configuration file.
+    ConfigFile,
+
+    /// A `YART_*` environment variable.
+    Env,
+
+    /// The command line.
+    CommandLine,
+}
+
+impl Source {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::ConfigFile => CONFIG_FILE_NAME,
+            Self::Env => "env",
+            Self::CommandLine => "command line",
+        }
+    }
+}
+
+/// The value of a setting.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    String(String),
+    List(Vec<String>),
+}
+
+impl Value {
+    /// Formats the value as TOML, which is also valid JSON.
+    fn format(&self) -> String {
+        match self {
+            Self::Bool(value) => value.to_string(),
+            Self::String(value) => quote_string(value),
+            Self::List(values) => format!(
+                "[{}]",
+                values
+                    .iter()
+                    .map(|value| quote_string(value))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+/// A resolved setting.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Setting {
+    pub name: String,
+
+    /// `None` if the setting has neither a value nor a default.
+    pub value: Option<Value>,
+
+    pub source: Source,
+}
+
+/// The settings yart would use, after resolving all of their sources.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EffectiveConfig {
+    pub settings: Vec<Setting>,
+}
+
+impl EffectiveConfig {
+    pub fn push(&mut self, name: &str, value: Option<Value>, source: Source) {
+        self.settings.push(Setting {
+            name: name.to_owned(),
+            value,
+            source,
+        });
+    }
+
+    /// Adds the settings of the configuration file that are set.
+    pub fn add_config_file(&mut self, config: &Config) {
+        if let Some(build_date_file) = &config.build_date_file {
+            self.push(
+                "build_date_file",
+                Some(Value::String(build_date_file.clone())),
+                Source::ConfigFile,
+            );
+        }
+        if !config.ignore_dirs.is_empty() {
+            self.push(
+                "ignore_dirs",
+                Some(Value::List(config.ignore_dirs.clone())),
+                Source::ConfigFile,
+            );
+        }
+        if !config.cargo_extra_keys.is_empty() {
+            self.push(
+                "cargo.extra_keys",
+                Some(Value::List(config.cargo_extra_keys.clone())),
+                Source::ConfigFile,
+            );
+        }
+        if !config.generic.is_empty() {
+            self.push(
+                "generic.files",
+                Some(Value::List(
+                    config
+                        .generic
+                        .iter()
+                        .map(|entry| entry.file.clone())
+                        .collect(),
+                )),
+                Source::ConfigFile,
+            );
+        }
+    }
+
+    /// Finds a setting by name.
+    pub fn get(&self, name: &str) -> Option<&Setting> {
+        self.settings.iter().find(|setting| setting.name == name)
+    }
+
+    pub fn format(&self, format: ConfigFormat) -> String {
+        match format {
+            ConfigFormat::Toml => self.to_toml(),
+            ConfigFormat::Json => self.to_json(),
+        }
+    }
+
+    /// TOML has no null, so settings without a value are commented out.
+    fn to_toml(&self) -> String {
+        let mut result = String::new();
+        for setting in &self.settings {
+            match &setting.value {
+                Some(value) => result.push_str(&format!(
+                    "{} = {} # {}\n",
+                    setting.name,
+                    value.format(),
+                    setting.source.name()
+                )),
+                None => result.push_str(&format!("# {} is not set\n", setting.name)),
+            }
+        }
+        result
+    }
+
+    fn to_json(&self) -> String {
+        let settings: Vec<String> = self
+            .settings
+            .iter()
+            .map(|setting| {
+                format!(
+                    "{}:{{\"value\":{},\"source\":{}}}",
+                    quote_string(&setting.name),
+                    setting
+                        .value
+                        .as_ref()
+                        .map_or_else(|| "null".to_owned(), Value::format),
+                    quote_string(setting.source.name())
+                )
+            })
+            .collect();
+        format!("{{{}}}\n", settings.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> EffectiveConfig {
+        let mut config = EffectiveConfig::default();
+        config.push(
+            "dir",
+            Some(Value::String("app".to_owned())),
+            Source::CommandLine,
+        );
+        config.push("no-push", Some(Value::Bool(true)), Source::Env);
+        config.push("build-number", None, Source::Default);
+        config.add_config_file(&Config {
+            ignore_dirs: vec!["vendor".to_owned(), "dist".to_owned()],
+            ..Config::default()
+        });
+        config
+    }
+
+    #[test]
+    fn test_to_toml() {
+        assert_eq!(
+            sample().format(ConfigFormat::Toml),
+            r#"dir = "app" # command line
+no-push = true # env
+# build-number is not set
+ignore_dirs = ["vendor", "dist"] # .yart.toml
+"#
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            sample().format(ConfigFormat::Json),
+            concat!(
+                r#"{"dir":{"value":"app","source":"command line"},"#,
+                r#""no-push":{"value":true,"source":"env"},"#,
+                r#""build-number":{"value":null,"source":"default"},"#,
+                r#""ignore_dirs":{"value":["vendor", "dist"],"source":".yart.toml"}}"#,
+                "\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Handles Elixir projects (mix.exs), including umbrella projects
+use crate::files::{ContentProcessor, FileFinder};
+use crate::SemVer;
+use std::fs;
+use std::path::PathBuf;
+
+/// Updates the version of `mix.exs` files.
+///
+/// Finds the `mix.exs` of the root directory and, for umbrella projects,
+/// the `mix.exs` of every app under `apps/`.
+pub struct MixUpdater {}
+
+impl FileFinder for MixUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let dir_path_buf = PathBuf::from(dir);
+        let mut result: Vec<PathBuf> = Vec::new();
+        let root_mix = dir_path_buf.join("mix.exs");
+        if root_mix.is_file() {
+            result.push(root_mix);
+        }
+        let apps_dir = dir_path_buf.join("apps");
+        if apps_dir.is_dir() {
+            let mut app_mixes: Vec<PathBuf> = Vec::new();
+            for res_entry in fs::read_dir(apps_dir)? {
+                let app_mix = res_entry?.path().join("mix.exs");
+                if app_mix.is_file() {
+                    app_mixes.push(app_mix);
+                }
+            }
+            app_mixes.sort();
+            result.append(&mut app_mixes);
+        }
+        Ok(result)
+    }
+}
+
+impl ContentProcessor for MixUpdater {
+    type Err = std::io::Error; // does not really throw error
+
+    /// Updates the `@version "..."` module attribute and the `version: "..."`
+    /// keyword of the project. A `mix.exs` without a version
+    /// (e.g. the root of an umbrella project) is left unchanged.
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        let mut result = String::new();
+        for line in old_contents.split_inclusive('\n') {
+            match process_line(line, version) {
+                Some(new_line) => result.push_str(&new_line),
+                None => result.push_str(line),
+            }
+        }
+        Ok(result)
+    }
+}
+
+fn process_line(line: &str, version: SemVer) -> Option<String> {
+    let value_start = find_value_start(line)?;
+    let value_len = line[value_start..].find('"')?;
+    Some(format!(
+        "{}{}{}",
+        &line[..value_start],
+        version,
+        &line[value_start + value_len..]
+    ))
+}
+
+/// Finds the position after the opening quote of the version of the line.
+fn find_value_start(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("@version \"") {
+        return Some(line.len() - trimmed.len() + "@version \"".len());
+    }
+    let pos = line.find("version: \"")?;
+    let is_keyword = !matches!(
+        line[..pos].chars().next_back(),
+        Some(ch) if ch.is_alphanumeric() || ch == '_' || ch == '@'
+    );
+    if is_keyword {
+        Some(pos + "version: \"".len())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::DirUpdater;
+
+    #[test]
+    fn test_process_module_attribute() {
+        let input = r#"defmodule MyApp.MixProject do
+  use Mix.Project
+
+  @version "0.1.0"
+
+  def project do
+    [
+      app: :my_app,
+      version: @version,
+      deps: deps()
+    ]
+  end
+
+  defp deps do
+    [{:jason, "~> 1.2"}]
+  end
+end
+"#;
+        let expected = input.replace("@version \"0.1.0\"", "@version \"1.2.3\"");
+        let result = MixUpdater {}.process(input, SemVer::new(1, 2, 3)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_process_keyword() {
+        let input = "  def project do\r\n    [app: :my_app,\r\n     version: \"0.1.0\",\r\n     deps: deps()]\r\n  end\r\n";
+        let expected = "  def project do\r\n    [app: :my_app,\r\n     version: \"2.0.0\",\r\n     deps: deps()]\r\n  end\r\n";
+        let result = MixUpdater {}.process(input, SemVer::new(2, 0, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_umbrella() {
+        let dir = tempfile::tempdir().unwrap();
+        let root_mix = "defmodule Umbrella.MixProject do\n  def project do\n    [apps_path: \"apps\"]\n  end\nend\n";
+        fs::write(dir.path().join("mix.exs"), root_mix).unwrap();
+        for app in ["api", "core"] {
+            let app_dir = dir.path().join("apps").join(app);
+            fs::create_dir_all(&app_dir).unwrap();
+            fs::write(
+                app_dir.join("mix.exs"),
+                "  def project do\n    [version: \"0.1.0\"]\n  end\n",
+            )
+            .unwrap();
+        }
+
+        let result = MixUpdater {}
+            .update(dir.path().to_str().unwrap(), SemVer::new(0, 2, 0))
+            .unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                (
+                    dir.path().join("apps").join("api").join("mix.exs"),
+                    "  def project do\n    [version: \"0.2.0\"]\n  end\n".into()
+                ),
+                (
+                    dir.path().join("apps").join("core").join("mix.exs"),
+                    "  def project do\n    [version: \"0.2.0\"]\n  end\n".into()
+                ),
+            ]
+        );
+    }
+}
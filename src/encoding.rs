@@ -0,0 +1,126 @@
+//! Handles legacy text encodings
+//!
+//! Some legacy files (e.g. VB6 projects) are not UTF-8 but Windows-1252,
+//! which is a superset of Latin-1 (ISO-8859-1).
+
+/// The characters of the bytes 0x80 to 0x9F in Windows-1252.
+/// Bytes that are not defined map to the equivalent Latin-1 control character.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+/// The encoding of a text file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Encoding {
+    Utf8,
+    Windows1252,
+}
+
+/// The text of a file along with the encoding the file was read in,
+/// so that new contents are written back in the same encoding.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextContents {
+    pub text: String,
+    pub encoding: Encoding,
+}
+
+impl TextContents {
+    pub fn new(text: String, encoding: Encoding) -> Self {
+        Self { text, encoding }
+    }
+
+    /// Encodes the text in its encoding.
+    /// Returns `None` if the encoding does not support some of its characters.
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        match self.encoding {
+            Encoding::Utf8 => Some(self.text.as_bytes().to_vec()),
+            Encoding::Windows1252 => encode_windows_1252(&self.text),
+        }
+    }
+}
+
+/// Text that was not read from a file (e.g. a new file) is UTF-8.
+impl From<String> for TextContents {
+    fn from(text: String) -> Self {
+        Self::new(text, Encoding::Utf8)
+    }
+}
+
+impl From<&str> for TextContents {
+    fn from(text: &str) -> Self {
+        Self::from(text.to_owned())
+    }
+}
+
+/// Decodes the given bytes, falling back to Windows-1252 if they are not valid UTF-8.
+pub fn decode(bytes: Vec<u8>) -> (String, Encoding) {
+    match String::from_utf8(bytes) {
+        Ok(s) => (s, Encoding::Utf8),
+        Err(err) => (decode_windows_1252(err.as_bytes()), Encoding::Windows1252),
+    }
+}
+
+pub fn decode_windows_1252(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| match *b {
+            0x80..=0x9F => WINDOWS_1252_HIGH[(*b - 0x80) as usize],
+            _ => char::from(*b),
+        })
+        .collect()
+}
+
+/// Encodes the given text as Windows-1252.
+/// Returns `None` if the text has characters that Windows-1252 does not support.
+pub fn encode_windows_1252(text: &str) -> Option<Vec<u8>> {
+    text.chars()
+        .map(|ch| match ch as u32 {
+            0x00..=0x7F | 0xA0..=0xFF => Some(ch as u8),
+            _ => WINDOWS_1252_HIGH
+                .iter()
+                .position(|high| *high == ch)
+                .map(|idx| 0x80 + idx as u8),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_utf8() {
+        let (text, encoding) = decode("Café".as_bytes().to_vec());
+        assert_eq!(text, "Café");
+        assert_eq!(encoding, Encoding::Utf8);
+    }
+
+    #[test]
+    fn test_decode_windows_1252() {
+        let (text, encoding) = decode(b"Caf\xE9 \x80 \x93quoted\x94".to_vec());
+        assert_eq!(text, "Café € “quoted”");
+        assert_eq!(encoding, Encoding::Windows1252);
+    }
+
+    #[test]
+    fn test_encode_windows_1252() {
+        assert_eq!(
+            encode_windows_1252("Café € “quoted”").unwrap(),
+            b"Caf\xE9 \x80 \x93quoted\x94".to_vec()
+        );
+        assert!(encode_windows_1252("Ελληνικά").is_none());
+    }
+
+    #[test]
+    fn test_text_contents_to_bytes() {
+        let legacy = TextContents::new("Café".to_owned(), Encoding::Windows1252);
+        assert_eq!(legacy.to_bytes().unwrap(), b"Caf\xE9".to_vec());
+        let utf8 = TextContents::from("Café");
+        assert_eq!(utf8.to_bytes().unwrap(), "Café".as_bytes().to_vec());
+        let unsupported = TextContents::new("Ελληνικά".to_owned(), Encoding::Windows1252);
+        assert!(unsupported.to_bytes().is_none());
+    }
+}
@@ -1,7 +1,10 @@
-use crate::SemVer;
+use crate::encoding::TextContents;
+use crate::sem_ver::ExtractedVersion;
+use crate::{encoding, SemVer};
+use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 /// Finds files in a folder.
 pub trait FileFinder {
@@ -11,8 +14,8 @@ pub trait FileFinder {
 
 /// Checks if the given path has the specified file extension,
 /// without case sensitivity.
-pub fn has_extension(path_buf: &PathBuf, extension: &str) -> bool {
-    match path_buf.extension() {
+pub fn has_extension(path: &Path, extension: &str) -> bool {
+    match path.extension() {
         Some(os_str) => os_str.to_string_lossy().eq_ignore_ascii_case(extension),
         _ => false,
     }
@@ -26,6 +29,65 @@ pub trait ContentProcessor {
     /// Modifies the given text contents so that they indicate
     /// the given semantic version.
     fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err>;
+
+    /// Checks if the given contents already indicate the given version,
+    /// i.e. processing them again would not change anything.
+    /// Processors that always change the contents (e.g. by incrementing a build number)
+    /// override this to ignore those parts.
+    fn is_at_version(&self, contents: &str, version: SemVer) -> bool {
+        self.process(contents, version)
+            .is_ok_and(|new_contents| new_contents == contents)
+    }
+
+    /// Reads the version the given contents currently indicate.
+    /// Processors that cannot tell return `None`, which is the default.
+    fn extract_version(&self, _contents: &str) -> Option<ExtractedVersion> {
+        None
+    }
+
+    /// Allows reading files that are not UTF-8 as Windows-1252.
+    /// Only line-oriented formats should opt in; e.g. XML files declare their own encoding.
+    fn supports_legacy_encoding(&self) -> bool {
+        false
+    }
+}
+
+/// Reads a text file, falling back to Windows-1252 if it is not UTF-8.
+pub fn read_legacy_text(path: &Path) -> std::io::Result<String> {
+    Ok(read_legacy_contents(path)?.text)
+}
+
+/// Like [read_legacy_text], but keeps the encoding the file was read in.
+pub fn read_legacy_contents(path: &Path) -> std::io::Result<TextContents> {
+    let (text, encoding) = encoding::decode(fs::read(path)?);
+    Ok(TextContents::new(text, encoding))
+}
+
+/// Makes the new contents end with a line break if and only if the old contents did.
+///
+/// Processors that rebuild a file (e.g. line by line or through an XML writer)
+/// call this, so that a release never adds or removes the final line break.
+/// The line break the processor wrote is kept; if it wrote none,
+/// the one of the old contents (`\n` or `\r\n`) is used.
+/// Spaces and tabs after the final line break of the old contents are ignored.
+pub fn keep_trailing_newline(old_contents: &str, mut new_contents: String) -> String {
+    let old_contents = old_contents.trim_end_matches(&[' ', '\t'][..]);
+    let ending = if new_contents.ends_with("\r\n") {
+        "\r\n"
+    } else if new_contents.ends_with('\n') {
+        "\n"
+    } else if old_contents.ends_with("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    if new_contents.ends_with('\n') {
+        new_contents.truncate(new_contents.len() - ending.len());
+    }
+    if old_contents.ends_with('\n') {
+        new_contents.push_str(ending);
+    }
+    new_contents
 }
 
 #[derive(Debug)]
@@ -56,11 +118,218 @@ impl Display for UpdateError {
 
 impl std::error::Error for UpdateError {}
 
+/// Updates the version of the files of a folder.
 pub trait DirUpdater {
-    fn update(&self, dir: &str, new_version: SemVer)
-        -> Result<Vec<(PathBuf, String)>, UpdateError>;
+    /// Computes the new contents of the files of the folder that need to change
+    /// to indicate the given version.
+    ///
+    /// Nothing is written to disk or staged in git;
+    /// the caller is responsible for writing the returned contents,
+    /// in the encoding their file was read in.
+    fn update(
+        &self,
+        dir: &str,
+        new_version: SemVer,
+    ) -> Result<Vec<(PathBuf, TextContents)>, UpdateError>;
+
+    /// Like [DirUpdater::update], but an updater made of several others continues
+    /// with the rest after one of them fails. Returns the files of the updaters
+    /// that succeeded, along with the errors of those that failed.
+    fn update_keep_going(
+        &self,
+        dir: &str,
+        new_version: SemVer,
+    ) -> (Vec<(PathBuf, TextContents)>, Vec<UpdateError>) {
+        match self.update(dir, new_version) {
+            Ok(files) => (files, Vec::new()),
+            Err(err) => (Vec::new(), vec![err]),
+        }
+    }
+
+    /// Reads the versions that the files of the folder currently indicate.
+    /// Files whose version cannot be read are left out.
+    fn extract_versions(
+        &self,
+        _dir: &str,
+    ) -> Result<Vec<(PathBuf, ExtractedVersion)>, UpdateError> {
+        Ok(Vec::new())
+    }
+
+    /// Finds the files of the folder that this updater is responsible for,
+    /// whether they already indicate a given version or not.
+    fn find_files(&self, _dir: &str) -> Result<Vec<PathBuf>, UpdateError> {
+        Ok(Vec::new())
+    }
+
+    /// Finds the files that do not indicate the given version.
+    fn find_outdated(&self, dir: &str, version: SemVer) -> Result<Vec<PathBuf>, UpdateError> {
+        Ok(self
+            .update(dir, version)?
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect())
+    }
+}
+
+/// The name of the file that lists the paths yart must never modify,
+/// expected at the root directory.
+pub const IGNORE_FILE_NAME: &str = ".yartignore";
+
+/// The paths of a `.yartignore` file, in gitignore syntax.
+///
+/// Supported are comments, negation (`!`), directory patterns (trailing `/`),
+/// patterns anchored to the root directory (containing a `/`) and the wildcards
+/// `*`, `?` and `**`.
+#[derive(Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug)]
+struct IgnoreRule {
+    segments: Vec<String>,
+    anchored: bool,
+    dir_only: bool,
+    negated: bool,
+}
+
+impl IgnoreRules {
+    /// Loads the `.yartignore` file of the given directory.
+    /// If the file does not exist, nothing is ignored.
+    pub fn load<P: AsRef<Path>>(dir: P) -> std::io::Result<Self> {
+        let path = dir.as_ref().join(IGNORE_FILE_NAME);
+        if path.is_file() {
+            Ok(Self::parse(&fs::read_to_string(path)?))
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        let mut rules = Vec::<IgnoreRule>::new();
+        for line in contents.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, pattern) = match line.strip_prefix('!') {
+                Some(pattern) => (true, pattern),
+                None => (false, line),
+            };
+            let (dir_only, pattern) = match pattern.strip_suffix('/') {
+                Some(pattern) => (true, pattern),
+                None => (false, pattern),
+            };
+            // a slash at the start or in the middle anchors the pattern to the root
+            let anchored = pattern.contains('/');
+            let segments: Vec<String> = pattern
+                .trim_start_matches('/')
+                .split('/')
+                .map(str::to_owned)
+                .collect();
+            if segments.iter().all(String::is_empty) {
+                continue;
+            }
+            rules.push(IgnoreRule {
+                segments,
+                anchored,
+                dir_only,
+                negated,
+            });
+        }
+        Self { rules }
+    }
+
+    /// Checks if the given path, relative to the root directory, is ignored.
+    /// A file inside an ignored directory is ignored too.
+    pub fn is_ignored(&self, relative_path: &Path) -> bool {
+        let components: Vec<String> = relative_path
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+        for end in 1..=components.len() {
+            let is_dir = end < components.len();
+            let ignored = self
+                .rules
+                .iter()
+                .rev()
+                .find(|rule| rule.matches(&components[..end], is_dir))
+                .is_some_and(|rule| !rule.negated);
+            if ignored || !is_dir {
+                return ignored;
+            }
+        }
+        false
+    }
+
+    /// Removes the ignored files, given relative to `dir` or joined with it.
+    pub fn retain_not_ignored(&self, dir: &str, files: Vec<PathBuf>) -> Vec<PathBuf> {
+        files
+            .into_iter()
+            .filter(|file| !self.is_ignored(file.strip_prefix(dir).unwrap_or(file)))
+            .collect()
+    }
+}
+
+impl IgnoreRule {
+    fn matches(&self, components: &[String], is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            false
+        } else if self.anchored {
+            matches_segments(&self.segments, components)
+        } else {
+            components
+                .last()
+                .is_some_and(|name| matches_glob(&self.segments[0], name))
+        }
+    }
+}
+
+/// Matches path components against pattern segments, where `**` matches any number of components.
+fn matches_segments(segments: &[String], components: &[String]) -> bool {
+    match segments.split_first() {
+        None => components.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=components.len()).any(|skip| matches_segments(rest, &components[skip..]))
+        }
+        Some((first, rest)) => match components.split_first() {
+            Some((component, other_components)) => {
+                matches_glob(first, component) && matches_segments(rest, other_components)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Matches a single file name against a pattern with the wildcards `*` and `?`.
+fn matches_glob(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // the positions to continue from, after the last `*`
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|ch| *ch == '*')
 }
 
+/// Files listed in `.yartignore` are never updated.
 impl<T> DirUpdater for T
 where
     T: FileFinder + ContentProcessor,
@@ -70,34 +339,107 @@ where
         &self,
         dir: &str,
         new_version: SemVer,
-    ) -> Result<Vec<(PathBuf, String)>, UpdateError> {
-        let files = self.find(dir)?;
-        let mut result = Vec::<(PathBuf, String)>::new();
+    ) -> Result<Vec<(PathBuf, TextContents)>, UpdateError> {
+        let files = IgnoreRules::load(dir)?.retain_not_ignored(dir, self.find(dir)?);
+        let mut result = Vec::<(PathBuf, TextContents)>::new();
         for file in files {
-            let old_contents = fs::read_to_string(&file)?;
+            let old_contents = read_for_processor(self, &file)?;
             let changed_contents = self
-                .process(&old_contents, new_version)
+                .process(&old_contents.text, new_version)
                 .map_err(UpdateError::new_boxing_other)?;
-            if old_contents != changed_contents {
-                result.push((file, changed_contents));
+            if old_contents.text != changed_contents {
+                // written back in the encoding it was read in
+                result.push((
+                    file,
+                    TextContents::new(changed_contents, old_contents.encoding),
+                ));
+            }
+        }
+        Ok(result)
+    }
+
+    fn find_files(&self, dir: &str) -> Result<Vec<PathBuf>, UpdateError> {
+        Ok(IgnoreRules::load(dir)?.retain_not_ignored(dir, self.find(dir)?))
+    }
+
+    fn find_outdated(&self, dir: &str, version: SemVer) -> Result<Vec<PathBuf>, UpdateError> {
+        let mut result = Vec::<PathBuf>::new();
+        for file in IgnoreRules::load(dir)?.retain_not_ignored(dir, self.find(dir)?) {
+            let contents = read_for_processor(self, &file)?.text;
+            // processing again must not change anything, and the version that can be
+            // read back must be the given one (a processor may consistently write a wrong one)
+            let is_outdated = !self.is_at_version(&contents, version)
+                || self
+                    .extract_version(&contents)
+                    .is_some_and(|extracted| extracted.version != version);
+            if is_outdated {
+                result.push(file);
+            }
+        }
+        Ok(result)
+    }
+
+    fn extract_versions(&self, dir: &str) -> Result<Vec<(PathBuf, ExtractedVersion)>, UpdateError> {
+        let mut result = Vec::<(PathBuf, ExtractedVersion)>::new();
+        for file in IgnoreRules::load(dir)?.retain_not_ignored(dir, self.find(dir)?) {
+            let contents = read_for_processor(self, &file)?.text;
+            if let Some(version) = self.extract_version(&contents) {
+                result.push((file, version));
             }
         }
         Ok(result)
     }
 }
 
-/// Finds files in a folder that match a given file extension.
+fn read_for_processor<T: ContentProcessor>(
+    processor: &T,
+    file: &Path,
+) -> std::io::Result<TextContents> {
+    if processor.supports_legacy_encoding() {
+        read_legacy_contents(file)
+    } else {
+        fs::read_to_string(file).map(TextContents::from)
+    }
+}
+
+/// Removes files that were found more than once, keeping the first occurrence,
+/// and sorts the remaining files by path.
+/// Paths are compared after being canonicalized, so that the same file
+/// reached in different ways (e.g. `dir/a.vbp` and `dir/./a.vbp`) is only written once.
+pub fn dedup_and_sort<T>(files: Vec<(PathBuf, T)>) -> Vec<(PathBuf, T)> {
+    let mut seen = HashSet::<PathBuf>::new();
+    let mut result: Vec<(PathBuf, T)> = files
+        .into_iter()
+        .filter(|(path, _)| seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+        .collect();
+    result.sort_by(|(left, _), (right, _)| left.cmp(right));
+    result
+}
+
+/// Finds files in a folder that match any of the given file extensions.
 /// Does not search sub-folders, only root folder.
 pub struct RootFileFinderByExt {
-    extension: String,
+    extensions: Vec<String>,
 }
 
 impl RootFileFinderByExt {
+    /// Creates a finder that matches a single file extension.
     pub fn new(extension: &str) -> Self {
+        Self::with_extensions(&[extension])
+    }
+
+    /// Creates a finder that matches any of the given file extensions.
+    pub fn with_extensions(extensions: &[&str]) -> Self {
         Self {
-            extension: extension.to_owned(),
+            extensions: extensions.iter().map(|e| (*e).to_owned()).collect(),
         }
     }
+
+    fn matches(&self, path: &Path) -> bool {
+        self.extensions
+            .iter()
+            .any(|extension| has_extension(path, extension))
+    }
 }
 
 impl FileFinder for RootFileFinderByExt {
@@ -106,10 +448,374 @@ impl FileFinder for RootFileFinderByExt {
         for res_entry in fs::read_dir(dir)? {
             let entry = res_entry?;
             let path = entry.path();
-            if path.is_file() && has_extension(&path, &self.extension) {
+            if path.is_file() && self.matches(&path) {
                 result.push(path);
             }
         }
         Ok(result)
     }
 }
+
+/// Folder names that are never searched by `RecursiveFileFinderByExt`.
+pub const DEFAULT_IGNORE_DIRS: [&str; 3] = [".git", "target", "node_modules"];
+
+/// Finds files in a folder and its sub-folders that match any of the given file extensions.
+/// Sub-folders deeper than `max_depth` are not searched; depth 0 means only the root folder.
+/// Sub-folders named like one of the ignored folders are skipped.
+pub struct RecursiveFileFinderByExt {
+    root_finder: RootFileFinderByExt,
+    max_depth: usize,
+    ignore_dirs: Vec<String>,
+}
+
+impl RecursiveFileFinderByExt {
+    pub fn new(extensions: &[&str], max_depth: usize) -> Self {
+        Self {
+            root_finder: RootFileFinderByExt::with_extensions(extensions),
+            max_depth,
+            ignore_dirs: DEFAULT_IGNORE_DIRS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// Skips the given folder names, in addition to the default ones.
+    pub fn with_ignore_dirs(mut self, ignore_dirs: &[String]) -> Self {
+        for ignore_dir in ignore_dirs {
+            if !self.ignore_dirs.contains(ignore_dir) {
+                self.ignore_dirs.push(ignore_dir.clone());
+            }
+        }
+        self
+    }
+
+    fn is_ignored(&self, dir: &Path) -> bool {
+        dir.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| self.ignore_dirs.iter().any(|ignored| ignored == name))
+    }
+
+    fn walk(&self, dir: &Path, depth: usize, result: &mut Vec<PathBuf>) -> std::io::Result<()> {
+        for res_entry in fs::read_dir(dir)? {
+            let path = res_entry?.path();
+            if path.is_file() && self.root_finder.matches(&path) {
+                result.push(path);
+            } else if path.is_dir() && depth < self.max_depth && !self.is_ignored(&path) {
+                self.walk(&path, depth + 1, result)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FileFinder for RecursiveFileFinderByExt {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let mut result = Vec::<PathBuf>::new();
+        self.walk(Path::new(dir), 0, &mut result)?;
+        Ok(result)
+    }
+}
+
+/// File names of manifests that usually hold the version of a project,
+/// whether yart can update them or not. Only used by [find_unhandled_manifests].
+pub const KNOWN_MANIFESTS: [&str; 18] = [
+    "Cargo.toml",
+    "package.json",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "pyproject.toml",
+    "setup.py",
+    "setup.cfg",
+    "composer.json",
+    "mix.exs",
+    "pubspec.yaml",
+    "build.sbt",
+    "version.sbt",
+    "build.zig.zon",
+    "metadata.json",
+    "Chart.yaml",
+    "Package.swift",
+    "appcast.xml",
+];
+
+/// Finds the known manifests at the root of the folder
+/// that are not among the handled files (see [DirUpdater::find_files]).
+pub fn find_unhandled_manifests(dir: &str, handled: &[PathBuf]) -> Vec<PathBuf> {
+    KNOWN_MANIFESTS
+        .iter()
+        .map(|name| PathBuf::from(dir).join(name))
+        .filter(|path| path.is_file() && !handled.contains(path))
+        .collect()
+}
+
+/// Returns an explicit list of files, without any discovery heuristics.
+/// Relative paths are resolved against the given folder.
+/// It is an error if any of the files does not exist.
+pub struct ExplicitFileFinder {
+    files: Vec<PathBuf>,
+}
+
+impl ExplicitFileFinder {
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        Self { files }
+    }
+}
+
+impl FileFinder for ExplicitFileFinder {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        self.files
+            .iter()
+            .map(|file| {
+                let path = PathBuf::from(dir).join(file);
+                if path.is_file() {
+                    Ok(path)
+                } else {
+                    Err(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("file not found: {}", path.to_string_lossy()),
+                    ))
+                }
+            })
+            .collect()
+    }
+}
+
+/// Combines any [FileFinder] with any [ContentProcessor],
+/// e.g. to update an explicit list of files with the processor of a known format.
+pub struct FileUpdater<F, P> {
+    pub finder: F,
+    pub processor: P,
+}
+
+impl<F: FileFinder, P> FileFinder for FileUpdater<F, P> {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        self.finder.find(dir)
+    }
+}
+
+impl<F, P: ContentProcessor> ContentProcessor for FileUpdater<F, P> {
+    type Err = P::Err;
+
+    fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
+        self.processor.process(old_contents, new_version)
+    }
+
+    fn is_at_version(&self, contents: &str, version: SemVer) -> bool {
+        self.processor.is_at_version(contents, version)
+    }
+
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        self.processor.extract_version(contents)
+    }
+
+    fn supports_legacy_encoding(&self) -> bool {
+        self.processor.supports_legacy_encoding()
+    }
+}
+
+impl<P: ContentProcessor + ?Sized> ContentProcessor for Box<P> {
+    type Err = P::Err;
+
+    fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
+        self.as_ref().process(old_contents, new_version)
+    }
+
+    fn is_at_version(&self, contents: &str, version: SemVer) -> bool {
+        self.as_ref().is_at_version(contents, version)
+    }
+
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        self.as_ref().extract_version(contents)
+    }
+
+    fn supports_legacy_encoding(&self) -> bool {
+        self.as_ref().supports_legacy_encoding()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keep_trailing_newline() {
+        // added by the processor
+        assert_eq!(keep_trailing_newline("a", "b\n".to_owned()), "b");
+        assert_eq!(keep_trailing_newline("a", "b\r\n".to_owned()), "b");
+        assert_eq!(keep_trailing_newline("", "b\n".to_owned()), "b");
+        // dropped by the processor
+        assert_eq!(keep_trailing_newline("a\n", "b".to_owned()), "b\n");
+        assert_eq!(keep_trailing_newline("a\r\n", "b".to_owned()), "b\r\n");
+        // kept by the processor
+        assert_eq!(keep_trailing_newline("a\n", "b\n".to_owned()), "b\n");
+        assert_eq!(keep_trailing_newline("a\n", "b\r\n".to_owned()), "b\r\n");
+        assert_eq!(keep_trailing_newline("a\n\n", "b\n\n".to_owned()), "b\n\n");
+        assert_eq!(keep_trailing_newline("a", "b".to_owned()), "b");
+        assert_eq!(keep_trailing_newline("a\n  ", "b".to_owned()), "b\n");
+    }
+
+    #[test]
+    fn root_file_finder_matches_any_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in &["a.vbp", "b.VBG", "c.txt"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        fs::create_dir(dir.path().join("sub.vbp")).unwrap();
+
+        let finder = RootFileFinderByExt::with_extensions(&["vbp", "vbg"]);
+        let mut result = finder.find(dir.path().to_str().unwrap()).unwrap();
+        result.sort();
+
+        assert_eq!(
+            result,
+            vec![dir.path().join("a.vbp"), dir.path().join("b.VBG")]
+        );
+    }
+
+    #[test]
+    fn root_file_finder_single_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in &["a.lpi", "b.lpk"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let finder = RootFileFinderByExt::new("lpi");
+        let result = finder.find(dir.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(result, vec![dir.path().join("a.lpi")]);
+    }
+
+    #[test]
+    fn recursive_file_finder_max_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        let level1 = dir.path().join("src");
+        let level2 = level1.join("App");
+        fs::create_dir_all(&level2).unwrap();
+        for path in &[
+            dir.path().join("Root.csproj"),
+            level1.join("Lib.csproj"),
+            level2.join("App.csproj"),
+            level2.join("App.cs"),
+        ] {
+            fs::write(path, "").unwrap();
+        }
+        let dir_str = dir.path().to_str().unwrap();
+        let find = |max_depth| {
+            let mut result = RecursiveFileFinderByExt::new(&["csproj"], max_depth)
+                .find(dir_str)
+                .unwrap();
+            result.sort();
+            result
+        };
+
+        assert_eq!(find(0), vec![dir.path().join("Root.csproj")]);
+        assert_eq!(
+            find(1),
+            vec![dir.path().join("Root.csproj"), level1.join("Lib.csproj")]
+        );
+        assert_eq!(
+            find(2),
+            vec![
+                dir.path().join("Root.csproj"),
+                level2.join("App.csproj"),
+                level1.join("Lib.csproj")
+            ]
+        );
+    }
+
+    #[test]
+    fn recursive_file_finder_ignore_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        for sub_dir in &["src", "vendor", "node_modules"] {
+            fs::create_dir(dir.path().join(sub_dir)).unwrap();
+            fs::write(dir.path().join(sub_dir).join("Lib.csproj"), "").unwrap();
+        }
+
+        let mut result = RecursiveFileFinderByExt::new(&["csproj"], 1)
+            .with_ignore_dirs(&["vendor".to_owned()])
+            .find(dir.path().to_str().unwrap())
+            .unwrap();
+        result.sort();
+
+        assert_eq!(result, vec![dir.path().join("src").join("Lib.csproj")]);
+    }
+
+    #[test]
+    fn ignore_rules() {
+        let rules = IgnoreRules::parse(
+            "# fixtures\ntemplates/\n/Cargo.toml\n*.nuspec\n!keep.nuspec\ndocs/**/*.csproj\n",
+        );
+        assert!(rules.is_ignored(Path::new("Cargo.toml")));
+        assert!(!rules.is_ignored(Path::new("app/Cargo.toml")));
+        assert!(rules.is_ignored(Path::new("templates/Cargo.toml")));
+        assert!(rules.is_ignored(Path::new("src/templates/app/App.csproj")));
+        assert!(!rules.is_ignored(Path::new("templates")));
+        assert!(rules.is_ignored(Path::new("src/App.nuspec")));
+        assert!(!rules.is_ignored(Path::new("keep.nuspec")));
+        assert!(rules.is_ignored(Path::new("docs/App.csproj")));
+        assert!(rules.is_ignored(Path::new("docs/a/b/App.csproj")));
+        assert!(!rules.is_ignored(Path::new("src/docs/App.csproj")));
+    }
+
+    #[test]
+    fn matches_glob_wildcards() {
+        assert!(matches_glob("*.toml", "Cargo.toml"));
+        assert!(matches_glob("Cargo.*", "Cargo.lock"));
+        assert!(matches_glob("?pp", "app"));
+        assert!(matches_glob("*", ""));
+        assert!(matches_glob("a*b*c", "aXbYbZc"));
+        assert!(!matches_glob("*.toml", "Cargo.lock"));
+        assert!(!matches_glob("?pp", "pp"));
+    }
+
+    #[test]
+    fn ignored_file_is_not_updated() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_toml = "[package]\nname = \"app\"\nversion = \"0.1.0\"\n";
+        fs::write(dir.path().join("Cargo.toml"), cargo_toml).unwrap();
+        let updater = FileUpdater {
+            finder: ExplicitFileFinder::new(vec![PathBuf::from("Cargo.toml")]),
+            processor: crate::rust::CargoTomlContentProcessor::new(),
+        };
+        let dir_str = dir.path().to_str().unwrap();
+        assert_eq!(
+            updater.update(dir_str, SemVer::new(0, 2, 0)).unwrap().len(),
+            1
+        );
+
+        fs::write(dir.path().join(IGNORE_FILE_NAME), "Cargo.toml\n").unwrap();
+
+        assert!(updater
+            .update(dir_str, SemVer::new(0, 2, 0))
+            .unwrap()
+            .is_empty());
+        assert!(updater
+            .find_outdated(dir_str, SemVer::new(0, 2, 0))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn unhandled_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in &["Cargo.toml", "package.json", "README.md"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let dir_str = dir.path().to_str().unwrap();
+
+        let result =
+            find_unhandled_manifests(dir_str, &[PathBuf::from(dir_str).join("Cargo.toml")]);
+
+        assert_eq!(result, vec![PathBuf::from(dir_str).join("package.json")]);
+    }
+
+    #[test]
+    fn explicit_file_finder_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.toml"), "").unwrap();
+
+        let finder = ExplicitFileFinder::new(vec!["a.toml".into(), "b.toml".into()]);
+        let err = finder.find(dir.path().to_str().unwrap()).unwrap_err();
+
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,492 @@
+//! Updates files that are configured in `.yart.toml`
+
+use crate::config::{GenericEntry, GenericLocation};
+use crate::encoding::TextContents;
+use crate::files::{ContentProcessor, DirUpdater, UpdateError};
+use crate::json_util::{self, JsonError};
+use crate::toml_util::{self, TomlError};
+use crate::yaml_util::{set_scalar_at_path, YamlError};
+use crate::{config, SemVer};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::PathBuf;
+
+/// Sets the version at a dotted path of a JSON document.
+pub struct JsonPathProcessor {
+    path: String,
+}
+
+impl JsonPathProcessor {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+        }
+    }
+}
+
+impl ContentProcessor for JsonPathProcessor {
+    type Err = JsonError;
+
+    fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
+        json_util::set_string_at_path(old_contents, &self.path, &new_version.to_string())
+    }
+}
+
+/// Sets the version at a dotted path of a YAML document.
+pub struct YamlPathProcessor {
+    path: String,
+}
+
+impl YamlPathProcessor {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+        }
+    }
+}
+
+impl ContentProcessor for YamlPathProcessor {
+    type Err = YamlError;
+
+    fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
+        set_scalar_at_path(old_contents, &self.path, &new_version.to_string())
+    }
+}
+
+/// Sets the version at a dotted path of a TOML document
+/// (e.g. `tool.poetry.version` of `pyproject.toml`).
+pub struct TomlPathProcessor {
+    path: String,
+}
+
+impl TomlPathProcessor {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+        }
+    }
+}
+
+impl ContentProcessor for TomlPathProcessor {
+    type Err = TomlError;
+
+    fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
+        toml_util::set_string_at_path(old_contents, &self.path, &new_version.to_string())
+    }
+}
+
+#[derive(Debug)]
+pub enum MarkerError {
+    /// The marker is not followed by a version anywhere in the file.
+    NotFound(String),
+
+    /// The marker is empty, so it would match everywhere.
+    Empty,
+}
+
+impl Display for MarkerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(marker) => write!(f, "no version found after marker {}", marker),
+            Self::Empty => write!(f, "the marker must not be empty"),
+        }
+    }
+}
+
+impl std::error::Error for MarkerError {}
+
+/// Sets the version that immediately follows a marker text,
+/// e.g. the version segment of a shields.io badge URL
+/// (marker `badge/version-`) or a `Current version: 1.2.3` line
+/// (marker `Current version:`).
+///
+/// Only a version-shaped substring (`1.2.3` or `v1.2.3`) right after
+/// the marker is replaced, so other numbers in the file are left alone.
+/// A `-prerelease` or `+build` suffix of the old version is replaced too.
+/// After a marker that ends with a dash, like the one of a badge, a single dash
+/// ends the version (e.g. before the color of the badge) and the dashes of the
+/// version are doubled, as shields.io escapes them (`1.2.3--rc.1-blue`).
+pub struct MarkerProcessor {
+    marker: String,
+}
+
+impl MarkerProcessor {
+    pub fn new(marker: &str) -> Self {
+        Self {
+            marker: marker.to_owned(),
+        }
+    }
+}
+
+impl ContentProcessor for MarkerProcessor {
+    type Err = MarkerError;
+
+    fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
+        if self.marker.is_empty() {
+            return Err(MarkerError::Empty);
+        }
+        let mut result = String::new();
+        let mut found = false;
+        let mut rest = old_contents;
+        while let Some(idx) = rest.find(&self.marker) {
+            // the marker is not empty, so the search always moves forward
+            let after_marker = idx + self.marker.len();
+            result.push_str(&rest[..after_marker]);
+            rest = &rest[after_marker..];
+            let after_spaces = rest.trim_start_matches([' ', '\t']);
+            result.push_str(&rest[..rest.len() - after_spaces.len()]);
+            rest = after_spaces;
+            let (prefix, candidate) = match rest.strip_prefix('v') {
+                Some(stripped) => ("v", stripped),
+                None => ("", rest),
+            };
+            if let Some(len) = version_len(candidate, self.marker.ends_with('-')) {
+                found = true;
+                result.push_str(prefix);
+                result.push_str(&new_version.to_string());
+                rest = &candidate[len..];
+            }
+        }
+        result.push_str(rest);
+        if found {
+            Ok(result)
+        } else {
+            Err(MarkerError::NotFound(self.marker.clone()))
+        }
+    }
+}
+
+/// Gets the length of the version at the start of the text: `major.minor.patch`,
+/// followed by an optional `-prerelease` and `+build`.
+/// With `doubled_dashes`, the dashes of the version are written as `--`.
+fn version_len(text: &str, doubled_dashes: bool) -> Option<usize> {
+    let mut len = core_version_len(text)?;
+    let dash = if doubled_dashes { "--" } else { "-" };
+    for separator in [dash, "+"] {
+        if let Some(rest) = text[len..].strip_prefix(separator) {
+            let identifiers = identifiers_len(rest, doubled_dashes);
+            if identifiers > 0 {
+                len += separator.len() + identifiers;
+            }
+        }
+    }
+    Some(len)
+}
+
+/// Gets the length of the dot separated identifiers (e.g. `rc.1`) at the start of the text.
+fn identifiers_len(text: &str, doubled_dashes: bool) -> usize {
+    let mut len = identifier_len(text, doubled_dashes);
+    while len > 0 && text[len..].starts_with('.') {
+        match identifier_len(&text[len + 1..], doubled_dashes) {
+            0 => break,
+            next => len += 1 + next,
+        }
+    }
+    len
+}
+
+fn identifier_len(text: &str, doubled_dashes: bool) -> usize {
+    let dash = if doubled_dashes { "--" } else { "-" };
+    let mut len = 0;
+    loop {
+        let rest = &text[len..];
+        if rest.starts_with(|ch: char| ch.is_ascii_alphanumeric()) {
+            len += 1;
+        } else if rest.starts_with(dash) {
+            len += dash.len();
+        } else {
+            return len;
+        }
+    }
+}
+
+/// Gets the length of the `major.minor.patch` version at the start of the text.
+fn core_version_len(text: &str) -> Option<usize> {
+    let mut len = 0;
+    for part in 0..3 {
+        if part > 0 {
+            if !text[len..].starts_with('.') {
+                return None;
+            }
+            len += 1;
+        }
+        let digits = text[len..]
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(text.len() - len);
+        if digits == 0 {
+            return None;
+        }
+        len += digits;
+    }
+    Some(len)
+}
+
+/// Updates the generic entries of the configuration file.
+#[derive(Default)]
+pub struct GenericDirUpdater {}
+
+impl GenericDirUpdater {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+fn process_entry(
+    entry: &GenericEntry,
+    old_contents: &str,
+    new_version: SemVer,
+) -> Result<String, UpdateError> {
+    match &entry.location {
+        GenericLocation::JsonPath(path) => JsonPathProcessor::new(path)
+            .process(old_contents, new_version)
+            .map_err(UpdateError::new_boxing_other),
+        GenericLocation::YamlPath(path) => YamlPathProcessor::new(path)
+            .process(old_contents, new_version)
+            .map_err(UpdateError::new_boxing_other),
+        GenericLocation::TomlPath(path) => TomlPathProcessor::new(path)
+            .process(old_contents, new_version)
+            .map_err(UpdateError::new_boxing_other),
+        GenericLocation::Marker(marker) => MarkerProcessor::new(marker)
+            .process(old_contents, new_version)
+            .map_err(UpdateError::new_boxing_other),
+    }
+}
+
+impl DirUpdater for GenericDirUpdater {
+    fn update(
+        &self,
+        dir: &str,
+        new_version: SemVer,
+    ) -> Result<Vec<(PathBuf, TextContents)>, UpdateError> {
+        let config = config::load(dir).map_err(UpdateError::new_boxing_other)?;
+        // (path, original contents, updated contents)
+        // several entries can update the same file, e.g. a badge and a text line of README.md
+        let mut files = Vec::<(PathBuf, String, String)>::new();
+        for entry in config.generic.iter() {
+            let path_buf = PathBuf::from(dir).join(&entry.file);
+            match files.iter_mut().find(|(path, _, _)| *path == path_buf) {
+                Some((_, _, contents)) => {
+                    *contents = process_entry(entry, contents, new_version)?;
+                }
+                None => {
+                    let old_contents = fs::read_to_string(&path_buf)?;
+                    let new_contents = process_entry(entry, &old_contents, new_version)?;
+                    files.push((path_buf, old_contents, new_contents));
+                }
+            }
+        }
+        Ok(files
+            .into_iter()
+            .filter(|(_, old_contents, new_contents)| old_contents != new_contents)
+            .map(|(path_buf, _, new_contents)| (path_buf, new_contents.into()))
+            .collect())
+    }
+
+    fn find_files(&self, dir: &str) -> Result<Vec<PathBuf>, UpdateError> {
+        let config = config::load(dir).map_err(UpdateError::new_boxing_other)?;
+        let mut result = Vec::<PathBuf>::new();
+        for entry in config.generic.iter() {
+            let path_buf = PathBuf::from(dir).join(&entry.file);
+            if !result.contains(&path_buf) {
+                result.push(path_buf);
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_path_processor_nested() {
+        let input = r#"{
+  "name": "app",
+  "build": {
+    "number": 42,
+    "version": "0.1.0"
+  }
+}
+"#;
+        let expected = r#"{
+  "name": "app",
+  "build": {
+    "number": 42,
+    "version": "1.2.3"
+  }
+}
+"#;
+        let processor = JsonPathProcessor::new("build.version");
+        let result = processor.process(input, SemVer::new(1, 2, 3)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_json_path_processor_deeply_nested() {
+        let input = r#"{"a": {"version": "0.0.1", "b": {"c": {"version": "0.1.0"}}}}"#;
+        let expected = r#"{"a": {"version": "0.0.1", "b": {"c": {"version": "2.0.0"}}}}"#;
+        let processor = JsonPathProcessor::new("a.b.c.version");
+        let result = processor.process(input, SemVer::new(2, 0, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_json_path_processor_missing_path() {
+        let input = r#"{"build": {"number": 42}}"#;
+        let processor = JsonPathProcessor::new("build.version");
+        let err = processor.process(input, SemVer::new(1, 2, 3)).unwrap_err();
+        assert_eq!(err.to_string(), "JSON path build.version not found");
+    }
+
+    #[test]
+    fn test_yaml_path_processor_nested() {
+        let input = r#"replicaCount: 1
+image:
+  repository: example/app
+  # keep in sync with the chart
+  tag: "0.1.0"
+service:
+  port: 80
+"#;
+        let expected = r#"replicaCount: 1
+image:
+  repository: example/app
+  # keep in sync with the chart
+  tag: "1.2.3"
+service:
+  port: 80
+"#;
+        let processor = YamlPathProcessor::new("image.tag");
+        let result = processor.process(input, SemVer::new(1, 2, 3)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_yaml_path_processor_missing_path() {
+        let input = "image:\n  repository: example/app\n";
+        let processor = YamlPathProcessor::new("image.tag");
+        let err = processor.process(input, SemVer::new(1, 2, 3)).unwrap_err();
+        assert_eq!(err.to_string(), "YAML path image.tag not found");
+    }
+
+    #[test]
+    fn test_toml_path_processor_nested() {
+        let input = "[tool.poetry]\nname = \"app\"\nversion = \"0.1.0\"\n";
+        let expected = "[tool.poetry]\nname = \"app\"\nversion = \"1.2.3\"\n";
+        let processor = TomlPathProcessor::new("tool.poetry.version");
+        let result = processor.process(input, SemVer::new(1, 2, 3)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_marker_processor_badge_url() {
+        let input = "# app\n\n![version](https://img.shields.io/badge/version-1.2.3-blue)\n\nRequires node 18.0.0.\n";
+        let expected = "# app\n\n![version](https://img.shields.io/badge/version-1.3.0-blue)\n\nRequires node 18.0.0.\n";
+        let processor = MarkerProcessor::new("badge/version-");
+        let result = processor.process(input, SemVer::new(1, 3, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_marker_processor_badge_url_prerelease() {
+        let input = "![version](https://img.shields.io/badge/version-1.2.3--rc.1-blue)\n";
+        let expected = "![version](https://img.shields.io/badge/version-1.3.0-blue)\n";
+        let processor = MarkerProcessor::new("badge/version-");
+        let result = processor.process(input, SemVer::new(1, 3, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_marker_processor_prerelease_and_build() {
+        let input = "Current version: 1.2.3-beta.2+build-7.\n";
+        let expected = "Current version: 1.3.0.\n";
+        let processor = MarkerProcessor::new("Current version:");
+        let result = processor.process(input, SemVer::new(1, 3, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_marker_processor_text_line() {
+        let input = "Current version: v1.2.3\n\nSee the 1.2.3 release notes.\n";
+        let expected = "Current version: v2.0.0\n\nSee the 1.2.3 release notes.\n";
+        let processor = MarkerProcessor::new("Current version:");
+        let result = processor.process(input, SemVer::new(2, 0, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_marker_processor_not_found() {
+        let processor = MarkerProcessor::new("Current version:");
+        let err = processor
+            .process("Current version: unreleased\n", SemVer::new(1, 2, 3))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no version found after marker Current version:"
+        );
+    }
+
+    #[test]
+    fn test_marker_processor_empty_marker() {
+        let err = MarkerProcessor::new("")
+            .process("hello 1.2.3\n", SemVer::new(1, 3, 0))
+            .unwrap_err();
+        assert!(matches!(err, MarkerError::Empty));
+    }
+
+    #[test]
+    fn test_generic_dir_updater() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(".yart.toml"),
+            "[[generic]]\nfile = \"app.json\"\njson_path = \"expo.version\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("app.json"),
+            "{ \"expo\": { \"version\": \"0.1.0\" } }\n",
+        )
+        .unwrap();
+
+        let result = GenericDirUpdater::new()
+            .update(dir.path().to_str().unwrap(), SemVer::new(0, 2, 0))
+            .unwrap();
+
+        assert_eq!(
+            result,
+            vec![(
+                dir.path().join("app.json"),
+                "{ \"expo\": { \"version\": \"0.2.0\" } }\n".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_generic_dir_updater_same_file_twice() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(".yart.toml"),
+            "[[generic]]\nfile = \"README.md\"\nmarker = \"badge/version-\"\n\n[[generic]]\nfile = \"README.md\"\nmarker = \"Current version:\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("README.md"),
+            "![version](https://img.shields.io/badge/version-0.1.0-blue)\nCurrent version: 0.1.0\n",
+        )
+        .unwrap();
+
+        let result = GenericDirUpdater::new()
+            .update(dir.path().to_str().unwrap(), SemVer::new(0, 2, 0))
+            .unwrap();
+
+        assert_eq!(
+            result,
+            vec![(
+                dir.path().join("README.md"),
+                "![version](https://img.shields.io/badge/version-0.2.0-blue)\nCurrent version: 0.2.0\n"
+                    .into()
+            )]
+        );
+    }
+}
@@ -2,8 +2,9 @@
 
 use std::ffi::OsStr;
 use std::fmt::Formatter;
-use std::path::Path;
-use std::process::Command;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
 use std::string::FromUtf8Error;
 
 #[derive(Debug)]
@@ -11,6 +12,7 @@ pub enum GitError {
     IOError(std::io::Error),
     FromUtf8Error(FromUtf8Error),
     NonZeroExitCode,
+    UnexpectedOutput(String),
 }
 
 impl std::fmt::Display for GitError {
@@ -19,35 +21,112 @@ impl std::fmt::Display for GitError {
             Self::IOError(e) => std::fmt::Display::fmt(&e, f),
             Self::FromUtf8Error(e) => std::fmt::Display::fmt(&e, f),
             Self::NonZeroExitCode => f.write_str("git returned non-zero exit code"),
+            Self::UnexpectedOutput(output) => write!(f, "unexpected git output: {}", output),
         }
     }
 }
 
 impl std::error::Error for GitError {}
 
-pub fn tags<P: AsRef<Path>>(dir: P) -> Result<String, GitError> {
-    match Command::new("git")
-        .arg("tag")
-        .arg("--list")
-        .current_dir(dir)
-        .output()
-    {
-        Ok(output) => {
-            if output.status.success() {
-                match String::from_utf8(output.stdout) {
-                    Ok(s) => Ok(s),
-                    Err(err) => Err(GitError::FromUtf8Error(err)),
-                }
-            } else {
-                Err(GitError::NonZeroExitCode)
-            }
-        }
-        Err(err) => Err(GitError::IOError(err)),
+/// Runs git with the given arguments in the given directory.
+///
+/// Every function of this module goes through a runner, so that tests can
+/// record the issued commands instead of running them.
+pub trait GitRunner {
+    fn run(&self, args: &[&OsStr], dir: &Path) -> std::io::Result<Output>;
+
+    /// Runs git with the given bytes on its standard input (e.g. for `-F -`).
+    /// Runners that do not run git, like the ones of the tests, may ignore the input.
+    fn run_with_input(
+        &self,
+        args: &[&OsStr],
+        dir: &Path,
+        _input: &[u8],
+    ) -> std::io::Result<Output> {
+        self.run(args, dir)
+    }
+}
+
+/// Runs the `git` executable.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProcessGitRunner;
+
+impl GitRunner for ProcessGitRunner {
+    fn run(&self, args: &[&OsStr], dir: &Path) -> std::io::Result<Output> {
+        Command::new("git").args(args).current_dir(dir).output()
+    }
+
+    fn run_with_input(&self, args: &[&OsStr], dir: &Path, input: &[u8]) -> std::io::Result<Output> {
+        let mut child = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        // dropping stdin closes it, so that git stops reading
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(input)?;
+        child.wait_with_output()
+    }
+}
+
+/// Lists the tags of the repository, one per line.
+pub fn tags<P: AsRef<Path>>(runner: &dyn GitRunner, dir: P) -> Result<String, GitError> {
+    read_output(runner, &mut tags_command(dir, None))
+}
+
+/// Lists the tags of the repository that match a glob pattern (e.g. `v*`), one per line.
+pub fn tags_matching<P: AsRef<Path>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    pattern: &str,
+) -> Result<String, GitError> {
+    read_output(runner, &mut tags_command(dir, Some(pattern)))
+}
+
+fn tags_command<P: AsRef<Path>>(dir: P, pattern: Option<&str>) -> Command {
+    let mut command = Command::new("git");
+    command.arg("tag").arg("--list");
+    if let Some(pattern) = pattern {
+        command.arg(pattern);
     }
+    command.current_dir(dir);
+    command
 }
 
-pub fn add<P: AsRef<Path>, Q: AsRef<OsStr>>(dir: P, item_to_add: Q) -> Result<(), GitError> {
+/// Finds the most recent tag that is reachable from HEAD (`git describe`).
+/// If a glob pattern is given (e.g. `v*`), only the matching tags are considered.
+/// Fails with [GitError::NonZeroExitCode] if there is no such tag.
+pub fn describe<P: AsRef<Path>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    pattern: Option<&str>,
+) -> Result<String, GitError> {
+    let output = read_output(runner, &mut describe_command(dir, pattern))?;
+    Ok(output.trim_end().to_owned())
+}
+
+fn describe_command<P: AsRef<Path>>(dir: P, pattern: Option<&str>) -> Command {
+    let mut command = Command::new("git");
+    command.arg("describe").arg("--tags").arg("--abbrev=0");
+    if let Some(pattern) = pattern {
+        command.arg("--match").arg(pattern);
+    }
+    command.current_dir(dir);
+    command
+}
+
+pub fn add<P: AsRef<Path>, Q: AsRef<OsStr>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    item_to_add: Q,
+) -> Result<(), GitError> {
     discard_output(
+        runner,
         Command::new("git")
             .arg("add")
             .arg(item_to_add)
@@ -55,8 +134,57 @@ pub fn add<P: AsRef<Path>, Q: AsRef<OsStr>>(dir: P, item_to_add: Q) -> Result<()
     )
 }
 
-pub fn commit<P: AsRef<Path>, Q: AsRef<OsStr>>(dir: P, message: Q) -> Result<(), GitError> {
+/// Lists the tracked files whose changes are not staged,
+/// relative to the root of the repository.
+pub fn unstaged_files<P: AsRef<Path>>(
+    runner: &dyn GitRunner,
+    dir: P,
+) -> Result<Vec<String>, GitError> {
+    let output = read_output(runner, &mut unstaged_files_command(dir))?;
+    Ok(output
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_owned)
+        .collect())
+}
+
+fn unstaged_files_command<P: AsRef<Path>>(dir: P) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("diff")
+        .arg("--name-only")
+        .arg("-z")
+        .current_dir(dir);
+    command
+}
+
+/// Stages the given paths, which are relative to the root of the repository
+/// (e.g. as listed by [unstaged_files]).
+pub fn add_from_root<P: AsRef<Path>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    paths: &[String],
+) -> Result<(), GitError> {
+    discard_output(runner, &mut add_from_root_command(dir, paths))
+}
+
+fn add_from_root_command<P: AsRef<Path>>(dir: P, paths: &[String]) -> Command {
+    let mut command = Command::new("git");
+    command.arg("add").arg("--");
+    for path in paths {
+        command.arg(format!(":(top,literal){}", path));
+    }
+    command.current_dir(dir);
+    command
+}
+
+pub fn commit<P: AsRef<Path>, Q: AsRef<OsStr>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    message: Q,
+) -> Result<(), GitError> {
     discard_output(
+        runner,
         Command::new("git")
             .arg("commit")
             .arg("-m")
@@ -65,32 +193,484 @@ pub fn commit<P: AsRef<Path>, Q: AsRef<OsStr>>(dir: P, message: Q) -> Result<(),
     )
 }
 
+/// The kind of tag to create.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TagKind {
+    /// An annotated tag, which carries a message.
+    Annotated,
+
+    /// A lightweight tag, which is just a named ref. The message is ignored.
+    Lightweight,
+}
+
 pub fn tag<P: AsRef<Path>, Q: AsRef<OsStr>, R: AsRef<OsStr>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    message: Q,
+    tag: R,
+    kind: TagKind,
+) -> Result<(), GitError> {
+    discard_output(runner, &mut tag_command(dir, message, tag, kind, false))
+}
+
+/// Creates the tag, replacing an existing tag with the same name.
+pub fn tag_force<P: AsRef<Path>, Q: AsRef<OsStr>, R: AsRef<OsStr>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    message: Q,
+    tag: R,
+    kind: TagKind,
+) -> Result<(), GitError> {
+    discard_output(runner, &mut tag_command(dir, message, tag, kind, true))
+}
+
+/// Creates the tag on the given commit (e.g. `HEAD~1`),
+/// replacing an existing tag with the same name.
+pub fn tag_force_at<P: AsRef<Path>, Q: AsRef<OsStr>, R: AsRef<OsStr>>(
+    runner: &dyn GitRunner,
     dir: P,
     message: Q,
     tag: R,
+    kind: TagKind,
+    commit: &str,
 ) -> Result<(), GitError> {
     discard_output(
-        Command::new("git")
-            .arg("tag")
-            .arg("-m")
-            .arg(message)
-            .arg(tag)
-            .current_dir(dir),
+        runner,
+        tag_command(dir, message, tag, kind, true).arg(commit),
     )
 }
 
-pub fn push<P: AsRef<Path>>(dir: P) -> Result<(), GitError> {
-    discard_output(
-        Command::new("git")
-            .arg("push")
-            .arg("--follow-tags")
-            .current_dir(dir),
+/// Creates an annotated (or signed) tag whose message is kept exactly as given,
+/// including blank lines, trailing whitespace and lines starting with `#`.
+///
+/// The message is passed to git on its standard input, not as an argument or a file.
+pub fn tag_with_body<P: AsRef<Path>, R: AsRef<OsStr>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    body: &str,
+    tag: R,
+    sign: bool,
+) -> Result<(), GitError> {
+    discard_output_with_input(
+        runner,
+        &mut tag_with_body_command(dir, tag, sign, None),
+        body.as_bytes(),
+    )
+}
+
+/// Like [tag_with_body], but on the given commit (e.g. `HEAD~1`),
+/// replacing an existing tag with the same name.
+pub fn tag_with_body_force_at<P: AsRef<Path>, R: AsRef<OsStr>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    body: &str,
+    tag: R,
+    sign: bool,
+    commit: &str,
+) -> Result<(), GitError> {
+    discard_output_with_input(
+        runner,
+        &mut tag_with_body_command(dir, tag, sign, Some(commit)),
+        body.as_bytes(),
+    )
+}
+
+fn tag_with_body_command<P: AsRef<Path>, R: AsRef<OsStr>>(
+    dir: P,
+    tag: R,
+    sign: bool,
+    force_at: Option<&str>,
+) -> Command {
+    let mut command = Command::new("git");
+    command.arg("tag").arg(if sign { "-s" } else { "-a" });
+    if force_at.is_some() {
+        command.arg("-f");
+    }
+    command
+        .arg("--cleanup=verbatim")
+        .arg("-F")
+        .arg("-")
+        .arg(tag)
+        .args(force_at)
+        .current_dir(dir);
+    command
+}
+
+fn tag_command<P: AsRef<Path>, Q: AsRef<OsStr>, R: AsRef<OsStr>>(
+    dir: P,
+    message: Q,
+    tag: R,
+    kind: TagKind,
+    force: bool,
+) -> Command {
+    let mut command = Command::new("git");
+    command.arg("tag");
+    if force {
+        command.arg("-f");
+    }
+    if kind == TagKind::Annotated {
+        // keep lines starting with # (e.g. markdown headings in release notes)
+        command.arg("--cleanup=whitespace").arg("-m").arg(message);
+    }
+    command.arg(tag).current_dir(dir);
+    command
+}
+
+/// Pushes the current branch together with the given tag.
+///
+/// Annotated tags are pushed by `--follow-tags`, but lightweight tags are not,
+/// so in that case the tag ref is pushed explicitly.
+pub fn push<P: AsRef<Path>, R: AsRef<str>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    tag: R,
+    kind: TagKind,
+) -> Result<(), GitError> {
+    discard_output(runner, &mut push_command(&dir))?;
+    if kind == TagKind::Lightweight {
+        push_tag(runner, &dir, tag)?;
+    }
+    Ok(())
+}
+
+/// Pushes only the given tag to `origin`, whatever its kind.
+pub fn push_tag<P: AsRef<Path>, R: AsRef<str>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    tag: R,
+) -> Result<(), GitError> {
+    discard_output(runner, &mut push_tag_command(dir, tag, false))
+}
+
+/// Pushes only the given tag to `origin`, replacing the remote tag
+/// (e.g. after the tag was moved to re-release the same version).
+pub fn push_tag_force<P: AsRef<Path>, R: AsRef<str>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    tag: R,
+) -> Result<(), GitError> {
+    discard_output(runner, &mut push_tag_command(dir, tag, true))
+}
+
+/// Pushes the current branch without any tags.
+pub fn push_branch<P: AsRef<Path>>(runner: &dyn GitRunner, dir: P) -> Result<(), GitError> {
+    discard_output(runner, &mut push_branch_command(&dir))
+}
+
+fn push_command<P: AsRef<Path>>(dir: P) -> Command {
+    let mut command = Command::new("git");
+    command.arg("push").arg("--follow-tags").current_dir(dir);
+    command
+}
+
+fn push_branch_command<P: AsRef<Path>>(dir: P) -> Command {
+    let mut command = Command::new("git");
+    command.arg("push").current_dir(dir);
+    command
+}
+
+fn push_tag_command<P: AsRef<Path>, R: AsRef<str>>(dir: P, tag: R, force: bool) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("push")
+        .arg("origin")
+        .arg(format!(
+            "{}refs/tags/{}",
+            if force { "+" } else { "" },
+            tag.as_ref()
+        ))
+        .current_dir(dir);
+    command
+}
+
+/// Pulls remote changes, rebasing local commits on top of them.
+pub fn pull_rebase<P: AsRef<Path>>(runner: &dyn GitRunner, dir: P) -> Result<(), GitError> {
+    discard_output(runner, &mut pull_rebase_command(dir))
+}
+
+fn pull_rebase_command<P: AsRef<Path>>(dir: P) -> Command {
+    let mut command = Command::new("git");
+    command.arg("pull").arg("--rebase").current_dir(dir);
+    command
+}
+
+/// Finds the root directory of the repository that contains the given directory.
+pub fn toplevel<P: AsRef<Path>>(runner: &dyn GitRunner, dir: P) -> Result<PathBuf, GitError> {
+    let output = read_output(runner, &mut toplevel_command(dir))?;
+    Ok(PathBuf::from(output.trim_end()))
+}
+
+fn toplevel_command<P: AsRef<Path>>(dir: P) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("rev-parse")
+        .arg("--show-toplevel")
+        .current_dir(dir);
+    command
+}
+
+/// Reads a configuration value (e.g. `user.name`) of the repository.
+pub fn config_get<P: AsRef<Path>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    key: &str,
+) -> Result<String, GitError> {
+    let output = read_output(runner, &mut config_get_command(dir, key))?;
+    Ok(output.trim_end().to_owned())
+}
+
+fn config_get_command<P: AsRef<Path>>(dir: P, key: &str) -> Command {
+    let mut command = Command::new("git");
+    command.arg("config").arg("--get").arg(key).current_dir(dir);
+    command
+}
+
+/// Reads the URL of the given remote (e.g. `origin`).
+/// See [crate::remote::parse_remote_url] to get the owner and the name of the repository.
+pub fn remote_url<P: AsRef<Path>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    remote: &str,
+) -> Result<String, GitError> {
+    let output = read_output(runner, &mut remote_url_command(dir, remote))?;
+    Ok(output.trim_end().to_owned())
+}
+
+fn remote_url_command<P: AsRef<Path>>(dir: P, remote: &str) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("remote")
+        .arg("get-url")
+        .arg(remote)
+        .current_dir(dir);
+    command
+}
+
+/// Counts the commits that are reachable from HEAD but not from the given tag.
+pub fn commit_count_since<P: AsRef<Path>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    tag: &str,
+) -> Result<u32, GitError> {
+    let output = read_output(runner, &mut commit_count_since_command(dir, tag))?;
+    let output = output.trim();
+    output
+        .parse()
+        .map_err(|_| GitError::UnexpectedOutput(output.to_owned()))
+}
+
+fn commit_count_since_command<P: AsRef<Path>>(dir: P, tag: &str) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("rev-list")
+        .arg(format!("{}..HEAD", tag))
+        .arg("--count")
+        .current_dir(dir);
+    command
+}
+
+/// Counts the commits that the current branch is ahead and behind of its upstream.
+/// Fails with [GitError::NonZeroExitCode] if the branch has no upstream.
+pub fn ahead_behind<P: AsRef<Path>>(
+    runner: &dyn GitRunner,
+    dir: P,
+) -> Result<(u32, u32), GitError> {
+    let output = read_output(runner, &mut ahead_behind_command(dir))?;
+    parse_ahead_behind(&output).ok_or_else(|| GitError::UnexpectedOutput(output.trim().to_owned()))
+}
+
+fn ahead_behind_command<P: AsRef<Path>>(dir: P) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("rev-list")
+        .arg("--left-right")
+        .arg("--count")
+        .arg("HEAD...@{u}")
+        .current_dir(dir);
+    command
+}
+
+/// Parses the `<ahead>\t<behind>` counts of `git rev-list --left-right --count`.
+fn parse_ahead_behind(output: &str) -> Option<(u32, u32)> {
+    let mut counts = output.split_whitespace().map(str::parse);
+    match (counts.next(), counts.next(), counts.next()) {
+        (Some(Ok(ahead)), Some(Ok(behind)), None) => Some((ahead, behind)),
+        _ => None,
+    }
+}
+
+/// Reads the name of the current branch (`HEAD` if it is detached).
+pub fn current_branch<P: AsRef<Path>>(runner: &dyn GitRunner, dir: P) -> Result<String, GitError> {
+    let output = read_output(runner, &mut current_branch_command(dir))?;
+    Ok(output.trim_end().to_owned())
+}
+
+fn current_branch_command<P: AsRef<Path>>(dir: P) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("rev-parse")
+        .arg("--abbrev-ref")
+        .arg("HEAD")
+        .current_dir(dir);
+    command
+}
+
+/// Reads the default branch of the given remote (e.g. `main` or `master`).
+///
+/// The default branch is known locally after cloning (`refs/remotes/<remote>/HEAD`);
+/// otherwise, the remote is asked with `git remote show`.
+pub fn default_branch<P: AsRef<Path>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    remote: &str,
+) -> Result<String, GitError> {
+    let dir = dir.as_ref();
+    if let Ok(output) = read_output(runner, &mut symbolic_ref_command(dir, remote)) {
+        if let Some(branch) = parse_symbolic_ref(&output, remote) {
+            return Ok(branch.to_owned());
+        }
+    }
+    let output = read_output(runner, &mut remote_show_command(dir, remote))?;
+    parse_remote_show(&output)
+        .map(str::to_owned)
+        .ok_or_else(|| GitError::UnexpectedOutput(output.trim().to_owned()))
+}
+
+fn symbolic_ref_command<P: AsRef<Path>>(dir: P, remote: &str) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("symbolic-ref")
+        .arg(format!("refs/remotes/{}/HEAD", remote))
+        .current_dir(dir);
+    command
+}
+
+fn remote_show_command<P: AsRef<Path>>(dir: P, remote: &str) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("remote")
+        .arg("show")
+        .arg(remote)
+        .current_dir(dir);
+    command
+}
+
+/// Gets the branch of `refs/remotes/<remote>/<branch>`.
+fn parse_symbolic_ref<'a>(output: &'a str, remote: &str) -> Option<&'a str> {
+    output
+        .trim()
+        .strip_prefix("refs/remotes/")?
+        .strip_prefix(remote)?
+        .strip_prefix('/')
+        .filter(|branch| !branch.is_empty())
+}
+
+/// Gets the branch of the `HEAD branch: <branch>` line.
+/// It is `(unknown)` if the remote has no default branch.
+fn parse_remote_show(output: &str) -> Option<&str> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("HEAD branch:"))
+        .map(str::trim)
+        .filter(|branch| !branch.is_empty() && *branch != "(unknown)")
+}
+
+/// Separates the fields of a commit in the output of `git log`.
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+/// Terminates each commit in the output of `git log`.
+const RECORD_SEPARATOR: char = '\u{1e}';
+
+/// A commit, as returned by [log_between].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Commit {
+    /// The abbreviated commit hash.
+    pub hash: String,
+
+    /// The first line of the commit message.
+    pub subject: String,
+
+    /// The rest of the commit message, without trailing whitespace.
+    pub body: String,
+}
+
+/// Lists the commits that are reachable from `to` but not from `from`, newest first.
+/// If `from` is `None` (e.g. there is no previous tag), the full history is returned.
+pub fn log_between<P: AsRef<Path>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    from: Option<&str>,
+    to: &str,
+) -> Result<Vec<Commit>, GitError> {
+    let output = read_output(runner, &mut log_command(dir, from, to))?;
+    Ok(parse_log(&output))
+}
+
+fn log_command<P: AsRef<Path>>(dir: P, from: Option<&str>, to: &str) -> Command {
+    let mut command = Command::new("git");
+    command.arg("log").arg("--format=%h%x1f%s%x1f%b%x1e");
+    match from {
+        Some(from) => command.arg(format!("{}..{}", from, to)),
+        None => command.arg(to),
+    };
+    command.arg("--").current_dir(dir);
+    command
+}
+
+fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .split(RECORD_SEPARATOR)
+        .map(|record| record.trim_start_matches('\n'))
+        .filter(|record| !record.is_empty())
+        .filter_map(|record| {
+            let mut fields = record.splitn(3, FIELD_SEPARATOR);
+            Some(Commit {
+                hash: fields.next()?.to_owned(),
+                subject: fields.next()?.to_owned(),
+                body: fields.next()?.trim_end().to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Runs the given git command through the runner.
+fn run(runner: &dyn GitRunner, command: &Command) -> std::io::Result<Output> {
+    let args: Vec<&OsStr> = command.get_args().collect();
+    runner.run(
+        &args,
+        command.get_current_dir().unwrap_or_else(|| Path::new(".")),
     )
 }
 
-fn discard_output(command: &mut Command) -> Result<(), GitError> {
-    match command.output() {
+fn discard_output_with_input(
+    runner: &dyn GitRunner,
+    command: &mut Command,
+    input: &[u8],
+) -> Result<(), GitError> {
+    let args: Vec<&OsStr> = command.get_args().collect();
+    let dir = command.get_current_dir().unwrap_or_else(|| Path::new("."));
+    match runner.run_with_input(&args, dir, input) {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(_) => Err(GitError::NonZeroExitCode),
+        Err(err) => Err(GitError::IOError(err)),
+    }
+}
+
+fn read_output(runner: &dyn GitRunner, command: &mut Command) -> Result<String, GitError> {
+    match run(runner, command) {
+        Ok(output) => {
+            if output.status.success() {
+                String::from_utf8(output.stdout).map_err(GitError::FromUtf8Error)
+            } else {
+                Err(GitError::NonZeroExitCode)
+            }
+        }
+        Err(err) => Err(GitError::IOError(err)),
+    }
+}
+
+fn discard_output(runner: &dyn GitRunner, command: &mut Command) -> Result<(), GitError> {
+    match run(runner, command) {
         Ok(output) => {
             if output.status.success() {
                 Ok(())
@@ -101,3 +681,442 @@ fn discard_output(command: &mut Command) -> Result<(), GitError> {
         Err(err) => Err(GitError::IOError(err)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_tag_command_annotated() {
+        let command = tag_command(
+            ".",
+            "Releasing version 1.2.3",
+            "v1.2.3",
+            TagKind::Annotated,
+            false,
+        );
+        assert_eq!(
+            args(&command),
+            vec![
+                "tag",
+                "--cleanup=whitespace",
+                "-m",
+                "Releasing version 1.2.3",
+                "v1.2.3"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tag_command_lightweight() {
+        let command = tag_command(
+            ".",
+            "Releasing version 1.2.3",
+            "v1.2.3",
+            TagKind::Lightweight,
+            false,
+        );
+        assert_eq!(args(&command), vec!["tag", "v1.2.3"]);
+    }
+
+    #[test]
+    fn test_tag_command_force() {
+        let command = tag_command(".", "msg", "v1.2.3", TagKind::Annotated, true);
+        assert_eq!(
+            args(&command),
+            vec!["tag", "-f", "--cleanup=whitespace", "-m", "msg", "v1.2.3"]
+        );
+        let command = tag_command(".", "msg", "v1.2.3", TagKind::Lightweight, true);
+        assert_eq!(args(&command), vec!["tag", "-f", "v1.2.3"]);
+    }
+
+    #[test]
+    fn test_push_commands() {
+        assert_eq!(args(&push_command(".")), vec!["push", "--follow-tags"]);
+        assert_eq!(args(&push_branch_command(".")), vec!["push"]);
+        assert_eq!(
+            args(&push_tag_command(".", "v1.2.3", false)),
+            vec!["push", "origin", "refs/tags/v1.2.3"]
+        );
+    }
+
+    #[test]
+    fn test_config_get_command() {
+        assert_eq!(
+            args(&config_get_command(".", "user.email")),
+            vec!["config", "--get", "user.email"]
+        );
+    }
+
+    #[test]
+    fn test_remote_url_command() {
+        assert_eq!(
+            args(&remote_url_command(".", "origin")),
+            vec!["remote", "get-url", "origin"]
+        );
+    }
+
+    #[test]
+    fn test_commit_count_since_command() {
+        assert_eq!(
+            args(&commit_count_since_command(".", "v1.2.3")),
+            vec!["rev-list", "v1.2.3..HEAD", "--count"]
+        );
+    }
+
+    #[test]
+    fn test_unstaged_files_command() {
+        assert_eq!(
+            args(&unstaged_files_command(".")),
+            vec!["diff", "--name-only", "-z"]
+        );
+    }
+
+    #[test]
+    fn test_add_from_root_command() {
+        assert_eq!(
+            args(&add_from_root_command(".", &["src/lib.rs".to_owned()])),
+            vec!["add", "--", ":(top,literal)src/lib.rs"]
+        );
+    }
+
+    #[test]
+    fn test_branch_commands() {
+        assert_eq!(
+            args(&current_branch_command(".")),
+            vec!["rev-parse", "--abbrev-ref", "HEAD"]
+        );
+        assert_eq!(
+            args(&symbolic_ref_command(".", "origin")),
+            vec!["symbolic-ref", "refs/remotes/origin/HEAD"]
+        );
+        assert_eq!(
+            args(&remote_show_command(".", "origin")),
+            vec!["remote", "show", "origin"]
+        );
+    }
+
+    #[test]
+    fn test_parse_symbolic_ref() {
+        assert_eq!(
+            parse_symbolic_ref("refs/remotes/origin/main\n", "origin"),
+            Some("main")
+        );
+        assert_eq!(
+            parse_symbolic_ref("refs/remotes/origin/release/2.x\n", "origin"),
+            Some("release/2.x")
+        );
+        assert_eq!(
+            parse_symbolic_ref("refs/remotes/upstream/main\n", "origin"),
+            None
+        );
+        assert_eq!(parse_symbolic_ref("refs/remotes/origin/\n", "origin"), None);
+        assert_eq!(parse_symbolic_ref("", "origin"), None);
+    }
+
+    #[test]
+    fn test_parse_remote_show() {
+        let output = "* remote origin
+  Fetch URL: git@github.com:ngeor/yart.git
+  Push  URL: git@github.com:ngeor/yart.git
+  HEAD branch: master
+  Remote branch:
+    master tracked
+";
+        assert_eq!(parse_remote_show(output), Some("master"));
+        assert_eq!(parse_remote_show("  HEAD branch: (unknown)\n"), None);
+        assert_eq!(parse_remote_show("* remote origin\n"), None);
+    }
+
+    #[test]
+    fn test_pull_rebase_command() {
+        assert_eq!(args(&pull_rebase_command(".")), vec!["pull", "--rebase"]);
+    }
+
+    #[test]
+    fn test_tags_command() {
+        assert_eq!(args(&tags_command(".", None)), vec!["tag", "--list"]);
+        assert_eq!(
+            args(&tags_command(".", Some("v*"))),
+            vec!["tag", "--list", "v*"]
+        );
+    }
+
+    #[test]
+    fn test_toplevel_command() {
+        assert_eq!(
+            args(&toplevel_command(".")),
+            vec!["rev-parse", "--show-toplevel"]
+        );
+    }
+
+    #[test]
+    fn test_log_command() {
+        assert_eq!(
+            args(&log_command(".", Some("v1.2.3"), "HEAD")),
+            vec!["log", "--format=%h%x1f%s%x1f%b%x1e", "v1.2.3..HEAD", "--"]
+        );
+        assert_eq!(
+            args(&log_command(".", None, "HEAD")),
+            vec!["log", "--format=%h%x1f%s%x1f%b%x1e", "HEAD", "--"]
+        );
+    }
+
+    #[test]
+    fn test_parse_log() {
+        // captured from git log --format=%h%x1f%s%x1f%b%x1e
+        let output = "a1b2c3d\u{1f}feat: support markers\u{1f}Markers are configured in .yart.toml.\n\nCloses #12\n\u{1e}\n\
+            e4f5a6b\u{1f}fix: typo\u{1f}\u{1e}\n";
+        assert_eq!(
+            parse_log(output),
+            vec![
+                Commit {
+                    hash: "a1b2c3d".to_owned(),
+                    subject: "feat: support markers".to_owned(),
+                    body: "Markers are configured in .yart.toml.\n\nCloses #12".to_owned(),
+                },
+                Commit {
+                    hash: "e4f5a6b".to_owned(),
+                    subject: "fix: typo".to_owned(),
+                    body: "".to_owned(),
+                },
+            ]
+        );
+        assert!(parse_log("").is_empty());
+    }
+
+    #[test]
+    fn test_toplevel_from_subdir() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        let runner = ProcessGitRunner;
+        discard_output(&runner, Command::new("git").arg("init").current_dir(&root)).unwrap();
+        let sub_dir = root.join("app");
+        std::fs::create_dir(&sub_dir).unwrap();
+
+        assert_eq!(toplevel(&runner, &sub_dir).unwrap(), root);
+        assert_eq!(toplevel(&runner, &root).unwrap(), root);
+    }
+
+    /// Answers every command with the same exit code and output.
+    struct FakeRunner {
+        exit_code: i32,
+        stdout: &'static str,
+    }
+
+    impl GitRunner for FakeRunner {
+        fn run(&self, _args: &[&OsStr], _dir: &Path) -> std::io::Result<Output> {
+            #[cfg(unix)]
+            use std::os::unix::process::ExitStatusExt;
+            #[cfg(windows)]
+            use std::os::windows::process::ExitStatusExt;
+            Ok(Output {
+                // the exit code is in the high byte of a unix wait status
+                #[cfg(unix)]
+                status: std::process::ExitStatus::from_raw(self.exit_code << 8),
+                #[cfg(windows)]
+                status: std::process::ExitStatus::from_raw(self.exit_code as u32),
+                stdout: self.stdout.as_bytes().to_vec(),
+                stderr: Vec::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_runner_output() {
+        let runner = FakeRunner {
+            exit_code: 0,
+            stdout: "v0.1.0\nv0.2.0\n",
+        };
+        assert_eq!(tags(&runner, ".").unwrap(), "v0.1.0\nv0.2.0\n");
+
+        let runner = FakeRunner {
+            exit_code: 128,
+            stdout: "",
+        };
+        assert!(matches!(
+            commit(&runner, ".", "Releasing version 1.2.3"),
+            Err(GitError::NonZeroExitCode)
+        ));
+    }
+
+    #[test]
+    fn test_tag_with_body_command() {
+        let command = tag_with_body_command(".", "v1.2.3", false, None);
+        assert_eq!(
+            args(&command),
+            vec!["tag", "-a", "--cleanup=verbatim", "-F", "-", "v1.2.3"]
+        );
+        let command = tag_with_body_command(".", "v1.2.3", true, None);
+        assert_eq!(args(&command)[1], "-s");
+        let command = tag_with_body_command(".", "v1.2.3", false, Some("HEAD~1"));
+        assert_eq!(
+            args(&command),
+            vec![
+                "tag",
+                "-a",
+                "-f",
+                "--cleanup=verbatim",
+                "-F",
+                "-",
+                "v1.2.3",
+                "HEAD~1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tag_with_body_keeps_the_body() {
+        let dir = tempfile::tempdir().unwrap();
+        let runner = ProcessGitRunner;
+        let git = |args: &[&str]| {
+            read_output(
+                &runner,
+                Command::new("git").args(args).current_dir(dir.path()),
+            )
+            .unwrap()
+        };
+        git(&["init", "--quiet"]);
+        git(&["config", "user.name", "yart"]);
+        git(&["config", "user.email", "yart@example.com"]);
+        git(&["commit", "--quiet", "--allow-empty", "-m", "Initial commit"]);
+        let body = "Releasing version 1.2.3\n\n# Added\n\n- Things  \n";
+
+        tag_with_body(&runner, dir.path(), body, "v1.2.3", false).unwrap();
+
+        assert_eq!(
+            git(&["tag", "--list", "--format=%(contents)", "v1.2.3"]),
+            format!("{}\n", body)
+        );
+    }
+
+    #[test]
+    fn test_ahead_behind_command() {
+        let command = ahead_behind_command(".");
+        assert_eq!(
+            args(&command),
+            vec!["rev-list", "--left-right", "--count", "HEAD...@{u}"]
+        );
+    }
+
+    #[test]
+    fn test_parse_ahead_behind() {
+        assert_eq!(parse_ahead_behind("2\t0\n"), Some((2, 0)));
+        assert_eq!(parse_ahead_behind("0\t13\n"), Some((0, 13)));
+        assert_eq!(parse_ahead_behind("3\n"), None);
+        assert_eq!(parse_ahead_behind("1\t2\t3\n"), None);
+        assert_eq!(parse_ahead_behind("a\tb\n"), None);
+
+        let runner = FakeRunner {
+            exit_code: 0,
+            stdout: "1\t4\n",
+        };
+        assert_eq!(ahead_behind(&runner, ".").unwrap(), (1, 4));
+    }
+
+    /// Records the commands and succeeds.
+    #[derive(Default)]
+    struct RecordingRunner {
+        commands: std::cell::RefCell<Vec<String>>,
+    }
+
+    impl GitRunner for RecordingRunner {
+        fn run(&self, args: &[&OsStr], dir: &Path) -> std::io::Result<Output> {
+            let args: Vec<String> = args
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            self.commands.borrow_mut().push(args.join(" "));
+            FakeRunner {
+                exit_code: 0,
+                stdout: "",
+            }
+            .run(&[], dir)
+        }
+    }
+
+    #[test]
+    fn test_push_per_tag_kind() {
+        // --follow-tags pushes annotated tags along with the branch
+        let runner = RecordingRunner::default();
+        push(&runner, ".", "v1.2.3", TagKind::Annotated).unwrap();
+        assert_eq!(runner.commands.into_inner(), vec!["push --follow-tags"]);
+
+        // but not lightweight tags
+        let runner = RecordingRunner::default();
+        push(&runner, ".", "v1.2.3", TagKind::Lightweight).unwrap();
+        assert_eq!(
+            runner.commands.into_inner(),
+            vec!["push --follow-tags", "push origin refs/tags/v1.2.3"]
+        );
+
+        let runner = RecordingRunner::default();
+        push_tag(&runner, ".", "v1.2.3").unwrap();
+        assert_eq!(
+            runner.commands.into_inner(),
+            vec!["push origin refs/tags/v1.2.3"]
+        );
+
+        let runner = RecordingRunner::default();
+        push_tag_force(&runner, ".", "v1.2.3").unwrap();
+        assert_eq!(
+            runner.commands.into_inner(),
+            vec!["push origin +refs/tags/v1.2.3"]
+        );
+    }
+
+    #[test]
+    fn test_describe_command() {
+        let command = describe_command(".", Some("v*"));
+        assert_eq!(
+            args(&command),
+            vec!["describe", "--tags", "--abbrev=0", "--match", "v*"]
+        );
+        let command = describe_command(".", None);
+        assert_eq!(args(&command), vec!["describe", "--tags", "--abbrev=0"]);
+    }
+
+    #[test]
+    fn test_describe() {
+        let runner = FakeRunner {
+            exit_code: 0,
+            stdout: "v1.2.3\n",
+        };
+        assert_eq!(describe(&runner, ".", Some("v*")).unwrap(), "v1.2.3");
+
+        // no tags
+        let runner = FakeRunner {
+            exit_code: 128,
+            stdout: "",
+        };
+        assert!(matches!(
+            describe(&runner, ".", Some("v*")),
+            Err(GitError::NonZeroExitCode)
+        ));
+    }
+
+    #[test]
+    fn test_commit_count_since() {
+        let runner = FakeRunner {
+            exit_code: 0,
+            stdout: "3\n",
+        };
+        assert_eq!(commit_count_since(&runner, ".", "v1.2.3").unwrap(), 3);
+
+        let runner = FakeRunner {
+            exit_code: 0,
+            stdout: "fatal\n",
+        };
+        assert!(matches!(
+            commit_count_since(&runner, ".", "v1.2.3"),
+            Err(GitError::UnexpectedOutput(output)) if output == "fatal"
+        ));
+    }
+}
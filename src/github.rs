@@ -0,0 +1,153 @@
+//! Creates GitHub releases via the REST API
+//!
+//! Only available with the `github` feature, which pulls in an HTTP client.
+
+use crate::git::{self, GitRunner};
+use crate::json_util::quote_string;
+use crate::remote::parse_remote_url;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+const TOKEN_VARIABLE: &str = "GITHUB_TOKEN";
+
+#[derive(Debug)]
+pub enum GitHubError {
+    /// The `GITHUB_TOKEN` environment variable is not set.
+    MissingToken,
+
+    /// The URL of the `origin` remote could not be read.
+    RemoteNotFound,
+
+    /// The `origin` remote does not point to GitHub.
+    UnsupportedRemote(String),
+
+    /// The API responded with an error status code.
+    Api(u16, String),
+
+    /// The API could not be reached.
+    Transport(String),
+}
+
+impl Display for GitHubError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingToken => write!(
+                f,
+                "the {} environment variable is required to create a GitHub release",
+                TOKEN_VARIABLE
+            ),
+            Self::RemoteNotFound => f.write_str("could not read the URL of the origin remote"),
+            Self::UnsupportedRemote(url) => {
+                write!(f, "the origin remote {} is not a GitHub repository", url)
+            }
+            Self::Api(status, body) => {
+                let hint = match status {
+                    401 => " (is the token valid?)",
+                    403 | 404 => " (does the token have access to the repository?)",
+                    422 => " (does the release already exist or is the tag not pushed?)",
+                    _ => "",
+                };
+                write!(
+                    f,
+                    "GitHub API returned status {}{}: {}",
+                    status,
+                    hint,
+                    body.trim()
+                )
+            }
+            Self::Transport(err) => write!(f, "could not reach the GitHub API: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for GitHubError {}
+
+/// Creates a GitHub release for the given (already pushed) tag.
+/// The repository is determined by the URL of the `origin` remote, read with the given runner.
+pub fn create_release<P: AsRef<Path>>(
+    runner: &dyn GitRunner,
+    dir: P,
+    tag: &str,
+    body: &str,
+) -> Result<(), GitHubError> {
+    let token = std::env::var(TOKEN_VARIABLE).map_err(|_| GitHubError::MissingToken)?;
+    let url = git::remote_url(runner, dir, "origin").map_err(|_| GitHubError::RemoteNotFound)?;
+    let (owner, repo) =
+        parse_github_remote(&url).ok_or_else(|| GitHubError::UnsupportedRemote(url.clone()))?;
+    let api_url = format!("https://api.github.com/repos/{}/{}/releases", owner, repo);
+    match ureq::post(&api_url)
+        .set("Accept", "application/vnd.github+json")
+        .set("Authorization", &format!("Bearer {}", token))
+        .set("User-Agent", "yart")
+        .send_string(&release_payload(tag, body))
+    {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(status, response)) => Err(GitHubError::Api(
+            status,
+            response.into_string().unwrap_or_default(),
+        )),
+        Err(err) => Err(GitHubError::Transport(err.to_string())),
+    }
+}
+
+/// Gets the owner and the name of a GitHub repository from its remote URL.
+/// Supports `git@github.com:owner/repo.git`, `ssh://git@github.com/owner/repo.git`
+/// and `https://github.com/owner/repo.git`, with or without the `.git` suffix.
+fn parse_github_remote(url: &str) -> Option<(String, String)> {
+    match parse_remote_url(url)? {
+        (host, owner, repo) if host == "github.com" && !owner.contains('/') => Some((owner, repo)),
+        _ => None,
+    }
+}
+
+/// Builds the JSON payload of the create release request.
+fn release_payload(tag: &str, body: &str) -> String {
+    format!(
+        "{{\"tag_name\":{},\"name\":{},\"body\":{}}}",
+        quote_string(tag),
+        quote_string(tag),
+        quote_string(body)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_github_remote() {
+        let expected = Some(("ngeor".to_owned(), "yart".to_owned()));
+        assert_eq!(
+            parse_github_remote("git@github.com:ngeor/yart.git"),
+            expected
+        );
+        assert_eq!(parse_github_remote("git@github.com:ngeor/yart"), expected);
+        assert_eq!(
+            parse_github_remote("ssh://git@github.com/ngeor/yart.git"),
+            expected
+        );
+        assert_eq!(
+            parse_github_remote("https://github.com/ngeor/yart.git"),
+            expected
+        );
+        assert_eq!(
+            parse_github_remote("https://github.com/ngeor/yart/"),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_github_remote_unsupported() {
+        assert!(parse_github_remote("https://gitlab.com/ngeor/yart.git").is_none());
+        assert!(parse_github_remote("https://github.com/ngeor").is_none());
+        assert!(parse_github_remote("/home/ngeor/yart").is_none());
+    }
+
+    #[test]
+    fn test_release_payload() {
+        assert_eq!(
+            release_payload("v1.2.3", "- Support \"quotes\"\n- Fix\tbugs"),
+            r#"{"tag_name":"v1.2.3","name":"v1.2.3","body":"- Support \"quotes\"\n- Fix\tbugs"}"#
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Handles Godot projects (project.godot)
+
+use crate::files::{ContentProcessor, FileFinder};
+use crate::SemVer;
+use std::path::PathBuf;
+
+/// Updates the `config/version` key of the `[application]` section of `project.godot`.
+/// Other keys (e.g. `config/name`) and sections are left as-is.
+/// Projects without a `config/version` are left alone.
+pub struct GodotUpdater {}
+
+impl FileFinder for GodotUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let path = PathBuf::from(dir).join("project.godot");
+        if path.is_file() {
+            Ok(vec![path])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+impl ContentProcessor for GodotUpdater {
+    type Err = std::io::Error; // does not really throw error
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        let mut result = String::with_capacity(old_contents.len());
+        let mut in_application = false;
+        for line in old_contents.split_inclusive('\n') {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                in_application = trimmed == "[application]";
+            } else if in_application {
+                if let Some(new_line) = replace_version(line, version) {
+                    result.push_str(&new_line);
+                    continue;
+                }
+            }
+            result.push_str(line);
+        }
+        Ok(result)
+    }
+}
+
+/// Replaces the quoted value of a `config/version="..."` line.
+fn replace_version(line: &str, version: SemVer) -> Option<String> {
+    let (key, value) = line.split_once('=')?;
+    if key.trim() != "config/version" {
+        return None;
+    }
+    let start = key.len() + 1 + value.find('"')? + 1;
+    let len = line[start..].find('"')?;
+    Some(format!(
+        "{}{}{}",
+        &line[..start],
+        version,
+        &line[start + len..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_godot() {
+        let input = r#"; Engine configuration file.
+; It's best edited using the editor UI and not directly,
+; since the parameters that go here are not all obvious.
+;
+; Format:
+;   [section] ; section goes between []
+;   param=value ; assign values to parameters
+
+config_version=5
+
+[application]
+
+config/name="Dodge the Creeps"
+config/description="A simple game"
+config/version="1.2.3"
+run/main_scene="res://main.tscn"
+config/features=PackedStringArray("4.2", "Forward Plus")
+config/icon="res://icon.svg"
+
+[display]
+
+window/size/viewport_width=480
+config/version="0.0.1"
+"#;
+        let expected = input.replacen("config/version=\"1.2.3\"", "config/version=\"1.3.0\"", 1);
+        let result = GodotUpdater {}
+            .process(input, SemVer::new(1, 3, 0))
+            .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_without_version() {
+        let input = "config_version=5\r\n\r\n[application]\r\n\r\nconfig/name=\"App\"\r\n";
+        let result = GodotUpdater {}
+            .process(input, SemVer::new(1, 3, 0))
+            .unwrap();
+        assert_eq!(result, input);
+    }
+}
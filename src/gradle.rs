@@ -0,0 +1,378 @@
+//! Handles Gradle projects, including Android apps
+//!
+//! Both the Groovy (`build.gradle`) and the Kotlin DSL (`build.gradle.kts`) are supported.
+use crate::files::{ContentProcessor, FileFinder};
+use crate::sem_ver::ExtractedVersion;
+use crate::SemVer;
+use std::fs;
+use std::path::PathBuf;
+
+const BUILD_FILE_NAMES: [&str; 2] = ["build.gradle", "build.gradle.kts"];
+
+const SETTINGS_FILE_NAMES: [&str; 2] = ["settings.gradle", "settings.gradle.kts"];
+
+/// Updates the version of Gradle build files.
+///
+/// The top-level `version` of the project and the Android `versionName`
+/// are set to the new version. The Android `versionCode` is incremented
+/// only if `bump_version_code` is set.
+pub struct GradleUpdater {
+    pub bump_version_code: bool,
+}
+
+/// Finds the build files of the root directory, of the `app` module of Android projects
+/// and of the subprojects that are included in the settings file of multi-project builds.
+impl FileFinder for GradleUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let dir_path_buf = PathBuf::from(dir);
+        let mut module_dirs = vec![dir_path_buf.clone(), dir_path_buf.join("app")];
+        for file_name in SETTINGS_FILE_NAMES {
+            let path = dir_path_buf.join(file_name);
+            if path.is_file() {
+                for project_dir in parse_settings(&fs::read_to_string(path)?) {
+                    let module_dir = dir_path_buf.join(project_dir);
+                    if !module_dirs.contains(&module_dir) {
+                        module_dirs.push(module_dir);
+                    }
+                }
+            }
+        }
+        let mut result = Vec::<PathBuf>::new();
+        for module_dir in module_dirs {
+            for file_name in BUILD_FILE_NAMES {
+                let path = module_dir.join(file_name);
+                if path.is_file() {
+                    result.push(path);
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Finds the directories of the subprojects of a settings file, relative to the root directory.
+///
+/// A project path maps to nested directories (e.g. `:libs:core` to `libs/core`),
+/// unless its directory is set with `project(":core").projectDir = file("...")`.
+/// The `rootProject.name` and included builds are not subprojects.
+fn parse_settings(contents: &str) -> Vec<PathBuf> {
+    let mut project_paths = Vec::<String>::new();
+    let mut project_dirs = Vec::<(String, String)>::new();
+    let mut lines = contents.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if let Some(rest) = line.strip_prefix("include") {
+            if rest.starts_with(|ch: char| ch == '(' || ch.is_whitespace()) {
+                let mut statement = rest.to_owned();
+                // include(...) may span multiple lines
+                if rest.trim_start().starts_with('(') {
+                    while !statement.contains(')') {
+                        match lines.next() {
+                            Some(next_line) => statement.push_str(next_line),
+                            None => break,
+                        }
+                    }
+                }
+                project_paths.append(&mut quoted_strings(&statement));
+            }
+        } else if line.starts_with("project(") && line.contains(".projectDir") {
+            let strings = quoted_strings(line);
+            if line.contains("file(") && strings.len() == 2 {
+                project_dirs.push((strings[0].clone(), strings[1].clone()));
+            }
+        }
+    }
+    project_paths
+        .iter()
+        .map(|project_path| {
+            let project_path = normalize_project_path(project_path);
+            match project_dirs
+                .iter()
+                .find(|(other, _)| normalize_project_path(other) == project_path)
+            {
+                Some((_, project_dir)) => PathBuf::from(project_dir),
+                None => project_path.split(':').collect(),
+            }
+        })
+        .collect()
+}
+
+fn normalize_project_path(project_path: &str) -> &str {
+    project_path.trim_start_matches(':')
+}
+
+/// Finds the contents of the single or double quoted strings of the given text.
+fn quoted_strings(text: &str) -> Vec<String> {
+    let mut result = Vec::<String>::new();
+    let mut current: Option<(char, String)> = None;
+    for ch in text.chars() {
+        match current.as_mut() {
+            Some((quote, value)) if ch == *quote => {
+                result.push(std::mem::take(value));
+                current = None;
+            }
+            Some((_, value)) => value.push(ch),
+            None if ch == '"' || ch == '\'' => current = Some((ch, String::new())),
+            None => {}
+        }
+    }
+    result
+}
+
+impl ContentProcessor for GradleUpdater {
+    type Err = std::io::Error; // does not really throw error
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        let version_as_str = version.to_string();
+        let mut result = String::new();
+        for line in old_contents.split_inclusive('\n') {
+            let new_line = replace_string_value(line, "versionName", &version_as_str)
+                .or_else(|| {
+                    // only the version of the project, not of nested blocks
+                    if line.starts_with("version") {
+                        replace_string_value(line, "version", &version_as_str)
+                    } else {
+                        None
+                    }
+                })
+                .or_else(|| {
+                    if self.bump_version_code {
+                        increment_int_value(line, "versionCode")
+                    } else {
+                        None
+                    }
+                });
+            match new_line {
+                Some(new_line) => result.push_str(&new_line),
+                None => result.push_str(line),
+            }
+        }
+        Ok(result)
+    }
+
+    /// The `versionCode` is not part of the version.
+    fn is_at_version(&self, contents: &str, version: SemVer) -> bool {
+        let updater = GradleUpdater {
+            bump_version_code: false,
+        };
+        updater
+            .process(contents, version)
+            .is_ok_and(|new_contents| new_contents == contents)
+    }
+
+    /// Reads the first `versionName` or project `version`.
+    /// A `-SNAPSHOT` version is read with its snapshot marker.
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        contents.lines().find_map(|line| {
+            let (start, end) = find_string_value(line, "versionName").or_else(|| {
+                if line.starts_with("version") {
+                    find_string_value(line, "version")
+                } else {
+                    None
+                }
+            })?;
+            ExtractedVersion::parse_snapshot(&line[start..end]).ok()
+        })
+    }
+}
+
+/// Finds the position of the value of the given key.
+/// Supports both `key value` (Groovy) and `key = value` (Groovy and Kotlin).
+fn find_value_start(line: &str, key: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    let after_key = trimmed.strip_prefix(key)?;
+    let value = after_key.trim_start();
+    if value.len() == after_key.len() && !value.starts_with('=') {
+        // not a separate token, e.g. versionNameSuffix
+        return None;
+    }
+    let value = match value.strip_prefix('=') {
+        Some(after_equals) => after_equals.trim_start(),
+        None => value,
+    };
+    Some(line.len() - value.len())
+}
+
+/// Replaces the quoted string value of the given key, keeping the quote style.
+fn replace_string_value(line: &str, key: &str, new_value: &str) -> Option<String> {
+    let (start, end) = find_string_value(line, key)?;
+    Some(format!("{}{}{}", &line[..start], new_value, &line[end..]))
+}
+
+/// Finds the byte span of the quoted value of the given key, without the quotes.
+fn find_string_value(line: &str, key: &str) -> Option<(usize, usize)> {
+    let start = find_value_start(line, key)?;
+    let quote = line[start..]
+        .chars()
+        .next()
+        .filter(|ch| *ch == '"' || *ch == '\'')?;
+    let value_len = line[start + 1..].find(quote)?;
+    Some((start + 1, start + 1 + value_len))
+}
+
+/// Increments the integer value of the given key.
+fn increment_int_value(line: &str, key: &str) -> Option<String> {
+    let start = find_value_start(line, key)?;
+    let digits_len = line[start..]
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(line.len() - start);
+    let value: u64 = line[start..start + digits_len].parse().ok()?;
+    Some(format!(
+        "{}{}{}",
+        &line[..start],
+        value + 1,
+        &line[start + digits_len..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_groovy_dsl() {
+        let input = r#"android {
+    defaultConfig {
+        applicationId "com.example.app"
+        versionCode 41
+        versionName "1.4.0"
+        versionNameSuffix "-beta"
+    }
+}
+"#;
+        let expected = r#"android {
+    defaultConfig {
+        applicationId "com.example.app"
+        versionCode 42
+        versionName "1.5.0"
+        versionNameSuffix "-beta"
+    }
+}
+"#;
+        let updater = GradleUpdater {
+            bump_version_code: true,
+        };
+        let result = updater.process(input, SemVer::new(1, 5, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_extract_version() {
+        let updater = GradleUpdater {
+            bump_version_code: false,
+        };
+        assert_eq!(
+            updater.extract_version("group = 'com.example'\nversion = '0.3.0-SNAPSHOT'\n"),
+            Some(ExtractedVersion {
+                version: SemVer::new(0, 3, 0),
+                snapshot: true,
+            })
+        );
+        assert_eq!(
+            updater
+                .extract_version("android {\n    versionCode 41\n    versionName \"1.4.0\"\n}\n"),
+            Some(SemVer::new(1, 4, 0).into())
+        );
+        assert_eq!(
+            updater.extract_version("version = findProperty(\"release\")\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_kotlin_dsl() {
+        let input = r#"android {
+    defaultConfig {
+        applicationId = "com.example.app"
+        versionCode = 9
+        versionName = "1.4.0"
+    }
+}
+"#;
+        let expected = r#"android {
+    defaultConfig {
+        applicationId = "com.example.app"
+        versionCode = 10
+        versionName = "2.0.0"
+    }
+}
+"#;
+        let updater = GradleUpdater {
+            bump_version_code: true,
+        };
+        let result = updater.process(input, SemVer::new(2, 0, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_version_code_is_kept_by_default() {
+        let input = "versionCode 41\nversionName '1.4.0'\n";
+        let expected = "versionCode 41\nversionName '1.5.0'\n";
+        let updater = GradleUpdater {
+            bump_version_code: false,
+        };
+        let result = updater.process(input, SemVer::new(1, 5, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        let input = r#"rootProject.name = 'demo'
+include ':app', ':libs:core'
+include(
+    ":cli",
+)
+includeBuild('build-logic')
+project(':cli').projectDir = file('tools/cli')
+"#;
+        assert_eq!(
+            parse_settings(input),
+            vec![
+                PathBuf::from("app"),
+                PathBuf::from("libs").join("core"),
+                PathBuf::from("tools/cli"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_included_subprojects() {
+        let dir = tempfile::tempdir().unwrap();
+        let lib_dir = dir.path().join("libs").join("core");
+        let server_dir = dir.path().join("server");
+        fs::create_dir_all(&lib_dir).unwrap();
+        fs::create_dir_all(&server_dir).unwrap();
+        fs::write(
+            dir.path().join("settings.gradle.kts"),
+            "rootProject.name = \"demo\"\ninclude(\":server\", \":libs:core\")\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("build.gradle.kts"), "version = \"0.1.0\"\n").unwrap();
+        fs::write(lib_dir.join("build.gradle"), "version = '0.1.0'\n").unwrap();
+        fs::write(server_dir.join("build.gradle.kts"), "version = \"0.1.0\"\n").unwrap();
+
+        let updater = GradleUpdater {
+            bump_version_code: false,
+        };
+        let result = updater.find(dir.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                dir.path().join("build.gradle.kts"),
+                server_dir.join("build.gradle.kts"),
+                lib_dir.join("build.gradle"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_project_version() {
+        let input = "group = 'com.example'\nversion = '0.1.0'\n\ndependencies {\n    implementation 'com.google.guava:guava:31.1-jre'\n}\n";
+        let expected = "group = 'com.example'\nversion = '0.2.0'\n\ndependencies {\n    implementation 'com.google.guava:guava:31.1-jre'\n}\n";
+        let updater = GradleUpdater {
+            bump_version_code: false,
+        };
+        let result = updater.process(input, SemVer::new(0, 2, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,157 @@
+//! Handles Helm charts (values.yaml)
+
+use crate::files::{ContentProcessor, FileFinder};
+use crate::yaml_util::{set_scalar_at_path, YamlError};
+use crate::SemVer;
+use std::path::PathBuf;
+
+/// Updates the `image.tag` of the `values.yaml` of a Helm chart,
+/// i.e. the tag of the application image that the chart deploys.
+///
+/// The `values.yaml` is only considered if a `Chart.yaml` sits next to it.
+/// Other `tag` keys (e.g. of sidecar images) are left alone,
+/// and so are charts without an `image.tag`.
+pub struct HelmValuesUpdater {
+    /// The values files to update instead (e.g. `values-prod.yaml`), relative to the folder.
+    /// They must exist, but they do not need a `Chart.yaml` next to them.
+    pub values_files: Vec<PathBuf>,
+}
+
+impl FileFinder for HelmValuesUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let dir = PathBuf::from(dir);
+        if !self.values_files.is_empty() {
+            return self
+                .values_files
+                .iter()
+                .map(|file| {
+                    let path = dir.join(file);
+                    if path.is_file() {
+                        Ok(path)
+                    } else {
+                        Err(std::io::Error::new(
+                            std::io::ErrorKind::NotFound,
+                            format!("values file {} not found", path.to_string_lossy()),
+                        ))
+                    }
+                })
+                .collect();
+        }
+        let path = dir.join("values.yaml");
+        if path.is_file() && dir.join("Chart.yaml").is_file() {
+            Ok(vec![path])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+impl ContentProcessor for HelmValuesUpdater {
+    type Err = YamlError;
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        match set_scalar_at_path(old_contents, "image.tag", &version.to_string()) {
+            Ok(new_contents) => Ok(new_contents),
+            Err(YamlError::PathNotFound(_)) => Ok(old_contents.to_owned()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::DirUpdater;
+
+    #[test]
+    fn test_values_yaml() {
+        let input = r#"# Default values for app.
+replicaCount: 1
+
+image:
+  repository: ghcr.io/ngeor/app
+  pullPolicy: IfNotPresent
+  # Overrides the image tag whose default is the chart appVersion.
+  tag: "1.2.3"
+
+sidecar:
+  image:
+    repository: envoyproxy/envoy
+    tag: v1.28.0
+
+metrics:
+  serviceMonitor:
+    tag: "1.2.3"
+
+ingress:
+  enabled: false
+  tls: []
+"#;
+        let expected = input.replacen("tag: \"1.2.3\"", "tag: \"1.3.0\"", 1);
+        let result = HelmValuesUpdater {
+            values_files: vec![],
+        }
+        .process(input, SemVer::new(1, 3, 0))
+        .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_values_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("values.yaml"), "image:\n  tag: 0.1.0\n").unwrap();
+        std::fs::write(
+            dir.path().join("values-prod.yaml"),
+            "replicaCount: 3\nimage:\n  tag: \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("env")).unwrap();
+        std::fs::write(
+            dir.path().join("env").join("staging.yaml"),
+            "image:\n  tag: 0.1.0\n",
+        )
+        .unwrap();
+        let updater = HelmValuesUpdater {
+            values_files: vec![
+                PathBuf::from("values-prod.yaml"),
+                PathBuf::from("env/staging.yaml"),
+            ],
+        };
+
+        // without a Chart.yaml
+        let result = updater
+            .update(dir.path().to_str().unwrap(), SemVer::new(0, 2, 0))
+            .unwrap();
+        assert_eq!(
+            result,
+            vec![
+                (
+                    dir.path().join("values-prod.yaml"),
+                    "replicaCount: 3\nimage:\n  tag: \"0.2.0\"\n".into()
+                ),
+                (
+                    dir.path().join("env/staging.yaml"),
+                    "image:\n  tag: 0.2.0\n".into()
+                ),
+            ]
+        );
+
+        let updater = HelmValuesUpdater {
+            values_files: vec![PathBuf::from("values-dev.yaml")],
+        };
+        assert!(updater
+            .update(dir.path().to_str().unwrap(), SemVer::new(0, 2, 0))
+            .is_err());
+    }
+
+    #[test]
+    fn test_without_image_tag() {
+        let input = "image:\n  repository: nginx\n";
+        let result = HelmValuesUpdater {
+            values_files: vec![],
+        }
+        .process(input, SemVer::new(1, 3, 0))
+        .unwrap();
+        assert_eq!(result, input);
+    }
+}
@@ -0,0 +1,138 @@
+//! Handles Homebrew formulae (Formula/*.rb)
+
+use crate::files::{ContentProcessor, FileFinder, RootFileFinderByExt};
+use crate::SemVer;
+use std::path::PathBuf;
+
+/// Updates the `version "..."` line of Homebrew formulae.
+///
+/// Formulae are found among the `.rb` files of the root directory and of its `Formula` folder;
+/// other Ruby files are left unchanged. Only the version of the formula itself is updated,
+/// not the versions of the `resource` blocks. The `url` and `sha256` are left alone,
+/// because they depend on the released artifact.
+pub struct HomebrewUpdater {}
+
+impl FileFinder for HomebrewUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let finder = RootFileFinderByExt::new("rb");
+        let mut result = finder.find(dir)?;
+        let formula_dir = PathBuf::from(dir).join("Formula");
+        if formula_dir.is_dir() {
+            result.append(&mut finder.find(&formula_dir.to_string_lossy())?);
+        }
+        Ok(result)
+    }
+}
+
+impl ContentProcessor for HomebrewUpdater {
+    type Err = std::io::Error; // does not really throw error
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        let mut result = String::with_capacity(old_contents.len());
+        // 0 outside the formula class, 1 in its body, more in nested blocks
+        let mut depth = 0;
+        for line in old_contents.split_inclusive('\n') {
+            let code = line.trim();
+            match depth {
+                0 if is_formula_class(code) => depth = 1,
+                0 => {}
+                1 if code.starts_with("version ") => {
+                    if let Some(new_line) = replace_version(line, version) {
+                        result.push_str(&new_line);
+                        continue;
+                    }
+                }
+                _ if code == "end" => depth -= 1,
+                _ if opens_block(code) => depth += 1,
+                _ => {}
+            }
+            result.push_str(line);
+        }
+        Ok(result)
+    }
+}
+
+/// Checks if the line declares a formula, e.g. `class Yart < Formula`.
+fn is_formula_class(code: &str) -> bool {
+    code.starts_with("class ")
+        && code
+            .split_once('<')
+            .is_some_and(|(_, parent)| parent.trim() == "Formula")
+}
+
+/// Checks if the line starts a block (e.g. `resource "x" do`) or a method definition,
+/// which are closed by an `end` line.
+fn opens_block(code: &str) -> bool {
+    let code = match code.find(" #") {
+        Some(idx) => code[..idx].trim_end(),
+        None => code,
+    };
+    code.starts_with("def ")
+        || code.ends_with(" do")
+        || (code.contains(" do |") && code.ends_with('|'))
+        || code.starts_with("if ")
+        || code.starts_with("unless ")
+        || code.starts_with("case ")
+}
+
+/// Replaces the quoted string of a `version "..."` line.
+fn replace_version(line: &str, version: SemVer) -> Option<String> {
+    let start = line.find('"')? + 1;
+    let len = line[start..].find('"')?;
+    Some(format!(
+        "{}{}{}",
+        &line[..start],
+        version,
+        &line[start + len..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formula() {
+        let input = r##"# typed: false
+# frozen_string_literal: true
+
+class Yart < Formula
+  desc "Yet another release tool"
+  homepage "https://github.com/ngeor/yart"
+  url "https://github.com/ngeor/yart/archive/refs/tags/v0.1.0.tar.gz"
+  sha256 "6a4e0c2d1f3b5a7c9e8d0f2b4a6c8e0d2f4b6a8c0e2d4f6b8a0c2e4d6f8b0a2c"
+  version "0.1.0"
+  license "MIT"
+
+  depends_on "rust" => :build
+
+  resource "completions" do
+    url "https://example.com/completions-1.0.0.tar.gz"
+    version "1.0.0"
+  end
+
+  def install
+    system "cargo", "install", *std_cargo_args
+  end
+
+  test do
+    assert_match "yart 0.1.0", shell_output("#{bin}/yart --version")
+  end
+end
+"##;
+        let expected = input.replace("version \"0.1.0\"", "version \"0.2.0\"");
+        let result = HomebrewUpdater {}
+            .process(input, SemVer::new(0, 2, 0))
+            .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_other_ruby_files_are_unchanged() {
+        let input = "module Helper\n  version \"0.1.0\"\nend\n";
+        let result = HomebrewUpdater {}
+            .process(input, SemVer::new(0, 2, 0))
+            .unwrap();
+        assert_eq!(result, input);
+    }
+}
@@ -0,0 +1,145 @@
+//! Runs user commands (hooks) at defined points of a release
+
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+
+#[derive(Debug)]
+pub enum HookError {
+    /// The hook could not be started. Holds the command of the hook.
+    IOError(String, std::io::Error),
+
+    /// The hook exited with a non-zero exit code (`None` if it was killed by a signal).
+    Failed(String, Option<i32>),
+}
+
+impl Display for HookError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IOError(command, err) => write!(f, "could not run hook {}: {}", command, err),
+            Self::Failed(command, Some(code)) => {
+                write!(f, "hook {} exited with code {}", command, code)
+            }
+            Self::Failed(command, None) => write!(f, "hook {} was terminated", command),
+        }
+    }
+}
+
+impl std::error::Error for HookError {}
+
+/// Runs the command of a hook in the given directory.
+///
+/// Hooks go through a runner, so that tests can record them instead of running them.
+pub trait HookRunner {
+    fn run(&self, command: &str, dir: &Path) -> std::io::Result<ExitStatus>;
+}
+
+/// Runs hooks with the shell (`sh -c` or `cmd /C` on Windows),
+/// so that they can use pipes, `&&`, etc.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ShellHookRunner;
+
+impl HookRunner for ShellHookRunner {
+    #[cfg(not(windows))]
+    fn run(&self, command: &str, dir: &Path) -> std::io::Result<ExitStatus> {
+        Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .current_dir(dir)
+            .status()
+    }
+
+    #[cfg(windows)]
+    fn run(&self, command: &str, dir: &Path) -> std::io::Result<ExitStatus> {
+        Command::new("cmd")
+            .arg("/C")
+            .arg(command)
+            .current_dir(dir)
+            .status()
+    }
+}
+
+/// Runs a hook, failing if it exits with a non-zero exit code.
+/// The output of the hook is not captured.
+pub fn run_hook<P: AsRef<Path>>(
+    runner: &dyn HookRunner,
+    command: &str,
+    dir: P,
+) -> Result<(), HookError> {
+    let status = runner
+        .run(command, dir.as_ref())
+        .map_err(|err| HookError::IOError(command.to_owned(), err))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(HookError::Failed(command.to_owned(), status.code()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    #[cfg(unix)]
+    use std::os::unix::process::ExitStatusExt;
+    #[cfg(windows)]
+    use std::os::windows::process::ExitStatusExt;
+
+    /// Records the hooks and exits with the given code.
+    struct RecordingRunner {
+        exit_code: i32,
+        commands: RefCell<Vec<String>>,
+    }
+
+    impl RecordingRunner {
+        fn new(exit_code: i32) -> Self {
+            Self {
+                exit_code,
+                commands: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl HookRunner for RecordingRunner {
+        fn run(&self, command: &str, _dir: &Path) -> std::io::Result<ExitStatus> {
+            self.commands.borrow_mut().push(command.to_owned());
+            // the exit code is in the high byte of a unix wait status
+            #[cfg(unix)]
+            return Ok(ExitStatus::from_raw(self.exit_code << 8));
+            #[cfg(windows)]
+            return Ok(ExitStatus::from_raw(self.exit_code as u32));
+        }
+    }
+
+    #[test]
+    fn test_run_hook() {
+        let runner = RecordingRunner::new(0);
+        run_hook(&runner, "cargo fmt", ".").unwrap();
+        assert_eq!(runner.commands.into_inner(), vec!["cargo fmt"]);
+    }
+
+    #[test]
+    fn test_run_hook_failure() {
+        let runner = RecordingRunner::new(2);
+        let err = run_hook(&runner, "cargo fmt --check", ".").unwrap_err();
+        assert!(
+            matches!(&err, HookError::Failed(command, Some(2)) if command == "cargo fmt --check")
+        );
+        assert_eq!(err.to_string(), "hook cargo fmt --check exited with code 2");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_hook_runner() {
+        let dir = tempfile::tempdir().unwrap();
+        run_hook(&ShellHookRunner, "echo formatted > out.txt", dir.path()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("out.txt")).unwrap(),
+            "formatted\n"
+        );
+        assert!(matches!(
+            run_hook(&ShellHookRunner, "exit 3", dir.path()),
+            Err(HookError::Failed(_, Some(3)))
+        ));
+    }
+}
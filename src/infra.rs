@@ -0,0 +1,82 @@
+//! Handles infrastructure modules (Puppet metadata.json)
+
+use crate::files::{ContentProcessor, FileFinder};
+use crate::json_util::{self, JsonError};
+use crate::SemVer;
+use std::path::PathBuf;
+
+/// Updates the top-level `version` of the `metadata.json` of a Puppet module.
+/// The rest of the file (e.g. key order, indentation, the versions of dependencies)
+/// is left as-is. Files without a top-level `version` are left alone.
+pub struct PuppetMetadataUpdater {}
+
+impl FileFinder for PuppetMetadataUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let path = PathBuf::from(dir).join("metadata.json");
+        if path.is_file() {
+            Ok(vec![path])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+impl ContentProcessor for PuppetMetadataUpdater {
+    type Err = JsonError;
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        match json_util::set_string_at_path(old_contents, "version", &version.to_string()) {
+            Ok(new_contents) => Ok(new_contents),
+            Err(JsonError::PathNotFound(_)) => Ok(old_contents.to_owned()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: &str = r#"{
+  "name": "puppetlabs-ntp",
+  "version": "9.2.0",
+  "author": "puppetlabs",
+  "summary": "Installs, configures, and manages the NTP service.",
+  "license": "Apache-2.0",
+  "source": "https://github.com/puppetlabs/puppetlabs-ntp",
+  "dependencies": [
+    {
+      "name": "puppetlabs/stdlib",
+      "version_requirement": ">= 9.0.0 < 10.0.0"
+    }
+  ],
+  "operatingsystem_support": [
+    {
+      "operatingsystem": "RedHat",
+      "operatingsystemrelease": ["7", "8", "9"]
+    }
+  ],
+  "pdk-version": "3.0.0"
+}
+"#;
+
+    #[test]
+    fn test_puppet_metadata() {
+        let result = PuppetMetadataUpdater {}
+            .process(METADATA, SemVer::new(9, 3, 0))
+            .unwrap();
+        assert_eq!(
+            result,
+            METADATA.replace(r#""version": "9.2.0""#, r#""version": "9.3.0""#)
+        );
+    }
+
+    #[test]
+    fn test_metadata_without_version() {
+        let contents = "{\n  \"name\": \"not-a-module\"\n}\n";
+        let result = PuppetMetadataUpdater {}
+            .process(contents, SemVer::new(1, 0, 0))
+            .unwrap();
+        assert_eq!(result, contents);
+    }
+}
@@ -0,0 +1,347 @@
+//! Minimal JSON scanning that preserves the original formatting
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+pub enum JsonError {
+    /// The contents are not valid JSON. Holds the byte offset of the problem.
+    Syntax(usize),
+
+    /// The requested path does not exist. Holds the dotted path.
+    PathNotFound(String),
+
+    /// The value at the requested path is an object or an array.
+    NotAScalar(String),
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax(offset) => write!(f, "invalid JSON at offset {}", offset),
+            Self::PathNotFound(path) => write!(f, "JSON path {} not found", path),
+            Self::NotAScalar(path) => write!(f, "JSON path {} does not point to a scalar", path),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+/// Replaces the scalar value found at the given dotted path (e.g. `build.version`)
+/// with the given string value. Everything else in the document stays as-is.
+pub fn set_string_at_path(contents: &str, path: &str, value: &str) -> Result<String, JsonError> {
+    let keys: Vec<&str> = path.split('.').collect();
+    let mut scanner = JsonScanner::new(contents);
+    match scanner.find(&keys)? {
+        Some((start, end)) => {
+            let old_value = &contents[start..end];
+            if old_value.starts_with('{') || old_value.starts_with('[') {
+                Err(JsonError::NotAScalar(path.to_owned()))
+            } else {
+                let mut result = String::with_capacity(contents.len() + value.len());
+                result.push_str(&contents[..start]);
+                result.push_str(&quote_string(value));
+                result.push_str(&contents[end..]);
+                Ok(result)
+            }
+        }
+        None => Err(JsonError::PathNotFound(path.to_owned())),
+    }
+}
+
+/// Formats the given value as a JSON string, escaping it as needed.
+pub(crate) fn quote_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            ch if ch < ' ' => result.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => result.push(ch),
+        }
+    }
+    result.push('"');
+    result
+}
+
+struct JsonScanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> JsonScanner<'a> {
+    fn new(contents: &'a str) -> Self {
+        Self {
+            bytes: contents.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    /// Finds the byte span of the value at the given path,
+    /// starting at the value at the current position.
+    fn find(&mut self, path: &[&str]) -> Result<Option<(usize, usize)>, JsonError> {
+        self.skip_whitespace();
+        match path.split_first() {
+            None => {
+                let start = self.pos;
+                self.skip_value()?;
+                Ok(Some((start, self.pos)))
+            }
+            Some((first, rest)) => {
+                if self.peek() != Some(b'{') {
+                    return Ok(None);
+                }
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.peek() == Some(b'}') {
+                    return Ok(None);
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.parse_string()?;
+                    self.skip_whitespace();
+                    self.expect(b':')?;
+                    if key == *first {
+                        return self.find(rest);
+                    }
+                    self.skip_value()?;
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(b',') => {}
+                        Some(b'}') => return Ok(None),
+                        _ => return Err(self.syntax_error()),
+                    }
+                }
+            }
+        }
+    }
+
+    fn skip_value(&mut self) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.skip_container(b'}', true),
+            Some(b'[') => self.skip_container(b']', false),
+            Some(b'"') => self.parse_string().map(|_| ()),
+            Some(_) => {
+                let start = self.pos;
+                while let Some(b) = self.peek() {
+                    if b == b',' || b == b'}' || b == b']' || b.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    Err(self.syntax_error())
+                } else {
+                    Ok(())
+                }
+            }
+            None => Err(self.syntax_error()),
+        }
+    }
+
+    fn skip_container(&mut self, close: u8, has_keys: bool) -> Result<(), JsonError> {
+        self.pos += 1;
+        self.skip_whitespace();
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            return Ok(());
+        }
+        loop {
+            if has_keys {
+                self.skip_whitespace();
+                self.parse_string()?;
+                self.skip_whitespace();
+                self.expect(b':')?;
+            }
+            self.skip_value()?;
+            self.skip_whitespace();
+            match self.next() {
+                Some(b',') => {}
+                Some(b) if b == close => return Ok(()),
+                _ => return Err(self.syntax_error()),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"')?;
+        let mut result = Vec::<u8>::new();
+        loop {
+            match self.next() {
+                Some(b'"') => break,
+                Some(b'\\') => match self.next() {
+                    Some(b'n') => result.push(b'\n'),
+                    Some(b't') => result.push(b'\t'),
+                    Some(b'r') => result.push(b'\r'),
+                    Some(b'b') => result.push(8),
+                    Some(b'f') => result.push(12),
+                    Some(b'u') => {
+                        let ch = self.parse_unicode_escape()?;
+                        let mut buf = [0; 4];
+                        result.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+                    }
+                    Some(b) => result.push(b),
+                    None => return Err(self.syntax_error()),
+                },
+                Some(b) => result.push(b),
+                None => return Err(self.syntax_error()),
+            }
+        }
+        String::from_utf8(result).map_err(|_| self.syntax_error())
+    }
+
+    /// Decodes the escape after `\u`, which is a surrogate pair for characters
+    /// outside the basic multilingual plane (e.g. `\ud83d\ude00`).
+    fn parse_unicode_escape(&mut self) -> Result<char, JsonError> {
+        let first = self.parse_hex4()?;
+        let code = if (0xD800..0xDC00).contains(&first) {
+            self.expect(b'\\')?;
+            self.expect(b'u')?;
+            let second = self.parse_hex4()?;
+            if !(0xDC00..0xE000).contains(&second) {
+                return Err(self.syntax_error());
+            }
+            0x10000 + ((first - 0xD800) << 10) + (second - 0xDC00)
+        } else {
+            first
+        };
+        char::from_u32(code).ok_or_else(|| self.syntax_error())
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, JsonError> {
+        let digits = self
+            .bytes
+            .get(self.pos..self.pos + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.syntax_error())?;
+        let result = u32::from_str_radix(digits, 16).map_err(|_| self.syntax_error())?;
+        self.pos += 4;
+        Ok(result)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b) = self.peek() {
+            if b.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), JsonError> {
+        if self.next() == Some(expected) {
+            Ok(())
+        } else {
+            Err(self.syntax_error())
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let result = self.peek();
+        if result.is_some() {
+            self.pos += 1;
+        }
+        result
+    }
+
+    fn syntax_error(&self) -> JsonError {
+        JsonError::Syntax(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_string() {
+        assert_eq!(quote_string("1.2.3"), r#""1.2.3""#);
+        assert_eq!(quote_string("a \"b\"\\c\n\u{1}"), r#""a \"b\"\\c\n\u0001""#);
+    }
+
+    #[test]
+    fn test_set_string_at_path_top_level() {
+        let input = r#"{ "name": "app", "version": "0.1.0" }"#;
+        let expected = r#"{ "name": "app", "version": "1.2.3" }"#;
+        assert_eq!(
+            set_string_at_path(input, "version", "1.2.3").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_set_string_at_path_preserves_formatting() {
+        let input = r#"{
+    "list": [1, {"version": "0.0.1"}],
+    "version" :   0.1,
+    "other": true
+}
+"#;
+        let expected = r#"{
+    "list": [1, {"version": "0.0.1"}],
+    "version" :   "1.2.3",
+    "other": true
+}
+"#;
+        assert_eq!(
+            set_string_at_path(input, "version", "1.2.3").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_set_string_at_path_escapes_the_value() {
+        let input = r#"{ "version": "0.1.0" }"#;
+        let expected = r#"{ "version": "1.2.3 \"beta\"" }"#;
+        assert_eq!(
+            set_string_at_path(input, "version", "1.2.3 \"beta\"").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_set_string_at_path_unicode_escapes_in_keys() {
+        let input = r#"{ "caf\u00e9": { "\ud83d\ude00": 1, "vers\u0069on": "0.1.0" } }"#;
+        let expected = r#"{ "caf\u00e9": { "\ud83d\ude00": 1, "vers\u0069on": "1.2.3" } }"#;
+        assert_eq!(
+            set_string_at_path(input, "caf\u{e9}.version", "1.2.3").unwrap(),
+            expected
+        );
+
+        for input in [r#"{ "a\u12": 1 }"#, r#"{ "\ud83d": 1 }"#] {
+            let err = set_string_at_path(input, "a", "1.2.3").unwrap_err();
+            assert!(matches!(err, JsonError::Syntax(_)), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_set_string_at_path_not_found() {
+        let input = r#"{ "build": { "number": 1 } }"#;
+        let err = set_string_at_path(input, "build.version", "1.2.3").unwrap_err();
+        assert!(matches!(err, JsonError::PathNotFound(ref p) if p == "build.version"));
+    }
+
+    #[test]
+    fn test_set_string_at_path_not_a_scalar() {
+        let input = r#"{ "build": { "version": {} } }"#;
+        let err = set_string_at_path(input, "build.version", "1.2.3").unwrap_err();
+        assert!(matches!(err, JsonError::NotAScalar(_)));
+    }
+
+    #[test]
+    fn test_set_string_at_path_invalid_json() {
+        let input = r#"{ "build": "#;
+        let err = set_string_at_path(input, "build", "1.2.3").unwrap_err();
+        assert!(matches!(err, JsonError::Syntax(_)));
+    }
+}
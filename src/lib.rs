@@ -0,0 +1,53 @@
+//! Yet another release tool
+//!
+//! The binary bumps the version of the supported files, commits, tags and pushes.
+//! The library exposes the file updaters, so that they can be driven programmatically,
+//! e.g. updating only the Cargo files of a crate with [rust::CargoDirUpdater].
+//!
+//! Updaters only compute the new contents of the files (see [files::DirUpdater]);
+//! writing them to disk (and staging them in git) is up to the caller.
+
+pub mod changelog;
+pub mod clojure;
+pub mod config;
+pub mod dart;
+pub mod date;
+pub mod delphi;
+mod diff;
+pub mod dotnet;
+pub mod effective_config;
+pub mod elixir;
+pub mod encoding;
+pub mod files;
+pub mod generic;
+pub mod git;
+#[cfg(feature = "github")]
+pub mod github;
+pub mod godot;
+pub mod gradle;
+pub mod helm;
+pub mod homebrew;
+pub mod hooks;
+pub mod infra;
+mod json_util;
+pub mod logger;
+pub mod maven;
+pub mod plan;
+pub mod progress;
+pub mod python;
+pub mod remote;
+pub mod ruby;
+pub mod rust;
+pub mod scala;
+pub mod sem_ver;
+pub mod sparkle;
+pub mod stamp;
+mod toml_util;
+pub mod updater;
+pub mod vb6;
+pub mod writers;
+mod xml_util;
+mod yaml_util;
+pub mod zig;
+
+pub use sem_ver::SemVer;
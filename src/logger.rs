@@ -0,0 +1,120 @@
+//! A tiny logger that writes messages to stderr, filtered by level
+
+use std::fmt::Display;
+use std::io::Write;
+use std::str::FromStr;
+
+/// The severity of a message, from the most to the least important.
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    /// The names of the levels, as accepted by [LogLevel::from_str].
+    pub const NAMES: [&'static str; 4] = ["error", "warn", "info", "debug"];
+}
+
+impl FromStr for LogLevel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Self::Error),
+            "warn" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Writes the messages that are at least as important as its level to stderr.
+/// Stdout is left for machine-readable output.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Logger {
+    level: LogLevel,
+}
+
+impl Logger {
+    pub fn new(level: LogLevel) -> Self {
+        Self { level }
+    }
+
+    /// Checks if messages of the given level are written.
+    pub fn enabled(&self, level: LogLevel) -> bool {
+        level <= self.level
+    }
+
+    pub fn error<D: Display>(&self, message: D) {
+        self.log(LogLevel::Error, message);
+    }
+
+    pub fn warn<D: Display>(&self, message: D) {
+        self.log(LogLevel::Warn, message);
+    }
+
+    pub fn info<D: Display>(&self, message: D) {
+        self.log(LogLevel::Info, message);
+    }
+
+    pub fn debug<D: Display>(&self, message: D) {
+        self.log(LogLevel::Debug, message);
+    }
+
+    /// Writes the message to stderr, followed by a newline, if its level is enabled.
+    pub fn log<D: Display>(&self, level: LogLevel, message: D) {
+        // a closed stderr is not worth failing the release for
+        let _ = self.write_to(&mut std::io::stderr(), level, message);
+    }
+
+    fn write_to<W: Write, D: Display>(
+        &self,
+        out: &mut W,
+        level: LogLevel,
+        message: D,
+    ) -> std::io::Result<()> {
+        if self.enabled(level) {
+            writeln!(out, "{}", message)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_is_suppressed_at_info() {
+        let logger = Logger::new(LogLevel::Info);
+        let mut out = Vec::<u8>::new();
+        logger
+            .write_to(&mut out, LogLevel::Debug, "Updated Cargo.toml")
+            .unwrap();
+        logger
+            .write_to(&mut out, LogLevel::Info, "Current version: 1.2.3")
+            .unwrap();
+        logger
+            .write_to(&mut out, LogLevel::Warn, "No files were changed")
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Current version: 1.2.3\nNo files were changed\n"
+        );
+    }
+
+    #[test]
+    fn test_enabled() {
+        let logger = Logger::new(LogLevel::Warn);
+        assert!(logger.enabled(LogLevel::Error));
+        assert!(logger.enabled(LogLevel::Warn));
+        assert!(!logger.enabled(LogLevel::Info));
+        assert!(!logger.enabled(LogLevel::Debug));
+    }
+}
@@ -1,70 +1,966 @@
 mod cli_options;
-mod delphi;
-mod files;
-mod git;
-mod rust;
-mod sem_ver;
-mod updater;
-mod vb6;
-mod writers;
-mod xml_util;
-
-use crate::sem_ver::SemVer;
+
+use crate::cli_options::{BumpFileOptions, OnOverflow, TagSource, TargetVersion};
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use yart::encoding::TextContents;
+use yart::files;
+#[cfg(feature = "github")]
+use yart::github;
+use yart::logger::Logger;
+use yart::plan::ReleasePlan;
+use yart::progress::{Event, Progress};
+use yart::sem_ver::{SemVer, SemVerComponent, SemVerComponentSet};
+use yart::writers::WriteOptions;
+use yart::{changelog, config, date, git, hooks, stamp, updater};
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+/// The message of the release is this prefix and the version, unless `--message` is given.
+const DEFAULT_MESSAGE_PREFIX: &str = "Releasing version";
 
-fn main() -> Result<(), &'static str> {
+/// The exit code of `--check` when no release is due.
+/// It differs from the exit code of errors (1) and of invalid arguments (2),
+/// so that CI can tell "nothing to release" from a failure.
+const CHECK_NOT_DUE_EXIT_CODE: i32 = 3;
+
+/// The value of `--branch` that stands for the default branch of the remote.
+const DEFAULT_BRANCH: &str = "@default";
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
     let args = cli_options::CliOptions::parse();
-    let git_tags_output = git::tags(&args.dir).unwrap();
-    match find_biggest_tag(&git_tags_output) {
+    let logger = Logger::new(args.log_level);
+    let progress = Progress::new(args.progress);
+    let runner = git::ProcessGitRunner;
+    let hook_runner = hooks::ShellHookRunner;
+    if let Some(format) = args.dump_config {
+        let mut effective_config = args.effective_config.clone();
+        effective_config.add_config_file(&config::load(&args.dir)?);
+        print!("{}", effective_config.format(format));
+        return Ok(());
+    }
+    let mut update_options = updater::UpdateOptions {
+        bump_version_code: args.bump_version_code,
+        snapshot: false,
+        build_number: args.build_number,
+        vb6_auto_revision: args.vb6_auto_revision,
+        stamp_date: if args.stamp_date {
+            Some(date::Date::today())
+        } else {
+            None
+        },
+        // read when releasing, so that an invalid counter does not abort read-only modes
+        build_counter: None,
+        maven_property: args.maven_property.clone(),
+        update_scm_tag: args.update_scm_tag,
+        update_values_image_tag: args.update_values_image_tag,
+        values_files: args.values_files.clone(),
+        skip_unpublished: args.skip_unpublished,
+        max_depth: args.max_depth,
+        logger,
+        keep_going: args.keep_going,
+    };
+    if let Some(bump_file) = &args.bump_file {
+        let mut contents = String::new();
+        std::io::stdin().read_to_string(&mut contents)?;
+        print!("{}", bump_contents(&contents, bump_file, &update_options)?);
+        return Ok(());
+    }
+    let selection = if args.files.is_empty() {
+        updater::FileSelection::Discover
+    } else {
+        updater::FileSelection::Explicit(args.files.clone(), args.file_kind)
+    };
+    if args.validate_only {
+        let version = updater::validate_versions(&args.dir, &selection, &update_options)?;
+        logger.info(format!("All files indicate version {}", version));
+        return Ok(());
+    }
+    if args.check {
+        let (is_due, reason) = check_release(
+            &runner,
+            &args.dir,
+            args.source,
+            args.lenient_tags,
+            &args.check_types,
+        )?;
+        println!("{}", reason);
+        if !is_due {
+            std::process::exit(CHECK_NOT_DUE_EXIT_CODE);
+        }
+        return Ok(());
+    }
+    if let Some(branch) = &args.branch {
+        let expected = if branch == DEFAULT_BRANCH {
+            git::default_branch(&runner, &args.dir, "origin")?
+        } else {
+            branch.clone()
+        };
+        let current = git::current_branch(&runner, &args.dir)?;
+        if current != expected {
+            return Err(format!(
+                "The current branch is {}, but releases are only made from {}",
+                current, expected
+            )
+            .into());
+        }
+    }
+    if !args.no_git && !args.force {
+        match git::ahead_behind(&runner, &args.dir) {
+            Ok((ahead, behind)) if behind > 0 => {
+                return Err(format!(
+                    "The branch is {} commit(s) behind its upstream (and {} ahead), pull before releasing or use --force",
+                    behind, ahead
+                )
+                .into());
+            }
+            Ok(_) => {}
+            Err(git::GitError::NonZeroExitCode) => {
+                logger.debug("The branch has no upstream, not checking if it is behind");
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+    let target_version = args
+        .version
+        .expect("clap requires -v unless --set-version or --validate-only is present");
+    let git_tags_output = if args.stdin_tags {
+        read_tags(std::io::stdin())?
+    } else {
+        read_git_tags(&runner, &args.dir, args.source)?
+    };
+    match find_biggest_tag(&git_tags_output, args.lenient_tags) {
         Some(biggest_tag) => {
-            let next_version = biggest_tag.bump(args.version);
-            println!(
+            if args.skip_if_no_commits {
+                let tag_name = find_tag_name(&git_tags_output, biggest_tag, args.lenient_tags)
+                    .expect("the biggest tag is one of the tags");
+                if git::commit_count_since(&runner, &args.dir, tag_name)? == 0 {
+                    logger.info(format!("No commits since {}, nothing to release", tag_name));
+                    return Ok(());
+                }
+            }
+            let next_version = resolve_next_version(
+                biggest_tag,
+                target_version,
+                args.allow_same_version,
+                args.on_overflow,
+            )?;
+            let is_same_version = next_version == biggest_tag;
+            if is_same_version && matches!(target_version, TargetVersion::Bump(_)) {
+                // the tag of the kept version is already published, it must not be moved
+                logger.warn(format!(
+                    "The version cannot be bumped further, keeping {} because of --on-overflow saturate, nothing to release",
+                    biggest_tag
+                ));
+                return Ok(());
+            }
+            logger.info(format!(
                 "Current version: {}, next version: {}",
                 biggest_tag, next_version
+            ));
+            // read the release notes before changing anything, so that a missing section aborts early
+            let release_notes = if args.tag_from_changelog {
+                Some(changelog::read_section(&args.dir, next_version)?)
+            } else if let Some(tag_body_file) = &args.tag_body_file {
+                Some(std::fs::read_to_string(tag_body_file).map_err(|err| {
+                    format!("Could not read {}: {}", tag_body_file.display(), err)
+                })?)
+            } else {
+                None
+            };
+            if args.build_counter {
+                update_options.build_counter = match stamp::next_build_counter(&args.dir) {
+                    Ok(counter) => Some(counter),
+                    Err(err) if args.plan_only || args.dry_run => {
+                        logger.warn(format!("{}, leaving out the build counter", err));
+                        None
+                    }
+                    Err(err) => return Err(err.into()),
+                };
+            }
+            if args.plan_only {
+                let plan = ReleasePlan::new(
+                    &args.dir,
+                    &selection,
+                    biggest_tag,
+                    next_version,
+                    &update_options,
+                )?;
+                print!("{}", plan.format(args.plan_format));
+                return Ok(());
+            }
+            // files are found under --dir, but staged relative to the repository root
+            let git_root = if args.no_git {
+                None
+            } else {
+                Some(git::toplevel(&runner, &args.dir)?)
+            };
+            // paths that yart does not update, but belong to the release commit,
+            // checked before any file is changed
+            let extra_paths = match (&args.commit_extra_paths_from, &git_root) {
+                (Some(list_file), Some(git_root)) => {
+                    let paths = parse_path_list(&std::fs::read_to_string(list_file)?);
+                    let missing = find_missing_paths(git_root, &paths);
+                    if !missing.is_empty() {
+                        return Err(format!(
+                            "Extra paths to commit not found: {}",
+                            missing
+                                .iter()
+                                .map(|path| path.to_string_lossy())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )
+                        .into());
+                    }
+                    paths
+                }
+                _ => Vec::new(),
+            };
+            if args.tag_only {
+                logger.info("Not updating any files because --tag-only was specified");
+            }
+            if args.strict {
+                // checked before any file is changed;
+                // a manifest that already indicates the next version is handled too
+                let handled =
+                    updater::dir_updater(&selection, &update_options).find_files(&args.dir)?;
+                let unhandled = files::find_unhandled_manifests(&args.dir, &handled);
+                if !unhandled.is_empty() {
+                    return Err(format!(
+                        "Found manifests that would not be updated, aborting because --strict was specified: {}",
+                        unhandled
+                            .iter()
+                            .map(|path| path.to_string_lossy())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                    .into());
+                }
+            }
+            let changed_files = if args.tag_only {
+                Vec::new()
+            } else if args.resume {
+                match resume_state(
+                    updater::files_match_version(
+                        &args.dir,
+                        &selection,
+                        next_version,
+                        &update_options,
+                    )?,
+                    updater::files_match_version(
+                        &args.dir,
+                        &selection,
+                        biggest_tag,
+                        &update_options,
+                    )?,
+                ) {
+                    ResumeState::Resume => {
+                        logger.info(format!(
+                            "Files are already at version {}, resuming with tagging",
+                            next_version
+                        ));
+                        Vec::new()
+                    }
+                    ResumeState::NotStarted => {
+                        return Err(
+                            "The release has not started yet, run again without --resume".into(),
+                        );
+                    }
+                    ResumeState::Inconsistent => {
+                        return Err(format!(
+                            "Cannot resume, files are neither at version {} nor at version {}",
+                            next_version, biggest_tag
+                        )
+                        .into());
+                    }
+                }
+            } else {
+                updater::update_files(
+                    &runner,
+                    args.dir.as_str(),
+                    git_root.clone(),
+                    &selection,
+                    next_version,
+                    &update_options,
+                    WriteOptions {
+                        dry_run: args.dry_run,
+                        output_dir: args.output_dir.clone(),
+                        color: use_color(args.no_color),
+                        logger,
+                        progress,
+                    },
+                )?
+            };
+            if args.verify {
+                updater::verify_files(&args.dir, &selection, next_version, &update_options)?;
+            }
+            if let Some(git_root) = &git_root {
+                for path in extra_paths.iter() {
+                    if args.dry_run {
+                        logger.info(format!("Would have staged {}", path.to_string_lossy()));
+                    } else {
+                        git::add(&runner, git_root, path)?;
+                        logger.debug(format!("Staged {}", path.to_string_lossy()));
+                    }
+                }
+            }
+            if !args.dry_run {
+                for (path_buf, _) in changed_files.iter() {
+                    logger.debug(format!("Updated {}", path_buf.to_string_lossy()));
+                }
+            }
+            // stdout is reserved for machine-readable output, diagnostics go to stderr
+            if args.list_changed {
+                let separator = if args.null_separated { '\0' } else { '\n' };
+                print!("{}", format_file_list(&changed_files, separator));
+            } else {
+                println!("{}", next_version);
+            }
+            // when resuming, the release commit is already HEAD;
+            // with --tag-only, HEAD is tagged as-is
+            let commit_action = commit_action(
+                !changed_files.is_empty() || !extra_paths.is_empty(),
+                args.require_changes && !args.resume,
             );
-            let changed_files =
-                updater::update_files(args.dir.as_str(), next_version, args.dry_run).unwrap();
+            match commit_action {
+                CommitAction::Abort => {
+                    return Err(
+                        "No files were changed, aborting because --require-changes was specified"
+                            .into(),
+                    );
+                }
+                CommitAction::TagHead if args.resume || args.tag_only => {}
+                CommitAction::TagHead => {
+                    logger.warn("No files were changed, the tag will point to the current HEAD");
+                }
+                CommitAction::Commit => {}
+            }
             if args.dry_run {
-                println!("Would have committed modified files, created tag, pushed to remote");
+                if let Some(hook) = &args.pre_commit_hook {
+                    logger.info(format!("Would have run the pre-commit hook {}", hook));
+                }
+                logger.info("Would have committed modified files, created tag, pushed to remote");
+                if let Some(hook) = &args.post_tag_hook {
+                    logger.info(format!("Would have run the post-tag hook {}", hook));
+                }
+                if let Some(component) = args.next_dev_version {
+                    logger.info(format!(
+                        "Would have committed the next development version {}-SNAPSHOT",
+                        bump_version(next_version, component, args.on_overflow)?
+                    ));
+                }
+            } else if args.no_git {
+                logger.info("Not committing, tagging or pushing because --no-git was specified");
             } else {
-                let msg_prefix = if args.message.is_empty() {
-                    "Releasing version".to_string()
-                } else {
-                    args.message
+                let template = match (&args.message, &args.message_prefix) {
+                    (Some(message), _) => message.clone(),
+                    (None, Some(prefix)) => format!("{} {{version}}", prefix),
+                    (None, None) => format!("{} {{version}}", DEFAULT_MESSAGE_PREFIX),
                 };
-                let msg = format!("{} {}", msg_prefix, next_version);
+                let today = date::Date::today().format(&args.date_format);
+                let msg = format_message(
+                    &template,
+                    next_version,
+                    &today,
+                    update_options.build_counter,
+                );
+                let mut trailers = args.commit_trailers.clone();
+                if args.signoff {
+                    trailers.push(format!(
+                        "Signed-off-by: {} <{}>",
+                        git::config_get(&runner, &args.dir, "user.name")?,
+                        git::config_get(&runner, &args.dir, "user.email")?
+                    ));
+                }
 
-                if !changed_files.is_empty() {
-                    git::commit(&args.dir, &msg).unwrap();
+                let commit_msg = if commit_action == CommitAction::Commit {
+                    Some(append_trailers(&msg, &trailers, &next_version.to_string()))
+                } else {
+                    None
+                };
+                let tag = format!("v{}", next_version);
+                let tag_kind = if args.lightweight {
+                    git::TagKind::Lightweight
+                } else {
+                    git::TagKind::Annotated
+                };
+                let mut tag_msg = match &release_notes {
+                    Some(notes) => format!("{}\n\n{}", msg, notes),
+                    None => msg.clone(),
+                };
+                if let Some(max_len) = args.max_tag_message_len {
+                    if let Some(truncated) = truncate_message(&tag_msg, max_len) {
+                        logger.warn(format!(
+                            "The tag message is longer than {} bytes, truncating it",
+                            max_len
+                        ));
+                        tag_msg = truncated;
+                    }
+                }
+                let mut release_tag = ReleaseTag {
+                    name: &tag,
+                    message: &tag_msg,
+                    kind: tag_kind,
+                    verbatim: release_notes.is_some(),
+                    commit: "HEAD",
+                    replace: is_same_version && args.allow_same_version,
+                    committed: commit_msg.is_some(),
+                };
+                // only when there is a release commit for the hook's changes to go in
+                if let (Some(hook), Some(_)) = (&args.pre_commit_hook, &commit_msg) {
+                    run_pre_commit_hook(&hook_runner, &runner, &args.dir, hook)?;
+                }
+                commit_and_tag(&runner, &args.dir, commit_msg.as_deref(), &release_tag)?;
+                if let Some(commit_msg) = commit_msg {
+                    progress.emit(Event::Committed(commit_msg));
+                }
+                progress.emit(Event::TagCreated(tag.clone()));
+                if let Some(hook) = &args.post_tag_hook {
+                    hooks::run_hook(&hook_runner, hook, &args.dir)?;
+                }
+                if let Some(component) = args.next_dev_version {
+                    let dev_version = bump_version(next_version, component, args.on_overflow)?;
+                    let dev_files = updater::update_dev_version_files(
+                        &runner,
+                        &args.dir,
+                        git_root.clone(),
+                        dev_version,
+                        WriteOptions {
+                            dry_run: false,
+                            output_dir: None,
+                            color: false,
+                            logger,
+                            progress,
+                        },
+                    )?;
+                    if dev_files.is_empty() {
+                        logger.warn("No files have a development version, not committing one");
+                    } else {
+                        let dev_msg = append_trailers(
+                            &format_dev_message(dev_version),
+                            &trailers,
+                            &format!("{}-SNAPSHOT", dev_version),
+                        );
+                        git::commit(&runner, &args.dir, &dev_msg)?;
+                        progress.emit(Event::Committed(dev_msg));
+                        // the tag stays on the release commit
+                        release_tag.commit = "HEAD~1";
+                    }
                 }
-                git::tag(&args.dir, &msg, format!("v{}", next_version)).unwrap();
                 if args.no_push {
-                    println!("Tagged, but not pushing because --no-push was specified");
+                    logger.info("Tagged, but not pushing because --no-push was specified");
                 } else {
-                    git::push(&args.dir).unwrap();
+                    if args.no_push_tags {
+                        logger.info("Not pushing the tag because --no-push-tags was specified");
+                    }
+                    push_with_retries(
+                        &runner,
+                        &args.dir,
+                        &release_tag,
+                        !args.no_push_tags,
+                        args.push_retries,
+                        logger,
+                    )?;
+                    progress.emit(Event::Pushed(if args.no_push_tags {
+                        None
+                    } else {
+                        Some(tag.clone())
+                    }));
+                    #[cfg(feature = "github")]
+                    if args.github_release {
+                        let body = match &release_notes {
+                            Some(notes) => notes.clone(),
+                            None => {
+                                changelog::read_section(&args.dir, next_version).unwrap_or_default()
+                            }
+                        };
+                        github::create_release(&runner, &args.dir, &tag, &body)?;
+                        logger.info(format!("Created GitHub release {}", tag));
+                    }
                 }
             }
             Ok(())
         }
-        _ => Err("Could not find a tag in vMajor.Minor.Patch format"),
+        _ => Err("Could not find a tag in vMajor.Minor.Patch format".into()),
     }
 }
 
-fn find_biggest_tag(tag_lines: &str) -> Option<SemVer> {
-    let mut tags: Vec<SemVer> = tag_lines
+/// The tag of the release.
+struct ReleaseTag<'a> {
+    name: &'a str,
+    message: &'a str,
+    kind: git::TagKind,
+
+    /// Keep the formatting of the message exactly (e.g. of release notes),
+    /// instead of letting git clean up its whitespace.
+    verbatim: bool,
+
+    /// The release commit, relative to HEAD (e.g. `HEAD~1` when
+    /// the next development version was committed after it).
+    commit: &'a str,
+
+    /// Replace an existing tag with the same name (re-releasing the same version),
+    /// locally and on the remote.
+    replace: bool,
+
+    /// The tag is on a release commit that was created locally, as opposed to
+    /// an existing commit (e.g. with `--tag-only` or when no files changed).
+    committed: bool,
+}
+
+/// Runs the pre-commit hook and stages the files it changed,
+/// so that they are part of the release commit.
+/// Files that were already modified before the hook are left as they are.
+fn run_pre_commit_hook(
+    hook_runner: &dyn hooks::HookRunner,
+    runner: &dyn git::GitRunner,
+    dir: &str,
+    hook: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let modified_before = git::unstaged_files(runner, dir)?;
+    hooks::run_hook(hook_runner, hook, dir)?;
+    let changed_by_hook: Vec<String> = git::unstaged_files(runner, dir)?
+        .into_iter()
+        .filter(|path| !modified_before.contains(path))
+        .collect();
+    if !changed_by_hook.is_empty() {
+        git::add_from_root(runner, dir, &changed_by_hook)?;
+    }
+    Ok(())
+}
+
+/// Commits the release (unless there is nothing to commit) and tags HEAD.
+fn commit_and_tag(
+    runner: &dyn git::GitRunner,
+    dir: &str,
+    commit_msg: Option<&str>,
+    tag: &ReleaseTag,
+) -> Result<(), git::GitError> {
+    if let Some(commit_msg) = commit_msg {
+        git::commit(runner, dir, commit_msg)?;
+    }
+    let verbatim = tag.verbatim && tag.kind == git::TagKind::Annotated;
+    match (tag.replace, verbatim) {
+        (true, true) => {
+            git::tag_with_body_force_at(runner, dir, tag.message, tag.name, false, "HEAD")
+        }
+        (true, false) => git::tag_force(runner, dir, tag.message, tag.name, tag.kind),
+        (false, true) => git::tag_with_body(runner, dir, tag.message, tag.name, false),
+        (false, false) => git::tag(runner, dir, tag.message, tag.name, tag.kind),
+    }
+}
+
+/// Pushes the release, retrying up to the given number of times.
+/// The tag is pushed along with the branch, unless `push_tag` is false.
+/// Before each retry, remote changes are pulled with a rebase.
+/// The rebase rewrites the release commit, so the tag is moved to its new location.
+/// Without a release commit, the tag cannot follow the rebase, so nothing is retried.
+fn push_with_retries(
+    runner: &dyn git::GitRunner,
+    dir: &str,
+    tag: &ReleaseTag,
+    push_tag: bool,
+    retries: u32,
+    logger: Logger,
+) -> Result<(), git::GitError> {
+    let mut attempt = 0;
+    loop {
+        let push_result = if !push_tag {
+            git::push_branch(runner, dir)
+        } else if tag.replace {
+            // the remote rejects a moved tag unless it is forced
+            git::push_branch(runner, dir).and_then(|_| git::push_tag_force(runner, dir, tag.name))
+        } else {
+            git::push(runner, dir, tag.name, tag.kind)
+        };
+        match push_result {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt < retries && !tag.committed => {
+                logger.error(format!(
+                    "Push failed ({}), not retrying because the tag is not on a release commit that a rebase would keep",
+                    err
+                ));
+                return Err(err);
+            }
+            Err(err) if attempt < retries => {
+                attempt += 1;
+                logger.warn(format!(
+                    "Push failed ({}), pulling and retrying ({}/{})",
+                    err, attempt, retries
+                ));
+                std::thread::sleep(std::time::Duration::from_secs(u64::from(attempt)));
+                git::pull_rebase(runner, dir)?;
+                if tag.verbatim && tag.kind == git::TagKind::Annotated {
+                    git::tag_with_body_force_at(
+                        runner,
+                        dir,
+                        tag.message,
+                        tag.name,
+                        false,
+                        tag.commit,
+                    )?;
+                } else {
+                    git::tag_force_at(runner, dir, tag.message, tag.name, tag.kind, tag.commit)?;
+                }
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Checks if the output (stderr) should be colored.
+/// Follows the `NO_COLOR` convention (https://no-color.org).
+fn use_color(no_color: bool) -> bool {
+    !no_color
+        && std::env::var_os("NO_COLOR").unwrap_or_default().is_empty()
+        && std::io::stderr().is_terminal()
+}
+
+/// Formats the paths of the given files, each one followed by the separator.
+fn format_file_list(files: &[(PathBuf, TextContents)], separator: char) -> String {
+    let mut result = String::new();
+    for (path_buf, _) in files {
+        result.push_str(&path_buf.to_string_lossy());
+        result.push(separator);
+    }
+    result
+}
+
+/// Builds the commit and tag message, replacing the `{version}` and `{date}` placeholders
+/// and, with a build counter, the `{build}` placeholder.
+fn format_message(template: &str, version: SemVer, date: &str, build: Option<u64>) -> String {
+    let mut message = template
+        .replace("{version}", &version.to_string())
+        .replace("{date}", date);
+    if let Some(build) = build {
+        message = message.replace("{build}", &build.to_string());
+    }
+    message
+}
+
+/// Appends the trailers (e.g. `Release-Version: {version}`) to the commit message,
+/// separated by a blank line, replacing the `{version}` placeholder.
+fn append_trailers(message: &str, trailers: &[String], version: &str) -> String {
+    if trailers.is_empty() {
+        return message.to_owned();
+    }
+    let mut result = format!("{}\n", message);
+    for trailer in trailers {
+        result.push('\n');
+        result.push_str(&trailer.replace("{version}", version));
+    }
+    result
+}
+
+/// Shortens the message to at most `max_len` bytes, ending it with an ellipsis.
+/// Returns `None` if the message is short enough.
+/// Multibyte characters are never split.
+fn truncate_message(message: &str, max_len: usize) -> Option<String> {
+    if message.len() <= max_len {
+        return None;
+    }
+    let ellipsis = if max_len >= ELLIPSIS.len() {
+        ELLIPSIS
+    } else {
+        ""
+    };
+    let mut end = max_len - ellipsis.len();
+    while !message.is_char_boundary(end) {
+        end -= 1;
+    }
+    Some(format!("{}{}", &message[..end], ellipsis))
+}
+
+const ELLIPSIS: &str = "\u{2026}";
+
+/// Builds the message of the commit of the next development version.
+fn format_dev_message(dev_version: SemVer) -> String {
+    format!(
+        "Preparing next development version {}-SNAPSHOT",
+        dev_version
+    )
+}
+
+/// Updates the contents of a file of the given format (`bump-file`).
+/// Without a repository, a bumped version is based on the version of the file itself.
+fn bump_contents(
+    contents: &str,
+    bump_file: &BumpFileOptions,
+    options: &updater::UpdateOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let processor = updater::processor(bump_file.kind, options, &config::Config::default());
+    let version = match bump_file.version {
+        TargetVersion::Explicit(version) => version,
+        TargetVersion::Bump(component) => bump_version(
+            processor
+                .extract_version(contents)
+                .ok_or("Cannot read the version of the file, use --set-version instead")?
+                .version,
+            component,
+            bump_file.on_overflow,
+        )?,
+    };
+    Ok(processor.process(contents, version)?)
+}
+
+/// Determines the next version based on the current version.
+/// An explicit version must not leave gaps from the current version.
+/// It may only be equal to the current version if `allow_same_version` is set.
+fn resolve_next_version(
+    current: SemVer,
+    target: TargetVersion,
+    allow_same_version: bool,
+    on_overflow: OnOverflow,
+) -> Result<SemVer, &'static str> {
+    match target {
+        TargetVersion::Bump(component) => bump_version(current, component, on_overflow),
+        TargetVersion::Explicit(explicit) => {
+            if explicit == current {
+                if allow_same_version {
+                    Ok(explicit)
+                } else {
+                    Err("The requested version is the current version, use --allow-same-version to release it again")
+                }
+            } else if SemVerComponentSet::all()
+                .any(|component| current.checked_bump(component) == Some(explicit))
+            {
+                Ok(explicit)
+            } else {
+                Err("The requested version must be the next major, minor or patch version")
+            }
+        }
+    }
+}
+
+/// Bumps a component of the version, handling a component that is already 65535
+/// according to `--on-overflow`.
+fn bump_version(
+    current: SemVer,
+    component: SemVerComponent,
+    on_overflow: OnOverflow,
+) -> Result<SemVer, &'static str> {
+    match (current.checked_bump(component), on_overflow) {
+        (Some(next), _) => Ok(next),
+        (None, OnOverflow::Saturate) => Ok(current.saturating_bump(component)),
+        (None, OnOverflow::Error) => {
+            Err("The version cannot be bumped past 65535, use --on-overflow saturate to keep it")
+        }
+    }
+}
+
+/// Determines what happens after the project files have been updated.
+#[derive(Debug, Eq, PartialEq)]
+enum CommitAction {
+    /// Files were changed, commit them before tagging.
+    Commit,
+
+    /// No files were changed, tag the current HEAD without committing.
+    TagHead,
+
+    /// No files were changed and changes were required, abort the release.
+    Abort,
+}
+
+fn commit_action(has_changes: bool, require_changes: bool) -> CommitAction {
+    if has_changes {
+        CommitAction::Commit
+    } else if require_changes {
+        CommitAction::Abort
+    } else {
+        CommitAction::TagHead
+    }
+}
+
+/// Determines if an interrupted release can be resumed.
+#[derive(Debug, Eq, PartialEq)]
+enum ResumeState {
+    /// Files are at the next version, the release commit exists.
+    Resume,
+
+    /// Files are still at the current version, nothing to resume.
+    NotStarted,
+
+    /// Files are at neither version.
+    Inconsistent,
+}
+
+fn resume_state(files_at_next_version: bool, files_at_current_version: bool) -> ResumeState {
+    if files_at_next_version {
+        ResumeState::Resume
+    } else if files_at_current_version {
+        ResumeState::NotStarted
+    } else {
+        ResumeState::Inconsistent
+    }
+}
+
+/// Parses a list of paths, one per line. Blank lines are skipped.
+fn parse_path_list(contents: &str) -> Vec<PathBuf> {
+    contents
         .lines()
         .map(str::trim)
-        .map(remove_v_prefix)
-        .filter(Option::is_some)
-        .map(Option::unwrap)
-        .map(SemVer::from_str)
-        .filter(Result::is_ok)
-        .map(Result::unwrap)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Finds the paths that do not exist under the given root.
+fn find_missing_paths(root: &Path, paths: &[PathBuf]) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .filter(|path| !root.join(path).exists())
+        .cloned()
+        .collect()
+}
+
+/// Reads a list of tags, one per line, e.g. the output of `git tag --list`.
+fn read_tags<R: Read>(mut reader: R) -> std::io::Result<String> {
+    let mut result = String::new();
+    reader.read_to_string(&mut result)?;
+    Ok(result)
+}
+
+/// Reads the release tags (one per line) from git.
+fn read_git_tags<P: AsRef<Path>>(
+    runner: &dyn git::GitRunner,
+    dir: P,
+    source: TagSource,
+) -> Result<String, git::GitError> {
+    if source == TagSource::Describe {
+        match git::describe(runner, dir, Some("v*")) {
+            Ok(tag) => Ok(format!("{}\n", tag)),
+            // no tag is reachable from HEAD, same as having no tags
+            Err(git::GitError::NonZeroExitCode) => Ok(String::new()),
+            Err(err) => Err(err),
+        }
+    } else {
+        // only tags in vMajor.Minor.Patch format are considered
+        git::tags_matching(runner, dir, "v*")
+    }
+}
+
+/// Decides for `--check` if a release is due, i.e. if there are commits since the latest tag.
+/// With `types`, only Conventional Commits of these types (or breaking changes) count.
+/// Returns the decision and a short reason.
+fn check_release<P: AsRef<Path>>(
+    runner: &dyn git::GitRunner,
+    dir: P,
+    source: TagSource,
+    lenient: bool,
+    types: &[String],
+) -> Result<(bool, String), git::GitError> {
+    let dir = dir.as_ref();
+    let tags = read_git_tags(runner, dir, source)?;
+    let tag_name = match find_biggest_tag(&tags, lenient) {
+        Some(biggest_tag) => {
+            find_tag_name(&tags, biggest_tag, lenient).expect("the biggest tag is one of the tags")
+        }
+        None => return Ok((true, "No release tag yet, a release is due".to_owned())),
+    };
+    if types.is_empty() {
+        let count = git::commit_count_since(runner, dir, tag_name)?;
+        return Ok(if count == 0 {
+            (
+                false,
+                format!("No commits since {}, nothing to release", tag_name),
+            )
+        } else {
+            (
+                true,
+                format!("{} commit(s) since {}, a release is due", count, tag_name),
+            )
+        });
+    }
+    let commits = git::log_between(runner, dir, Some(tag_name), "HEAD")?;
+    let releasable = commits
+        .iter()
+        .filter(|commit| is_releasable(commit, types))
+        .count();
+    Ok(if releasable == 0 {
+        (
+            false,
+            format!(
+                "None of the {} commit(s) since {} is of type {}, nothing to release",
+                commits.len(),
+                tag_name,
+                types.join("/")
+            ),
+        )
+    } else {
+        (
+            true,
+            format!(
+                "{} {} commit(s) since {}, a release is due",
+                releasable,
+                types.join("/"),
+                tag_name
+            ),
+        )
+    })
+}
+
+/// Checks if a commit is a Conventional Commit (e.g. `feat(parser): ...`) of one of the given types.
+/// Breaking changes (`feat!: ...` or a `BREAKING CHANGE:` footer) always are.
+fn is_releasable(commit: &git::Commit, types: &[String]) -> bool {
+    let prefix = match commit.subject.split_once(':') {
+        Some((prefix, _)) => prefix.trim(),
+        None => return false,
+    };
+    let (prefix, is_breaking) = match prefix.strip_suffix('!') {
+        Some(prefix) => (prefix, true),
+        None => (prefix, false),
+    };
+    let commit_type = match prefix.split_once('(') {
+        Some((commit_type, scope)) if scope.ends_with(')') => commit_type,
+        Some(_) => return false,
+        None => prefix,
+    };
+    if commit_type.is_empty() || !commit_type.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return false;
+    }
+    is_breaking
+        || commit.body.contains("BREAKING CHANGE:")
+        || commit.body.contains("BREAKING-CHANGE:")
+        || types
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(commit_type))
+}
+
+/// Finds the biggest version among the given tags (one per line).
+/// Tags that are not valid versions are skipped, unless `lenient` is set,
+/// in which case common mistakes are recovered (see [SemVer::parse_lenient]).
+fn find_biggest_tag(tag_lines: &str, lenient: bool) -> Option<SemVer> {
+    let mut tags: Vec<SemVer> = tag_lines
+        .lines()
+        .filter_map(|tag| parse_tag(tag, lenient))
         .collect();
     tags.sort();
     tags.pop()
 }
 
+/// Finds the tag (as written, e.g. `v1.2` with `lenient`) of the given version.
+fn find_tag_name(tag_lines: &str, version: SemVer, lenient: bool) -> Option<&str> {
+    tag_lines
+        .lines()
+        .map(str::trim)
+        .find(|tag| parse_tag(tag, lenient) == Some(version))
+}
+
+fn parse_tag(tag: &str, lenient: bool) -> Option<SemVer> {
+    let tag = remove_v_prefix(tag.trim())?;
+    if lenient {
+        SemVer::parse_lenient(tag).ok().map(|(version, _)| version)
+    } else {
+        SemVer::from_str(tag).ok()
+    }
+}
+
 fn remove_v_prefix(tag: &str) -> Option<&str> {
     if tag.starts_with("v") {
         let (_, tag_without_v_prefix) = tag.split_at(1);
@@ -81,6 +977,210 @@ fn remove_v_prefix(tag: &str) -> Option<&str> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::ffi::OsStr;
+    #[cfg(unix)]
+    use std::os::unix::process::ExitStatusExt;
+    #[cfg(windows)]
+    use std::os::windows::process::ExitStatusExt;
+    use std::process::{ExitStatus, Output};
+
+    /// Records the git commands instead of running them.
+    /// The first `failing_pushes` pushes fail.
+    #[derive(Default)]
+    struct RecordingRunner {
+        commands: RefCell<Vec<String>>,
+        failing_pushes: Cell<u32>,
+    }
+
+    impl git::GitRunner for RecordingRunner {
+        fn run(&self, args: &[&OsStr], _dir: &Path) -> std::io::Result<Output> {
+            let args: Vec<String> = args
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            let fails = args[0] == "push" && self.failing_pushes.get() > 0;
+            if fails {
+                self.failing_pushes.set(self.failing_pushes.get() - 1);
+            }
+            self.commands.borrow_mut().push(args.join(" "));
+            Ok(Output {
+                // the exit code is in the high byte of a unix wait status
+                #[cfg(unix)]
+                status: ExitStatus::from_raw(if fails { 1 << 8 } else { 0 }),
+                #[cfg(windows)]
+                status: ExitStatus::from_raw(if fails { 1 } else { 0 }),
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_release_commands() {
+        let runner = RecordingRunner::default();
+        let tag = ReleaseTag {
+            name: "v1.2.3",
+            message: "Releasing version 1.2.3",
+            kind: git::TagKind::Annotated,
+            verbatim: false,
+            commit: "HEAD",
+            replace: false,
+            committed: true,
+        };
+        commit_and_tag(&runner, ".", Some("Releasing version 1.2.3"), &tag).unwrap();
+        push_with_retries(&runner, ".", &tag, true, 0, Logger::default()).unwrap();
+        assert_eq!(
+            runner.commands.into_inner(),
+            vec![
+                "commit -m Releasing version 1.2.3",
+                "tag --cleanup=whitespace -m Releasing version 1.2.3 v1.2.3",
+                "push --follow-tags",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_release_commands_without_changes() {
+        let runner = RecordingRunner::default();
+        let tag = ReleaseTag {
+            name: "v1.2.3",
+            message: "",
+            kind: git::TagKind::Lightweight,
+            verbatim: false,
+            commit: "HEAD",
+            replace: true,
+            committed: false,
+        };
+        commit_and_tag(&runner, ".", None, &tag).unwrap();
+        push_with_retries(&runner, ".", &tag, true, 0, Logger::default()).unwrap();
+        assert_eq!(
+            runner.commands.into_inner(),
+            vec!["tag -f v1.2.3", "push", "push origin +refs/tags/v1.2.3"]
+        );
+    }
+
+    #[test]
+    fn test_release_commands_with_tag_body() {
+        let runner = RecordingRunner::default();
+        let tag = ReleaseTag {
+            name: "v1.2.3",
+            message: "Releasing version 1.2.3\n\n# Added\n\n- Things  \n",
+            kind: git::TagKind::Annotated,
+            verbatim: true,
+            commit: "HEAD",
+            replace: false,
+            committed: false,
+        };
+        commit_and_tag(&runner, ".", None, &tag).unwrap();
+        assert_eq!(
+            runner.commands.into_inner(),
+            vec!["tag -a --cleanup=verbatim -F - v1.2.3"]
+        );
+    }
+
+    #[test]
+    fn test_release_commands_replacing_tag_with_body() {
+        let runner = RecordingRunner::default();
+        let tag = ReleaseTag {
+            name: "v1.2.3",
+            message: "Releasing version 1.2.3\n\n# Added\n",
+            kind: git::TagKind::Annotated,
+            verbatim: true,
+            commit: "HEAD",
+            replace: true,
+            committed: false,
+        };
+        commit_and_tag(&runner, ".", None, &tag).unwrap();
+        push_with_retries(&runner, ".", &tag, true, 0, Logger::default()).unwrap();
+        assert_eq!(
+            runner.commands.into_inner(),
+            vec![
+                "tag -a -f --cleanup=verbatim -F - v1.2.3 HEAD",
+                "push",
+                "push origin +refs/tags/v1.2.3"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_push_retry_moves_the_tag_to_the_rebased_release_commit() {
+        let runner = RecordingRunner {
+            failing_pushes: Cell::new(1),
+            ..RecordingRunner::default()
+        };
+        let tag = ReleaseTag {
+            name: "v1.2.3",
+            message: "Releasing version 1.2.3\n\n# Added\n",
+            kind: git::TagKind::Annotated,
+            verbatim: true,
+            commit: "HEAD~1",
+            replace: false,
+            committed: true,
+        };
+        push_with_retries(&runner, ".", &tag, true, 1, Logger::default()).unwrap();
+        assert_eq!(
+            runner.commands.into_inner(),
+            vec![
+                "push --follow-tags",
+                "pull --rebase",
+                "tag -a -f --cleanup=verbatim -F - v1.2.3 HEAD~1",
+                "push --follow-tags",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_push_is_not_retried_without_a_release_commit() {
+        let runner = RecordingRunner {
+            failing_pushes: Cell::new(1),
+            ..RecordingRunner::default()
+        };
+        let tag = ReleaseTag {
+            name: "v1.2.3",
+            message: "Releasing version 1.2.3",
+            kind: git::TagKind::Annotated,
+            verbatim: false,
+            commit: "HEAD",
+            replace: false,
+            committed: false,
+        };
+        assert!(push_with_retries(&runner, ".", &tag, true, 3, Logger::default()).is_err());
+        assert_eq!(runner.commands.into_inner(), vec!["push --follow-tags"]);
+    }
+
+    /// Exits every hook with the given code.
+    struct ExitingHookRunner(i32);
+
+    impl hooks::HookRunner for ExitingHookRunner {
+        fn run(&self, _command: &str, _dir: &Path) -> std::io::Result<ExitStatus> {
+            // the exit code is in the high byte of a unix wait status
+            #[cfg(unix)]
+            return Ok(ExitStatus::from_raw(self.0 << 8));
+            #[cfg(windows)]
+            return Ok(ExitStatus::from_raw(self.0 as u32));
+        }
+    }
+
+    #[test]
+    fn test_pre_commit_hook_stages_the_changes() {
+        let runner = RecordingRunner::default();
+        run_pre_commit_hook(&ExitingHookRunner(0), &runner, ".", "cargo fmt").unwrap();
+        // nothing was modified, so there is nothing to stage
+        assert_eq!(
+            runner.commands.into_inner(),
+            vec!["diff --name-only -z", "diff --name-only -z"]
+        );
+    }
+
+    #[test]
+    fn test_pre_commit_hook_failure_aborts() {
+        let runner = RecordingRunner::default();
+        let err =
+            run_pre_commit_hook(&ExitingHookRunner(1), &runner, ".", "cargo fmt").unwrap_err();
+        assert_eq!(err.to_string(), "hook cargo fmt exited with code 1");
+        assert_eq!(runner.commands.into_inner(), vec!["diff --name-only -z"]);
+    }
 
     #[test]
     fn test_find_biggest_tag() {
@@ -91,15 +1191,324 @@ mod tests {
         0.6.0
         ";
         let expected = SemVer::new(0, 4, 0);
-        let actual = find_biggest_tag(input).unwrap();
+        let actual = find_biggest_tag(input, false).unwrap();
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_find_biggest_tag_lenient() {
+        let input = "v0.3.0\nv0.4\nv0.3.1.7\nv0.2.0-\n";
+        assert_eq!(find_biggest_tag(input, false), Some(SemVer::new(0, 3, 0)));
+        assert_eq!(find_biggest_tag(input, true), Some(SemVer::new(0, 4, 0)));
+    }
+
+    #[test]
+    fn test_find_tag_name() {
+        let input = "v0.3.0\nv0.4\n";
+        assert_eq!(
+            find_tag_name(input, SemVer::new(0, 3, 0), false),
+            Some("v0.3.0")
+        );
+        assert_eq!(find_tag_name(input, SemVer::new(0, 4, 0), false), None);
+        assert_eq!(
+            find_tag_name(input, SemVer::new(0, 4, 0), true),
+            Some("v0.4")
+        );
+    }
+
+    #[test]
+    fn test_is_releasable() {
+        let types = vec!["feat".to_owned(), "fix".to_owned()];
+        let commit = |subject: &str, body: &str| git::Commit {
+            hash: "a1b2c3d".to_owned(),
+            subject: subject.to_owned(),
+            body: body.to_owned(),
+        };
+        assert!(is_releasable(&commit("feat: support markers", ""), &types));
+        assert!(is_releasable(&commit("fix(parser): keep BOM", ""), &types));
+        assert!(is_releasable(&commit("Fix: keep BOM", ""), &types));
+        assert!(!is_releasable(&commit("chore: update deps", ""), &types));
+        assert!(!is_releasable(
+            &commit("docs(readme): fix typo", ""),
+            &types
+        ));
+        assert!(!is_releasable(&commit("Fix the parser", ""), &types));
+        assert!(!is_releasable(&commit("fix parser: keep BOM", ""), &types));
+        // breaking changes are always releasable
+        assert!(is_releasable(&commit("refactor!: drop --foo", ""), &types));
+        assert!(is_releasable(
+            &commit("chore(deps)!: bump MSRV", ""),
+            &types
+        ));
+        assert!(is_releasable(
+            &commit("refactor: drop --foo", "BREAKING CHANGE: --foo is gone"),
+            &types
+        ));
+    }
+
+    #[test]
+    fn test_resolve_next_version_bump() {
+        let current = SemVer::new(1, 2, 3);
+        let actual = resolve_next_version(
+            current,
+            TargetVersion::Bump(SemVerComponent::Minor),
+            false,
+            OnOverflow::Error,
+        );
+        assert_eq!(actual, Ok(SemVer::new(1, 3, 0)));
+    }
+
+    #[test]
+    fn test_resolve_next_version_overflow() {
+        let current = SemVer::new(1, 2, u16::MAX);
+        let bump_patch = TargetVersion::Bump(SemVerComponent::Patch);
+        assert!(resolve_next_version(current, bump_patch, false, OnOverflow::Error).is_err());
+        assert_eq!(
+            resolve_next_version(current, bump_patch, false, OnOverflow::Saturate),
+            Ok(current)
+        );
+        // other components can still be bumped
+        assert_eq!(
+            resolve_next_version(
+                current,
+                TargetVersion::Bump(SemVerComponent::Minor),
+                false,
+                OnOverflow::Error
+            ),
+            Ok(SemVer::new(1, 3, 0))
+        );
+        // no next patch version to compare an explicit version with
+        assert_eq!(
+            resolve_next_version(
+                current,
+                TargetVersion::Explicit(SemVer::new(2, 0, 0)),
+                false,
+                OnOverflow::Error
+            ),
+            Ok(SemVer::new(2, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_resolve_next_version_explicit() {
+        let current = SemVer::new(1, 2, 3);
+        for next in &[
+            SemVer::new(2, 0, 0),
+            SemVer::new(1, 3, 0),
+            SemVer::new(1, 2, 4),
+        ] {
+            let actual = resolve_next_version(
+                current,
+                TargetVersion::Explicit(*next),
+                false,
+                OnOverflow::Error,
+            );
+            assert_eq!(actual, Ok(*next));
+        }
+    }
+
+    #[test]
+    fn test_resolve_next_version_explicit_gap() {
+        let current = SemVer::new(1, 2, 3);
+        for next in &[
+            SemVer::new(1, 2, 5),
+            SemVer::new(1, 4, 0),
+            SemVer::new(1, 3, 1),
+            SemVer::new(1, 2, 2),
+        ] {
+            assert!(resolve_next_version(
+                current,
+                TargetVersion::Explicit(*next),
+                true,
+                OnOverflow::Error
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_resolve_next_version_same_version_not_allowed() {
+        let current = SemVer::new(1, 2, 3);
+        assert!(resolve_next_version(
+            current,
+            TargetVersion::Explicit(current),
+            false,
+            OnOverflow::Error
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_resolve_next_version_same_version_allowed() {
+        let current = SemVer::new(1, 2, 3);
+        let actual = resolve_next_version(
+            current,
+            TargetVersion::Explicit(current),
+            true,
+            OnOverflow::Error,
+        );
+        assert_eq!(actual, Ok(current));
+    }
+
+    #[test]
+    fn test_format_message() {
+        assert_eq!(
+            format_message(
+                "Releasing version {version}",
+                SemVer::new(1, 2, 3),
+                "2024-03-07",
+                None
+            ),
+            "Releasing version 1.2.3"
+        );
+        assert_eq!(
+            format_message(
+                "Release of {date}, version {version}",
+                SemVer::new(1, 2, 3),
+                "2024-03-07",
+                None
+            ),
+            "Release of 2024-03-07, version 1.2.3"
+        );
+        assert_eq!(
+            format_message(
+                "Build {build}, version {version}",
+                SemVer::new(1, 2, 3),
+                "",
+                Some(42)
+            ),
+            "Build 42, version 1.2.3"
+        );
+        // the version is only part of the message where the placeholder is
+        assert_eq!(
+            format_message("chore: release", SemVer::new(1, 2, 3), "", None),
+            "chore: release"
+        );
+    }
+
+    #[test]
+    fn test_append_trailers() {
+        assert_eq!(
+            append_trailers("Releasing version 1.2.3", &[], "1.2.3"),
+            "Releasing version 1.2.3"
+        );
+        assert_eq!(
+            append_trailers(
+                "Releasing version 1.2.3",
+                &[
+                    "Release-Version: {version}".to_owned(),
+                    "Signed-off-by: Jane Doe <jane@example.com>".to_owned()
+                ],
+                "1.2.3"
+            ),
+            "Releasing version 1.2.3\n\nRelease-Version: 1.2.3\nSigned-off-by: Jane Doe <jane@example.com>"
+        );
+    }
+
+    #[test]
+    fn test_truncate_message() {
+        assert_eq!(truncate_message("Releasing version 1.2.3", 23), None);
+        assert_eq!(truncate_message("", 0), None);
+        assert_eq!(
+            truncate_message("Releasing version 1.2.3", 12),
+            Some("Releasing\u{2026}".to_owned())
+        );
+        assert_eq!(truncate_message("Releasing", 2), Some("Re".to_owned()));
+    }
+
+    #[test]
+    fn test_truncate_message_at_char_boundary() {
+        // "é" takes two bytes, the cut would fall in its middle
+        let truncated = truncate_message("Caf\u{e9} release notes", 7).unwrap();
+        assert_eq!(truncated, "Caf\u{2026}");
+        assert!(truncated.len() <= 7);
+        assert_eq!(
+            truncate_message("Caf\u{e9} release notes", 8),
+            Some("Caf\u{e9}\u{2026}".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_format_dev_message() {
+        assert_eq!(
+            format_dev_message(SemVer::new(1, 3, 0)),
+            "Preparing next development version 1.3.0-SNAPSHOT"
+        );
+    }
+
+    #[test]
+    fn test_commit_action() {
+        assert_eq!(commit_action(true, false), CommitAction::Commit);
+        assert_eq!(commit_action(true, true), CommitAction::Commit);
+    }
+
+    #[test]
+    fn test_commit_action_no_changes() {
+        assert_eq!(commit_action(false, false), CommitAction::TagHead);
+        assert_eq!(commit_action(false, true), CommitAction::Abort);
+    }
+
+    #[test]
+    fn test_format_file_list() {
+        let files = vec![
+            (
+                PathBuf::from("Cargo.toml"),
+                TextContents::from(String::new()),
+            ),
+            (
+                PathBuf::from("Cargo.lock"),
+                TextContents::from(String::new()),
+            ),
+        ];
+        assert_eq!(format_file_list(&files, '\n'), "Cargo.toml\nCargo.lock\n");
+        assert_eq!(format_file_list(&files, '\0'), "Cargo.toml\0Cargo.lock\0");
+        assert_eq!(format_file_list(&[], '\n'), "");
+    }
+
+    #[test]
+    fn test_resume_state() {
+        assert_eq!(resume_state(true, false), ResumeState::Resume);
+        // no versioned files at all
+        assert_eq!(resume_state(true, true), ResumeState::Resume);
+        assert_eq!(resume_state(false, true), ResumeState::NotStarted);
+        assert_eq!(resume_state(false, false), ResumeState::Inconsistent);
+    }
+
+    #[test]
+    fn test_read_tags() {
+        let input = "v0.1.0\nv0.10.0\nv0.2.0\n";
+        let tags = read_tags(input.as_bytes()).unwrap();
+        assert_eq!(find_biggest_tag(&tags, false), Some(SemVer::new(0, 10, 0)));
+    }
+
+    #[test]
+    fn test_parse_path_list() {
+        assert_eq!(
+            parse_path_list("db/migrations/042_release.sql\r\n\n  docs/release.md  \n"),
+            vec![
+                PathBuf::from("db/migrations/042_release.sql"),
+                PathBuf::from("docs/release.md")
+            ]
+        );
+        assert!(parse_path_list("\n").is_empty());
+    }
+
+    #[test]
+    fn test_find_missing_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("present.txt"), "").unwrap();
+        let paths = vec![PathBuf::from("present.txt"), PathBuf::from("missing.txt")];
+        assert_eq!(
+            find_missing_paths(dir.path(), &paths),
+            vec![PathBuf::from("missing.txt")]
+        );
+    }
+
     #[test]
     fn test_find_biggest_tag_no_tags() {
         let input = r"
         not-a-valid-tag
         ";
-        assert!(find_biggest_tag(input).is_none());
+        assert!(find_biggest_tag(input, false).is_none());
     }
 }
@@ -0,0 +1,232 @@
+//! Handles Maven projects (pom.xml)
+extern crate xml;
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use crate::files::{ContentProcessor, FileFinder};
+use crate::sem_ver::{ExtractedVersion, SemVer};
+use crate::xml_util::{echo, transform_xml, ElementPath, XmlError};
+use xml::reader::{EventReader, XmlEvent};
+use xml::writer::EventWriter;
+
+/// Updates the `pom.xml` at the root directory.
+///
+/// By default, the `project/version` element is updated. Multi-module builds
+/// that keep the version in a property (e.g. `<revision>` referenced as
+/// `${revision}`) can target `project/properties/<property>` instead,
+/// in which case `project/version` and the `${...}` references are left alone.
+/// The version of the parent POM is never changed.
+///
+/// If `scm_tag` is set, the `project/scm/tag` element is set to the new tag
+/// name (e.g. `v1.2.3`) as well.
+#[derive(Default)]
+pub struct PomUpdater {
+    pub property: Option<String>,
+    pub scm_tag: bool,
+}
+
+impl FileFinder for PomUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let path = PathBuf::from(dir).join("pom.xml");
+        if path.is_file() {
+            Ok(vec![path])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+impl ContentProcessor for PomUpdater {
+    type Err = XmlError;
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        let mut targets = vec![(self.version_path(), version.to_string())];
+        if self.scm_tag {
+            targets.push((vec!["project", "scm", "tag"], format!("v{}", version)));
+        }
+        transform_xml(old_contents, |parser, writer| {
+            do_process(parser, writer, &targets)
+        })
+    }
+
+    /// A `-SNAPSHOT` version is read with its snapshot marker.
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        let version_path = self.version_path();
+        let mut element_path = ElementPath::Empty;
+        for result_xml_event in EventReader::new(contents.as_bytes()) {
+            match result_xml_event.ok()? {
+                XmlEvent::StartElement { name, .. } => {
+                    element_path = element_path.push(&name.local_name);
+                }
+                XmlEvent::EndElement { .. } => {
+                    element_path = element_path.pop();
+                }
+                XmlEvent::Characters(value) if element_path.matches(&version_path) => {
+                    return ExtractedVersion::parse_snapshot(value.trim()).ok();
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+impl PomUpdater {
+    /// The path of the element that holds the version.
+    fn version_path(&self) -> Vec<&str> {
+        match &self.property {
+            Some(property) => vec!["project", "properties", property],
+            None => vec!["project", "version"],
+        }
+    }
+}
+
+/// Replaces the text of the elements at the given paths with the given values.
+fn do_process<R: Read, W: Write>(
+    parser: EventReader<R>,
+    writer: &mut EventWriter<W>,
+    targets: &[(Vec<&str>, String)],
+) -> Result<(), XmlError> {
+    let mut element_path = ElementPath::Empty;
+    let mut replacing = false;
+    for result_xml_event in parser {
+        let xml_event = result_xml_event?;
+        match &xml_event {
+            XmlEvent::StartElement { name, .. } => {
+                element_path = element_path.push(&name.local_name);
+                echo(&xml_event, writer)?;
+                if let Some((_, value)) =
+                    targets.iter().find(|(path, _)| element_path.matches(path))
+                {
+                    writer.write(xml::writer::XmlEvent::characters(value))?;
+                    replacing = true;
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                element_path = element_path.pop();
+                replacing = false;
+                echo(&xml_event, writer)?;
+            }
+            XmlEvent::Characters(_) | XmlEvent::CData(_) if replacing => {
+                // the old version is discarded
+            }
+            XmlEvent::Whitespace(_) => {
+                // discarding whitespace because it confuses indentation
+            }
+            _ => {
+                echo(&xml_event, writer)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POM: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <modelVersion>4.0.0</modelVersion>
+  <parent>
+    <groupId>com.example</groupId>
+    <artifactId>parent</artifactId>
+    <version>2.0.0</version>
+  </parent>
+  <artifactId>app</artifactId>
+  <version>${revision}</version>
+  <properties>
+    <revision>1.2.3</revision>
+    <java.version>17</java.version>
+  </properties>
+  <modules>
+    <module>core</module>
+  </modules>
+</project>
+"#;
+
+    #[test]
+    fn test_property() {
+        let updater = PomUpdater {
+            property: Some("revision".to_owned()),
+            scm_tag: false,
+        };
+        let result = updater.process(POM, SemVer::new(1, 3, 0)).unwrap();
+        assert_eq!(
+            result,
+            POM.replace("<revision>1.2.3</revision>", "<revision>1.3.0</revision>")
+        );
+    }
+
+    #[test]
+    fn test_project_version() {
+        let input = POM
+            .replace("${revision}", "1.2.3")
+            .replace("    <revision>1.2.3</revision>\n", "");
+        let result = PomUpdater::default()
+            .process(&input, SemVer::new(1, 3, 0))
+            .unwrap();
+        assert_eq!(
+            result,
+            input.replace("<version>1.2.3</version>", "<version>1.3.0</version>")
+        );
+    }
+
+    #[test]
+    fn test_extract_version() {
+        let snapshot = POM.replace("1.2.3", "1.3.0-SNAPSHOT");
+        let updater = PomUpdater {
+            property: Some("revision".to_owned()),
+            scm_tag: false,
+        };
+        assert_eq!(
+            updater.extract_version(&snapshot),
+            Some(ExtractedVersion {
+                version: SemVer::new(1, 3, 0),
+                snapshot: true,
+            })
+        );
+        assert_eq!(
+            updater.extract_version(POM),
+            Some(SemVer::new(1, 2, 3).into())
+        );
+        // ${revision} is not a version
+        assert_eq!(PomUpdater::default().extract_version(POM), None);
+        assert_eq!(
+            PomUpdater::default()
+                .extract_version(&snapshot.replace("${revision}", "1.3.0-SNAPSHOT")),
+            Some(ExtractedVersion {
+                version: SemVer::new(1, 3, 0),
+                snapshot: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_scm_tag() {
+        let input = POM.replace(
+            "  <modules>",
+            "  <scm>\n    <url>https://github.com/example/app</url>\n    <tag>HEAD</tag>\n  </scm>\n  <modules>",
+        );
+        let updater = PomUpdater {
+            property: Some("revision".to_owned()),
+            scm_tag: true,
+        };
+        let result = updater.process(&input, SemVer::new(1, 3, 0)).unwrap();
+        assert_eq!(
+            result,
+            input
+                .replace("<revision>1.2.3</revision>", "<revision>1.3.0</revision>")
+                .replace("<tag>HEAD</tag>", "<tag>v1.3.0</tag>")
+        );
+
+        // opt-in
+        let updater = PomUpdater {
+            property: Some("revision".to_owned()),
+            scm_tag: false,
+        };
+        let result = updater.process(&input, SemVer::new(1, 3, 0)).unwrap();
+        assert!(result.contains("<tag>HEAD</tag>"));
+    }
+}
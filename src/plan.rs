@@ -0,0 +1,183 @@
+//! The plan of a release, computed before anything is written
+
+use crate::encoding::TextContents;
+use crate::files::UpdateError;
+use crate::json_util::quote_string;
+use crate::updater::{dir_updater, FileSelection, UpdateOptions};
+use crate::SemVer;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// The format of a printed plan.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PlanFormat {
+    /// A human-readable summary.
+    #[default]
+    Text,
+
+    /// A single JSON object, including the new contents of the files.
+    Json,
+}
+
+impl PlanFormat {
+    /// The names of the formats, as accepted by [PlanFormat::from_str].
+    pub const NAMES: [&'static str; 2] = ["text", "json"];
+}
+
+impl FromStr for PlanFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What a release would do: the versions, the tag and the files that would change.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ReleasePlan {
+    pub current_version: SemVer,
+    pub next_version: SemVer,
+    pub tag: String,
+
+    /// The files that would change, with their new contents.
+    pub files: Vec<(PathBuf, TextContents)>,
+}
+
+impl ReleasePlan {
+    /// Computes the plan of releasing `next_version`, without writing anything.
+    pub fn new(
+        dir: &str,
+        selection: &FileSelection,
+        current_version: SemVer,
+        next_version: SemVer,
+        options: &UpdateOptions,
+    ) -> Result<Self, UpdateError> {
+        let files = dir_updater(selection, options).update(dir, next_version)?;
+        Ok(Self {
+            current_version,
+            next_version,
+            tag: format!("v{}", next_version),
+            files,
+        })
+    }
+
+    pub fn format(&self, format: PlanFormat) -> String {
+        match format {
+            PlanFormat::Text => self.to_text(),
+            PlanFormat::Json => self.to_json(),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut result = format!(
+            "Current version: {}\nNext version: {}\nTag: {}\n",
+            self.current_version, self.next_version, self.tag
+        );
+        if self.files.is_empty() {
+            result.push_str("No files would change\n");
+        } else {
+            result.push_str("Files that would change:\n");
+            for (path, _) in &self.files {
+                result.push_str("  ");
+                result.push_str(&path.to_string_lossy());
+                result.push('\n');
+            }
+        }
+        result
+    }
+
+    fn to_json(&self) -> String {
+        let files: Vec<String> = self
+            .files
+            .iter()
+            .map(|(path, new_contents)| {
+                format!(
+                    "{{\"path\":{},\"new_contents\":{}}}",
+                    quote_string(&path.to_string_lossy()),
+                    quote_string(&new_contents.text)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"current_version\":{},\"next_version\":{},\"tag\":{},\"files\":[{}]}}\n",
+            quote_string(&self.current_version.to_string()),
+            quote_string(&self.next_version.to_string()),
+            quote_string(&self.tag),
+            files.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_release_plan() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_toml = dir.path().join("Cargo.toml");
+        fs::write(
+            &cargo_toml,
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("README.md"), "# app\n").unwrap();
+
+        let plan = ReleasePlan::new(
+            dir.path().to_str().unwrap(),
+            &FileSelection::Discover,
+            SemVer::new(0, 1, 0),
+            SemVer::new(0, 2, 0),
+            &UpdateOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            plan,
+            ReleasePlan {
+                current_version: SemVer::new(0, 1, 0),
+                next_version: SemVer::new(0, 2, 0),
+                tag: "v0.2.0".to_owned(),
+                files: vec![(
+                    cargo_toml.clone(),
+                    "[package]\nname = \"app\"\nversion = \"0.2.0\"\n".into()
+                )],
+            }
+        );
+        // nothing is written
+        assert!(fs::read_to_string(&cargo_toml).unwrap().contains("0.1.0"));
+        assert_eq!(
+            plan.format(PlanFormat::Text),
+            format!(
+                "Current version: 0.1.0\nNext version: 0.2.0\nTag: v0.2.0\nFiles that would change:\n  {}\n",
+                cargo_toml.display()
+            )
+        );
+        assert_eq!(
+            plan.format(PlanFormat::Json),
+            format!(
+                "{{\"current_version\":\"0.1.0\",\"next_version\":\"0.2.0\",\"tag\":\"v0.2.0\",\"files\":[{{\"path\":\"{}\",\"new_contents\":\"[package]\\nname = \\\"app\\\"\\nversion = \\\"0.2.0\\\"\\n\"}}]}}\n",
+                cargo_toml.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_release_plan_without_files() {
+        let plan = ReleasePlan {
+            current_version: SemVer::new(1, 0, 0),
+            next_version: SemVer::new(1, 0, 1),
+            tag: "v1.0.1".to_owned(),
+            files: vec![],
+        };
+        assert!(plan
+            .format(PlanFormat::Text)
+            .ends_with("No files would change\n"));
+        assert!(plan.format(PlanFormat::Json).ends_with("\"files\":[]}\n"));
+    }
+}
@@ -0,0 +1,152 @@
+//! Machine-readable progress events, written to stderr as they happen
+//!
+//! With the JSON format, every event is a JSON object on its own line,
+//! so that a wrapper can render live progress:
+//!
+//! ```json
+//! {"event":"file_changed","path":"Cargo.toml"}
+//! ```
+
+use crate::json_util::quote_string;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The format of the progress events.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProgressFormat {
+    /// One JSON object per line.
+    Json,
+}
+
+impl ProgressFormat {
+    /// The names of the formats, as accepted by [ProgressFormat::from_str].
+    pub const NAMES: [&'static str; 1] = ["json"];
+}
+
+impl FromStr for ProgressFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Something that happened during the release.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Event {
+    /// A file that needs to be updated was found.
+    FileDiscovered(PathBuf),
+
+    /// A file was written.
+    FileChanged(PathBuf),
+
+    /// The release commit was created. Holds the commit message.
+    Committed(String),
+
+    /// The release tag was created. Holds the tag name.
+    TagCreated(String),
+
+    /// The branch was pushed. Holds the tag name, if it was pushed too.
+    Pushed(Option<String>),
+}
+
+impl Event {
+    /// Formats the event as a single line JSON object.
+    pub fn to_json(&self) -> String {
+        let (name, key, value) = match self {
+            Self::FileDiscovered(path) => ("file_discovered", "path", json_path(path)),
+            Self::FileChanged(path) => ("file_changed", "path", json_path(path)),
+            Self::Committed(message) => ("committed", "message", quote_string(message)),
+            Self::TagCreated(tag) => ("tag_created", "tag", quote_string(tag)),
+            Self::Pushed(tag) => (
+                "pushed",
+                "tag",
+                tag.as_deref()
+                    .map_or_else(|| "null".to_owned(), quote_string),
+            ),
+        };
+        format!("{{\"event\":\"{}\",\"{}\":{}}}", name, key, value)
+    }
+}
+
+fn json_path(path: &Path) -> String {
+    quote_string(&path.to_string_lossy())
+}
+
+/// Writes the progress events to stderr, if a format was requested.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Progress {
+    format: Option<ProgressFormat>,
+}
+
+impl Progress {
+    pub fn new(format: Option<ProgressFormat>) -> Self {
+        Self { format }
+    }
+
+    pub fn emit(&self, event: Event) {
+        // a closed stderr is not worth failing the release for
+        let _ = self.write_to(&mut std::io::stderr(), &event);
+    }
+
+    fn write_to<W: Write>(&self, out: &mut W, event: &Event) -> std::io::Result<()> {
+        match self.format {
+            Some(ProgressFormat::Json) => writeln!(out, "{}", event.to_json()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            Event::FileChanged(PathBuf::from("Cargo.toml")).to_json(),
+            r#"{"event":"file_changed","path":"Cargo.toml"}"#
+        );
+        assert_eq!(
+            Event::Committed("Releasing \"app\"\n1.2.3".to_owned()).to_json(),
+            r#"{"event":"committed","message":"Releasing \"app\"\n1.2.3"}"#
+        );
+        assert_eq!(
+            Event::Pushed(None).to_json(),
+            r#"{"event":"pushed","tag":null}"#
+        );
+        assert_eq!(
+            Event::Pushed(Some("v1.2.3".to_owned())).to_json(),
+            r#"{"event":"pushed","tag":"v1.2.3"}"#
+        );
+    }
+
+    #[test]
+    fn test_disabled_writes_nothing() {
+        let mut out = Vec::<u8>::new();
+        Progress::default()
+            .write_to(&mut out, &Event::TagCreated("v1.2.3".to_owned()))
+            .unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let progress = Progress::new(Some(ProgressFormat::Json));
+        let mut out = Vec::<u8>::new();
+        for event in &[
+            Event::FileDiscovered(PathBuf::from("Cargo.toml")),
+            Event::TagCreated("v1.2.3".to_owned()),
+        ] {
+            progress.write_to(&mut out, event).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"event\":\"file_discovered\",\"path\":\"Cargo.toml\"}\n{\"event\":\"tag_created\",\"tag\":\"v1.2.3\"}\n"
+        );
+    }
+}
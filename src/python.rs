@@ -0,0 +1,330 @@
+//! Handles Python projects (pyproject.toml)
+
+use crate::files::{ContentProcessor, FileFinder};
+use crate::logger::Logger;
+use crate::sem_ver::ExtractedVersion;
+use crate::toml_util::{
+    get_string_at_path, get_toml_key_value, is_toml_section, is_toml_section_header,
+    set_string_at_path, TomlError,
+};
+use crate::SemVer;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Updates the version of `pyproject.toml`, which is `project.version`
+/// (PEP 621) or, for older Poetry projects, `tool.poetry.version`.
+///
+/// Projects that derive their version from git (e.g. with `setuptools-scm`
+/// or `poetry-dynamic-versioning`) are left alone, because the build would
+/// override the written version anyway.
+pub struct PyProjectUpdater {
+    /// Receives the message about skipped projects.
+    pub logger: Logger,
+}
+
+impl FileFinder for PyProjectUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let path = PathBuf::from(dir).join("pyproject.toml");
+        if path.is_file() {
+            Ok(vec![path])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+impl ContentProcessor for PyProjectUpdater {
+    type Err = TomlError;
+
+    fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
+        if let Some(reason) = find_dynamic_version(old_contents) {
+            self.logger.info(format!(
+                "Skipping pyproject.toml, its version is dynamic ({})",
+                reason
+            ));
+            return Ok(old_contents.to_owned());
+        }
+        let version = new_version.to_string();
+        match set_string_at_path(old_contents, "project.version", &version) {
+            Err(TomlError::PathNotFound(_)) => {
+                match set_string_at_path(old_contents, "tool.poetry.version", &version) {
+                    Err(TomlError::PathNotFound(_)) => Ok(old_contents.to_owned()),
+                    result => result,
+                }
+            }
+            result => result,
+        }
+    }
+
+    fn is_at_version(&self, contents: &str, version: SemVer) -> bool {
+        find_dynamic_version(contents).is_some()
+            || self
+                .process(contents, version)
+                .is_ok_and(|new_contents| new_contents == contents)
+    }
+
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        if find_dynamic_version(contents).is_some() {
+            return None;
+        }
+        get_string_at_path(contents, "project.version")
+            .or_else(|| get_string_at_path(contents, "tool.poetry.version"))
+            .and_then(|version| SemVer::from_str(&version).ok())
+            .map(ExtractedVersion::from)
+    }
+}
+
+/// Updates the version module that Hatch reads the version from,
+/// as configured in `pyproject.toml`:
+///
+/// ```toml
+/// [project]
+/// dynamic = ["version"]
+///
+/// [tool.hatch.version]
+/// path = "src/app/__about__.py"
+/// ```
+///
+/// The module is the single source of the version, e.g. `__version__ = "1.2.3"`.
+/// Like Hatch, a `VERSION = "1.2.3"` assignment is supported too.
+pub struct HatchVersionUpdater {}
+
+impl FileFinder for HatchVersionUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let dir = PathBuf::from(dir);
+        let pyproject = dir.join("pyproject.toml");
+        if !pyproject.is_file() {
+            return Ok(vec![]);
+        }
+        let contents = fs::read_to_string(pyproject)?;
+        match get_string_at_path(&contents, "tool.hatch.version.path") {
+            Some(path) if dir.join(&path).is_file() => Ok(vec![dir.join(path)]),
+            _ => Ok(vec![]),
+        }
+    }
+}
+
+impl ContentProcessor for HatchVersionUpdater {
+    type Err = std::io::Error; // does not really throw error
+
+    fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
+        let mut result = String::with_capacity(old_contents.len());
+        let mut found = false;
+        for line in old_contents.split_inclusive('\n') {
+            match find_version_assignment(line) {
+                Some((start, end)) if !found => {
+                    found = true;
+                    result.push_str(&line[..start]);
+                    result.push_str(&new_version.to_string());
+                    result.push_str(&line[end..]);
+                }
+                _ => result.push_str(line),
+            }
+        }
+        Ok(result)
+    }
+
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        contents
+            .lines()
+            .find_map(|line| {
+                find_version_assignment(line)
+                    .and_then(|(start, end)| SemVer::from_str(&line[start..end]).ok())
+            })
+            .map(ExtractedVersion::from)
+    }
+}
+
+/// Finds the range of the version in a line like `__version__ = "1.2.3"`,
+/// excluding the quotes and a leading `v`.
+fn find_version_assignment(line: &str) -> Option<(usize, usize)> {
+    let rest = line
+        .strip_prefix("__version__")
+        .or_else(|| line.strip_prefix("VERSION"))?;
+    let rest = rest.trim_start_matches(' ').strip_prefix('=')?;
+    let value = rest.trim_start_matches(' ');
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let mut start = line.len() - value.len() + 1;
+    if line[start..].starts_with('v') {
+        start += 1;
+    }
+    let end = start + line[start..].find(quote)?;
+    Some((start, end))
+}
+
+/// Checks if the version is derived by the build, returning the setting that says so.
+fn find_dynamic_version(contents: &str) -> Option<&'static str> {
+    let mut section = "";
+    let mut lines = contents.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if is_toml_section_header(line) {
+            section = if is_toml_section(line, "[project]") {
+                "project"
+            } else if is_toml_section(line, "[tool.poetry-dynamic-versioning]") {
+                "tool.poetry-dynamic-versioning"
+            } else {
+                ""
+            };
+        } else if section == "project" {
+            if let Some(value) = get_toml_key_value(line, "dynamic") {
+                // the array may span multiple lines
+                let mut array = value.to_owned();
+                while !array.contains(']') {
+                    match lines.next() {
+                        Some(next_line) => array.push_str(next_line),
+                        None => break,
+                    }
+                }
+                if array.contains("\"version\"") || array.contains("'version'") {
+                    return Some("dynamic = [\"version\"]");
+                }
+            }
+        } else if section == "tool.poetry-dynamic-versioning"
+            && get_toml_key_value(line, "enable").is_some_and(|value| value.starts_with("true"))
+        {
+            return Some("poetry-dynamic-versioning is enabled");
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn updater() -> PyProjectUpdater {
+        PyProjectUpdater {
+            logger: Logger::default(),
+        }
+    }
+
+    #[test]
+    fn test_project_version() {
+        let input = "[project]\nname = \"app\"\nversion = \"0.1.0\"\n\n[tool.poetry]\nversion = \"0.0.0\"\n";
+        let result = updater().process(input, SemVer::new(0, 2, 0)).unwrap();
+        assert_eq!(result, input.replace("0.1.0", "0.2.0"));
+    }
+
+    #[test]
+    fn test_poetry_version() {
+        let input = "[tool.poetry]\nname = \"app\"\nversion = \"0.1.0\"\n";
+        let result = updater().process(input, SemVer::new(0, 2, 0)).unwrap();
+        assert_eq!(result, input.replace("0.1.0", "0.2.0"));
+    }
+
+    #[test]
+    fn test_extract_version() {
+        assert_eq!(
+            updater().extract_version("[project]\nversion = \"0.1.0\"\n"),
+            Some(SemVer::new(0, 1, 0).into())
+        );
+        assert_eq!(
+            updater().extract_version("[tool.poetry]\nversion = \"1.2.3\"\n"),
+            Some(SemVer::new(1, 2, 3).into())
+        );
+        assert_eq!(
+            updater().extract_version("[project]\ndynamic = [\"version\"]\nversion = \"0.1.0\"\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_dynamic_version_is_skipped() {
+        let input = r#"[project]
+name = "app"
+dynamic = [
+    "readme",
+    "version",
+]
+
+[tool.setuptools_scm]
+"#;
+        assert_eq!(find_dynamic_version(input), Some("dynamic = [\"version\"]"));
+        assert_eq!(
+            updater().process(input, SemVer::new(0, 2, 0)).unwrap(),
+            input
+        );
+        assert!(updater().is_at_version(input, SemVer::new(0, 2, 0)));
+        assert!(
+            find_dynamic_version("[project]\ndynamic = [\"readme\"]\nversion = \"0.1.0\"\n")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_hatch_version_path() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("pyproject.toml"),
+            "[project]\nname = \"app\"\ndynamic = [\"version\"]\n\n[tool.hatch.version]\npath = \"src/app/__about__.py\"\n",
+        )
+        .unwrap();
+        fs::create_dir_all(dir.path().join("src/app")).unwrap();
+        let about = dir.path().join("src/app/__about__.py");
+        fs::write(
+            &about,
+            "# SPDX-License-Identifier: MIT\n__version__ = \"0.1.0\"\n",
+        )
+        .unwrap();
+        let hatch = HatchVersionUpdater {};
+        let files = hatch.find(dir.path().to_str().unwrap()).unwrap();
+        assert_eq!(files, vec![about.clone()]);
+        let old_contents = fs::read_to_string(&about).unwrap();
+        assert_eq!(
+            hatch.extract_version(&old_contents),
+            Some(SemVer::new(0, 1, 0).into())
+        );
+        assert_eq!(
+            hatch.process(&old_contents, SemVer::new(0, 2, 0)).unwrap(),
+            "# SPDX-License-Identifier: MIT\n__version__ = \"0.2.0\"\n"
+        );
+        // the pyproject.toml itself is left alone
+        assert!(updater().is_at_version(
+            &fs::read_to_string(dir.path().join("pyproject.toml")).unwrap(),
+            SemVer::new(0, 2, 0)
+        ));
+    }
+
+    #[test]
+    fn test_hatch_version_assignment() {
+        let hatch = HatchVersionUpdater {};
+        assert_eq!(
+            hatch
+                .process("VERSION = 'v1.0.0'\n", SemVer::new(1, 1, 0))
+                .unwrap(),
+            "VERSION = 'v1.1.0'\n"
+        );
+        assert_eq!(
+            hatch
+                .process("version = \"1.0.0\"\n", SemVer::new(1, 1, 0))
+                .unwrap(),
+            "version = \"1.0.0\"\n"
+        );
+        assert!(HatchVersionUpdater {}
+            .find(tempfile::tempdir().unwrap().path().to_str().unwrap())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_poetry_dynamic_versioning_is_skipped() {
+        let input = r#"[tool.poetry]
+name = "app"
+version = "0.0.0"
+
+[tool.poetry-dynamic-versioning]
+enable = true
+vcs = "git"
+"#;
+        assert_eq!(
+            find_dynamic_version(input),
+            Some("poetry-dynamic-versioning is enabled")
+        );
+        assert_eq!(
+            updater().process(input, SemVer::new(0, 2, 0)).unwrap(),
+            input
+        );
+        assert!(find_dynamic_version(&input.replace("true", "false")).is_none());
+    }
+}
@@ -0,0 +1,250 @@
+//! Handles Ruby gems
+use crate::encoding::TextContents;
+use crate::files::{
+    has_extension, keep_trailing_newline, ContentProcessor, DirUpdater, UpdateError,
+};
+use crate::sem_ver::SemVer;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Updates the version of the gem itself in `Gemfile.lock`.
+/// The gem is listed with four spaces of indentation under the `specs:` block
+/// of the `PATH` section, while its dependencies are indented further and are left alone.
+/// The `specs:` of other sections (e.g. `GEM`) list published gems and are left alone too.
+struct GemfileLockProcessor<'a> {
+    name: &'a str,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum GemfileLockState {
+    Initial,
+    InPath,
+    InPathSpecs,
+}
+
+impl<'a> ContentProcessor for GemfileLockProcessor<'a> {
+    type Err = UpdateError;
+
+    fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
+        let mut result = String::new();
+        let mut state = GemfileLockState::Initial;
+        for line in old_contents.lines() {
+            let mut new_line: Option<String> = None;
+            if !line.is_empty() && !line.starts_with(' ') {
+                // a section header, e.g. PATH or GEM
+                state = if line == "PATH" {
+                    GemfileLockState::InPath
+                } else {
+                    GemfileLockState::Initial
+                };
+            } else {
+                match state {
+                    GemfileLockState::Initial => {}
+                    GemfileLockState::InPath => {
+                        if line == "  specs:" {
+                            state = GemfileLockState::InPathSpecs;
+                        }
+                    }
+                    GemfileLockState::InPathSpecs => {
+                        if get_spec_name(line) == Some(self.name) {
+                            new_line = Some(format!("    {} ({})", self.name, new_version));
+                        }
+                    }
+                }
+            }
+            if let Some(x) = new_line {
+                result.push_str(x.as_str());
+            } else {
+                result.push_str(line);
+            }
+            result.push('\n');
+        }
+        Ok(keep_trailing_newline(old_contents, result))
+    }
+}
+
+/// Gets the gem name of a spec line, e.g. `    rake (13.0.6)`.
+/// Returns `None` for dependency lines, which are indented further.
+fn get_spec_name(line: &str) -> Option<&str> {
+    let spec = line.strip_prefix("    ")?;
+    if spec.starts_with(' ') {
+        None
+    } else {
+        let (name, version) = spec.split_once(' ')?;
+        if version.starts_with('(') && version.ends_with(')') {
+            Some(name)
+        } else {
+            None
+        }
+    }
+}
+
+/// Gets the gem name from a gemspec (e.g. `spec.name = "my_gem"`,
+/// or `s.name = "my_gem".freeze` as generated by RubyGems),
+/// falling back to the file name.
+fn get_gem_name(gemspec_path: &Path, contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter_map(|line| line.split_once('='))
+        .filter(|(left, _)| left.trim_end().ends_with(".name"))
+        .map(|(_, right)| {
+            let right = right.trim();
+            right
+                .strip_suffix(".freeze")
+                .unwrap_or(right)
+                .trim_matches(|c| c == '"' || c == '\'')
+                .to_owned()
+        })
+        .find(|name| !name.is_empty())
+        .or_else(|| {
+            gemspec_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+}
+
+/// Updates `Gemfile.lock` of a gem, found by its gemspec at the root directory.
+#[derive(Default)]
+pub struct RubyDirUpdater {}
+
+impl RubyDirUpdater {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl DirUpdater for RubyDirUpdater {
+    fn update(
+        &self,
+        dir: &str,
+        new_version: SemVer,
+    ) -> Result<Vec<(PathBuf, TextContents)>, UpdateError> {
+        let dir_path_buf = PathBuf::from(dir);
+        let lock_path_buf = dir_path_buf.join("Gemfile.lock");
+        let mut result = Vec::<(PathBuf, TextContents)>::new();
+        if !lock_path_buf.is_file() {
+            return Ok(result);
+        }
+        for res_entry in fs::read_dir(dir)? {
+            let path = res_entry?.path();
+            if path.is_file() && has_extension(&path, "gemspec") {
+                let gemspec_contents = fs::read_to_string(&path)?;
+                if let Some(name) = get_gem_name(&path, &gemspec_contents) {
+                    let processor = GemfileLockProcessor { name: &name };
+                    let old_contents = fs::read_to_string(&lock_path_buf)?;
+                    let new_contents = processor.process(&old_contents, new_version)?;
+                    if old_contents != new_contents {
+                        result.push((lock_path_buf, new_contents.into()));
+                    }
+                    break;
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gemfile_lock_processor() {
+        let input = r#"PATH
+  remote: .
+  specs:
+    my_gem (0.1.0)
+      rake (~> 13.0)
+      my_gem_helper (= 0.1.0)
+
+GEM
+  remote: https://rubygems.org/
+  specs:
+    ast (2.4.2)
+    my_gem_helper (0.1.0)
+    rake (13.0.6)
+    rubocop (1.50.2)
+      ast (~> 2.4.2)
+
+PLATFORMS
+  x86_64-linux
+
+DEPENDENCIES
+  my_gem!
+  rubocop (~> 1.50)
+
+BUNDLED WITH
+   2.4.10
+"#;
+        let expected = r#"PATH
+  remote: .
+  specs:
+    my_gem (1.0.0)
+      rake (~> 13.0)
+      my_gem_helper (= 0.1.0)
+
+GEM
+  remote: https://rubygems.org/
+  specs:
+    ast (2.4.2)
+    my_gem_helper (0.1.0)
+    rake (13.0.6)
+    rubocop (1.50.2)
+      ast (~> 2.4.2)
+
+PLATFORMS
+  x86_64-linux
+
+DEPENDENCIES
+  my_gem!
+  rubocop (~> 1.50)
+
+BUNDLED WITH
+   2.4.10
+"#;
+        let processor = GemfileLockProcessor { name: "my_gem" };
+        let result = processor.process(input, SemVer::new(1, 0, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_gemfile_lock_processor_leaves_published_gem_alone() {
+        // the gem depends on a published gem of the same name (e.g. a fork)
+        let input = r#"PATH
+  remote: .
+  specs:
+    my_gem (0.1.0)
+
+GEM
+  remote: https://rubygems.org/
+  specs:
+    my_gem (0.0.9)
+"#;
+        let expected = input.replacen("my_gem (0.1.0)", "my_gem (1.0.0)", 1);
+        let processor = GemfileLockProcessor { name: "my_gem" };
+        let result = processor.process(input, SemVer::new(1, 0, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_get_gem_name() {
+        let gemspec = r#"Gem::Specification.new do |spec|
+  spec.name = "my_gem"
+  spec.version = MyGem::VERSION
+end
+"#;
+        assert_eq!(
+            get_gem_name(Path::new("other.gemspec"), gemspec),
+            Some("my_gem".to_owned())
+        );
+        assert_eq!(
+            get_gem_name(Path::new("other.gemspec"), "  s.name = \"my_gem\".freeze\n"),
+            Some("my_gem".to_owned())
+        );
+        assert_eq!(
+            get_gem_name(Path::new("other.gemspec"), ""),
+            Some("other".to_owned())
+        );
+    }
+}
@@ -1,9 +1,33 @@
-use crate::files::{ContentProcessor, DirUpdater, UpdateError};
-use crate::sem_ver::SemVer;
+use crate::config;
+use crate::encoding::TextContents;
+use crate::files::{ContentProcessor, DirUpdater, IgnoreRules, UpdateError};
+use crate::sem_ver::{ExtractedVersion, SemVer};
+use crate::toml_util::{
+    self, get_toml_key_value, is_toml_key, is_toml_section, is_toml_section_header, TomlError,
+    TomlSectionProcessor,
+};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Updates the version of the `[package]` section of `Cargo.toml`.
+///
+/// Extra keys (e.g. `package.metadata.docs.version`) can be updated as well.
+/// Unlike the version of `[package]`, they must exist.
+#[derive(Default)]
+pub struct CargoTomlContentProcessor {
+    extra_keys: Vec<String>,
+}
+
+impl CargoTomlContentProcessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-struct CargoTomlContentProcessor {}
+    pub fn with_extra_keys(extra_keys: Vec<String>) -> Self {
+        Self { extra_keys }
+    }
+}
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum CargoTomlState {
@@ -16,34 +40,29 @@ impl ContentProcessor for CargoTomlContentProcessor {
     type Err = UpdateError;
 
     fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
-        let mut result = String::new();
-        let mut state: CargoTomlState = CargoTomlState::Initial;
-        for line in old_contents.lines() {
-            let mut new_line: Option<String> = None;
-            match state {
-                CargoTomlState::Initial => {
-                    if line == "[package]" {
-                        state = CargoTomlState::InPackageSection;
-                    }
-                }
-                CargoTomlState::InPackageSection => {
-                    if line.starts_with('[') {
-                        state = CargoTomlState::Stop;
-                    } else if is_toml_key(line, "version") {
-                        new_line = Some(format!("version = \"{}\"", new_version));
-                    }
-                }
-                CargoTomlState::Stop => {}
+        let mut result = match TomlSectionProcessor::new("package", "version")
+            .process(old_contents, new_version)
+        {
+            Ok(new_contents) => new_contents,
+            // e.g. a workspace member with `version = { workspace = true }`
+            // or `version.workspace = true`
+            Err(TomlError::NotAString(_)) | Err(TomlError::PathNotFound(_)) => {
+                old_contents.to_owned()
             }
-            if let Some(x) = new_line {
-                result.push_str(x.as_str());
-            } else {
-                result.push_str(line);
-            }
-            result.push('\n');
+        };
+        for key in &self.extra_keys {
+            result = toml_util::set_string_at_path(&result, key, &new_version.to_string())
+                .map_err(UpdateError::new_boxing_other)?;
         }
         Ok(result)
     }
+
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        TomlSectionProcessor::new("package", "version")
+            .get_string(contents)
+            .and_then(|version| SemVer::from_str(&version).ok())
+            .map(ExtractedVersion::from)
+    }
 }
 
 fn get_package_name_from_cargo_toml(contents: &str) -> Option<&str> {
@@ -51,12 +70,12 @@ fn get_package_name_from_cargo_toml(contents: &str) -> Option<&str> {
     for line in contents.lines() {
         match state {
             CargoTomlState::Initial => {
-                if line == "[package]" {
+                if is_toml_section(line, "[package]") {
                     state = CargoTomlState::InPackageSection;
                 }
             }
             CargoTomlState::InPackageSection => {
-                if line.starts_with('[') {
+                if is_toml_section_header(line) {
                     state = CargoTomlState::Stop;
                 } else if let Some(x) = get_toml_key_value(line, "name") {
                     return Some(x);
@@ -70,90 +89,198 @@ fn get_package_name_from_cargo_toml(contents: &str) -> Option<&str> {
     None
 }
 
+/// Updates the version of the entry of the crate in `Cargo.lock`.
+///
+/// A lock file may have several entries with the same name (e.g. a local crate
+/// and a crates.io crate that share its name). In that case, the entry without
+/// a `source` is updated, because that is the local package.
 struct CargoLockProcessor<'a> {
     name: &'a str,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-enum CargoLockState {
-    Initial,
-    InPackageSection,
-    InName,
-    Stop,
+/// A `[[package]]` entry of `Cargo.lock`.
+#[derive(Default)]
+struct CargoLockPackage<'a> {
+    name: Option<&'a str>,
+    version_line: Option<usize>,
+    has_source: bool,
+}
+
+fn parse_cargo_lock_packages<'a>(lines: &[&'a str]) -> Vec<CargoLockPackage<'a>> {
+    let mut packages = Vec::<CargoLockPackage>::new();
+    let mut in_package = false;
+    for (index, line) in lines.iter().enumerate() {
+        if is_toml_section(line, "[[package]]") {
+            packages.push(CargoLockPackage::default());
+            in_package = true;
+        } else if is_toml_section_header(line) {
+            in_package = false;
+        } else if let (true, Some(package)) = (in_package, packages.last_mut()) {
+            if let Some(name) = get_toml_key_value(line, "name") {
+                package.name = Some(name);
+            } else if is_toml_key(line, "version") {
+                package.version_line = Some(index);
+            } else if is_toml_key(line, "source") {
+                package.has_source = true;
+            }
+        }
+    }
+    packages
 }
 
 impl<'a> ContentProcessor for CargoLockProcessor<'a> {
     type Err = UpdateError;
 
     fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
+        let lines: Vec<&str> = old_contents.lines().collect();
+        let packages = parse_cargo_lock_packages(&lines);
+        let candidates: Vec<&CargoLockPackage> = packages
+            .iter()
+            .filter(|package| package.name == Some(self.name) && package.version_line.is_some())
+            .collect();
+        let version_line = candidates
+            .iter()
+            .find(|package| !package.has_source)
+            .or_else(|| candidates.first())
+            .and_then(|package| package.version_line);
+        // the lines keep their own line breaks (e.g. `\r\n` of a checkout on Windows)
         let mut result = String::new();
-        let mut state: CargoLockState = CargoLockState::Initial;
-        for line in old_contents.lines() {
-            let mut new_line: Option<String> = None;
-            match state {
-                CargoLockState::Initial => {
-                    if line == "[[package]]" {
-                        state = CargoLockState::InPackageSection;
-                    }
-                }
-                CargoLockState::InPackageSection => {
-                    if get_toml_key_value(line, "name") == Some(self.name) {
-                        state = CargoLockState::InName;
-                    }
-                }
-                CargoLockState::InName => {
-                    if is_toml_key(line, "version") {
-                        new_line = Some(format!("version = \"{}\"", new_version));
-                        state = CargoLockState::Stop;
-                    }
-                }
-                CargoLockState::Stop => {}
-            }
-            if let Some(x) = new_line {
-                result.push_str(x.as_str());
+        for (index, line) in old_contents.split_inclusive('\n').enumerate() {
+            if Some(index) == version_line {
+                let ending = &line[line.trim_end_matches(&['\r', '\n'][..]).len()..];
+                result.push_str(&format!("version = \"{}\"{}", new_version, ending));
             } else {
                 result.push_str(line);
             }
-            result.push('\n');
         }
         Ok(result)
     }
 }
 
-fn is_toml_key(line: &str, key: &str) -> bool {
-    if line.is_empty() || key.is_empty() {
-        false
-    } else if line.starts_with(key) {
-        let (_, second) = line.split_at(key.len());
-        second.trim_start().starts_with('=')
-    } else {
-        false
-    }
+/// Updates `Cargo.toml` at the root directory and the entry of the crate in `Cargo.lock`.
+/// The extra keys of `Cargo.toml` are read from the `[cargo]` table of `.yart.toml`.
+///
+/// The `Cargo.toml` of the members of a workspace (the `members` of its `[workspace]` table,
+/// either paths or `dir/*` patterns) are updated as well, along with their entries
+/// in `Cargo.lock`. Members that inherit the version of the workspace are left alone.
+///
+/// Only the new contents are returned, the caller is responsible for writing them:
+///
+/// ```
+/// use yart::files::DirUpdater;
+/// use yart::rust::CargoDirUpdater;
+/// use yart::SemVer;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let cargo_toml = dir.path().join("Cargo.toml");
+/// std::fs::write(&cargo_toml, "[package]\nname = \"app\"\nversion = \"0.1.0\"\n").unwrap();
+///
+/// let result = CargoDirUpdater::new()
+///     .update(dir.path().to_str().unwrap(), SemVer::new(1, 0, 0))
+///     .unwrap();
+///
+/// assert_eq!(
+///     result,
+///     vec![(
+///         cargo_toml.clone(),
+///         "[package]\nname = \"app\"\nversion = \"1.0.0\"\n".into()
+///     )]
+/// );
+/// // the file itself is not modified
+/// assert!(std::fs::read_to_string(&cargo_toml).unwrap().contains("0.1.0"));
+/// ```
+#[derive(Default)]
+pub struct CargoDirUpdater {
+    /// Leave the crates with `publish = false` unchanged, since they are not released.
+    pub skip_unpublished: bool,
 }
 
-fn get_toml_key_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
-    if line.is_empty() || key.is_empty() {
-        None
-    } else if line.starts_with(key) {
-        let (_, second) = line.split_at(key.len());
-        let second = second.trim_start();
-        if second.starts_with('=') {
-            let (_, second) = second.split_at(1);
-            Some(second.trim_start())
-        } else {
-            None
+impl CargoDirUpdater {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Finds the `Cargo.toml` of the root directory and of the workspace members,
+    /// relative to the root directory, leaving out the ignored ones.
+    fn find_manifests(&self, dir: &str) -> Result<Vec<PathBuf>, UpdateError> {
+        let dir_path_buf = PathBuf::from(dir);
+        let root = PathBuf::from("Cargo.toml");
+        if !dir_path_buf.join(&root).is_file() {
+            return Ok(Vec::new());
         }
-    } else {
-        None
+        let contents = fs::read_to_string(dir_path_buf.join(&root))?;
+        let mut manifests = vec![root];
+        for member in get_workspace_members(&contents) {
+            match member.strip_suffix("/*") {
+                Some(parent) => {
+                    let parent_path_buf = dir_path_buf.join(parent);
+                    if !parent_path_buf.is_dir() {
+                        continue;
+                    }
+                    let mut found = Vec::<PathBuf>::new();
+                    for res_entry in fs::read_dir(parent_path_buf)? {
+                        let name = res_entry?.file_name();
+                        let manifest = Path::new(parent).join(name).join("Cargo.toml");
+                        if dir_path_buf.join(&manifest).is_file() {
+                            found.push(manifest);
+                        }
+                    }
+                    found.sort();
+                    manifests.append(&mut found);
+                }
+                None => {
+                    let manifest = Path::new(&member).join("Cargo.toml");
+                    if dir_path_buf.join(&manifest).is_file() {
+                        manifests.push(manifest);
+                    }
+                }
+            }
+        }
+        let ignore_rules = IgnoreRules::load(dir)?;
+        Ok(manifests
+            .into_iter()
+            .filter(|manifest| !ignore_rules.is_ignored(manifest))
+            .collect())
     }
 }
 
-pub struct CargoDirUpdater {}
+/// Reads the `members` of the `[workspace]` table, which may span multiple lines.
+fn get_workspace_members(contents: &str) -> Vec<String> {
+    let mut in_workspace = false;
+    let mut lines = contents.lines();
+    while let Some(line) = lines.next() {
+        if is_toml_section_header(line) {
+            in_workspace = is_toml_section(line, "[workspace]");
+        } else if let (true, Some(value)) = (in_workspace, get_toml_key_value(line, "members")) {
+            let mut array = value.to_owned();
+            while !array.contains(']') {
+                match lines.next() {
+                    Some(next_line) => array.push_str(next_line),
+                    None => break,
+                }
+            }
+            return array
+                .split(['"', '\''])
+                .skip(1)
+                .step_by(2)
+                .map(str::to_owned)
+                .collect();
+        }
+    }
+    Vec::new()
+}
 
-impl CargoDirUpdater {
-    pub fn new() -> Self {
-        Self {}
+/// Checks if the crate has `publish = false` (or an empty list of registries).
+fn is_unpublished(contents: &str) -> bool {
+    let mut in_package = false;
+    for line in contents.lines() {
+        if is_toml_section_header(line) {
+            in_package = is_toml_section(line, "[package]");
+        } else if let (true, Some(value)) = (in_package, get_toml_key_value(line, "publish")) {
+            return value.starts_with("false") || value.replace(' ', "").starts_with("[]");
+        }
     }
+    false
 }
 
 impl DirUpdater for CargoDirUpdater {
@@ -161,45 +288,98 @@ impl DirUpdater for CargoDirUpdater {
         &self,
         dir: &str,
         new_version: SemVer,
-    ) -> Result<Vec<(PathBuf, String)>, UpdateError> {
+    ) -> Result<Vec<(PathBuf, TextContents)>, UpdateError> {
         let dir_path_buf = PathBuf::from(dir);
-        let cargo_toml_path_buf = dir_path_buf.join("Cargo.toml");
-        let mut result = Vec::<(PathBuf, String)>::new();
-        if cargo_toml_path_buf.is_file() {
-            let processor = CargoTomlContentProcessor {};
+        let mut result = Vec::<(PathBuf, TextContents)>::new();
+        // the crates whose entries in Cargo.lock are updated
+        let mut names = Vec::<String>::new();
+        for manifest in self.find_manifests(dir)? {
+            let cargo_toml_path_buf = dir_path_buf.join(&manifest);
             let old_contents = fs::read_to_string(&cargo_toml_path_buf)?;
+            if self.skip_unpublished && is_unpublished(&old_contents) {
+                continue;
+            }
+            let is_root = manifest == Path::new("Cargo.toml");
+            let processor = if is_root {
+                let config = config::load(dir).map_err(UpdateError::new_boxing_other)?;
+                CargoTomlContentProcessor::with_extra_keys(config.cargo_extra_keys)
+            } else {
+                CargoTomlContentProcessor::new()
+            };
             let new_contents = processor.process(&old_contents, new_version)?;
             if old_contents != new_contents {
-                result.push((cargo_toml_path_buf, new_contents));
+                result.push((cargo_toml_path_buf, new_contents.into()));
             }
-
-            // processing Cargo.lock even if Cargo.toml had no changes,
-            // in case someone accidentally bumped the version only on the toml file
-
-            let cargo_lock_path_buf = dir_path_buf.join("Cargo.lock");
-            if cargo_lock_path_buf.is_file() {
+            // members that inherit the version of the workspace keep their lock entry
+            let has_own_version = TomlSectionProcessor::new("package", "version")
+                .get_string(&old_contents)
+                .is_some();
+            if is_root || has_own_version {
                 if let Some(name) = get_package_name_from_cargo_toml(&old_contents) {
-                    let processor = CargoLockProcessor { name };
-                    let old_contents = fs::read_to_string(&cargo_lock_path_buf)?;
-                    let new_contents = processor.process(&old_contents, new_version)?;
-                    if old_contents != new_contents {
-                        result.push((cargo_lock_path_buf, new_contents));
-                    }
+                    names.push(name.to_owned());
                 }
             }
         }
+
+        // processing Cargo.lock even if Cargo.toml had no changes,
+        // in case someone accidentally bumped the version only on the toml file
+
+        let cargo_lock_path_buf = dir_path_buf.join("Cargo.lock");
+        if !names.is_empty()
+            && cargo_lock_path_buf.is_file()
+            && !IgnoreRules::load(dir)?.is_ignored(Path::new("Cargo.lock"))
+        {
+            let old_contents = fs::read_to_string(&cargo_lock_path_buf)?;
+            let mut new_contents = old_contents.clone();
+            for name in &names {
+                new_contents = CargoLockProcessor { name }.process(&new_contents, new_version)?;
+            }
+            if old_contents != new_contents {
+                result.push((cargo_lock_path_buf, new_contents.into()));
+            }
+        }
+        Ok(result)
+    }
+
+    fn find_files(&self, dir: &str) -> Result<Vec<PathBuf>, UpdateError> {
+        let mut result = Vec::<PathBuf>::new();
+        for manifest in self.find_manifests(dir)? {
+            let cargo_toml_path_buf = PathBuf::from(dir).join(manifest);
+            let contents = fs::read_to_string(&cargo_toml_path_buf)?;
+            if !(self.skip_unpublished && is_unpublished(&contents)) {
+                result.push(cargo_toml_path_buf);
+            }
+        }
+        Ok(result)
+    }
+
+    fn extract_versions(&self, dir: &str) -> Result<Vec<(PathBuf, ExtractedVersion)>, UpdateError> {
+        let mut result = Vec::<(PathBuf, ExtractedVersion)>::new();
+        for manifest in self.find_manifests(dir)? {
+            let cargo_toml_path_buf = PathBuf::from(dir).join(manifest);
+            let contents = fs::read_to_string(&cargo_toml_path_buf)?;
+            if self.skip_unpublished && is_unpublished(&contents) {
+                continue;
+            }
+            if let Some(version) = CargoTomlContentProcessor::new().extract_version(&contents) {
+                result.push((cargo_toml_path_buf, version));
+            }
+        }
         Ok(result)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::files::ContentProcessor;
+    use crate::files::{ContentProcessor, DirUpdater, ExplicitFileFinder, FileUpdater};
     use crate::rust::{
-        get_package_name_from_cargo_toml, is_toml_key, CargoLockProcessor,
+        get_package_name_from_cargo_toml, is_unpublished, CargoDirUpdater, CargoLockProcessor,
         CargoTomlContentProcessor,
     };
+    use crate::toml_util::{is_toml_key, is_toml_section};
     use crate::SemVer;
+    use std::fs;
+    use std::path::PathBuf;
 
     #[test]
     fn test_cargo_toml_content_processor() {
@@ -233,7 +413,7 @@ xml-rs = "~0.8"
 version = "~2.27.0"
 default-features = false
 "#;
-        let processor = CargoTomlContentProcessor {};
+        let processor = CargoTomlContentProcessor::new();
         let result = processor.process(toml, SemVer::new(1, 0, 0)).unwrap();
         assert_eq!(result, expected);
     }
@@ -362,11 +542,331 @@ dependencies = [
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_cargo_toml_content_processor_commented_section_header() {
+        let toml = r#"[package]   # main
+name = "yart"
+version = "0.1.0"
+
+  [dependencies] # deps
+version_check = "0.9"
+"#;
+        let expected = r#"[package]   # main
+name = "yart"
+version = "1.0.0"
+
+  [dependencies] # deps
+version_check = "0.9"
+"#;
+        let processor = CargoTomlContentProcessor::new();
+        let result = processor.process(toml, SemVer::new(1, 0, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_cargo_lock_processor_duplicate_names() {
+        let input = r#"[[package]]
+name = "log"
+version = "0.4.20"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "b5e6163cb8c49088c2c36f57875e58ccd8c87c7427f7fbd50ea6710b2f3f2e8f"
+
+[[package]]
+name = "log"
+version = "0.1.0"
+dependencies = [
+ "log 0.4.20",
+]
+"#;
+        let processor = CargoLockProcessor { name: "\"log\"" };
+        let result = processor.process(input, SemVer::new(0, 2, 0)).unwrap();
+        assert_eq!(
+            result,
+            input.replace("version = \"0.1.0\"", "version = \"0.2.0\"")
+        );
+        // a single entry is updated even if it has a source
+        let single = input.split("\n\n").next().unwrap().to_owned() + "\n";
+        assert_eq!(
+            processor.process(&single, SemVer::new(0, 5, 0)).unwrap(),
+            single.replace("0.4.20", "0.5.0")
+        );
+    }
+
+    #[test]
+    fn test_cargo_lock_processor_commented_section_header() {
+        let input = r#"[[package]] # local
+name = "yart"
+version = "0.1.0"
+"#;
+        let expected = r#"[[package]] # local
+name = "yart"
+version = "1.0.0"
+"#;
+        let processor = CargoLockProcessor { name: "\"yart\"" };
+        let result = processor.process(input, SemVer::new(1, 0, 0)).unwrap();
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_cargo_lock_processor_trailing_newline() {
+        let processor = CargoLockProcessor { name: "\"yart\"" };
+        let input = "[[package]]\nname = \"yart\"\nversion = \"0.1.0\"";
+        let result = processor.process(input, SemVer::new(1, 0, 0)).unwrap();
+        assert_eq!(result, "[[package]]\nname = \"yart\"\nversion = \"1.0.0\"");
+
+        let input = "[[package]]\r\nname = \"yart\"\r\nversion = \"0.1.0\"\r\n";
+        let result = processor.process(input, SemVer::new(1, 0, 0)).unwrap();
+        assert_eq!(
+            result,
+            "[[package]]\r\nname = \"yart\"\r\nversion = \"1.0.0\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_is_toml_section() {
+        assert!(is_toml_section("[package]", "[package]"));
+        assert!(is_toml_section("  [package]  ", "[package]"));
+        assert!(is_toml_section("[package] # main", "[package]"));
+        assert!(!is_toml_section("[package.metadata]", "[package]"));
+        assert!(!is_toml_section("# [package]", "[package]"));
+    }
+
     #[test]
     fn test_is_toml_key() {
         assert!(is_toml_key("version = 1", "version"));
         assert!(is_toml_key("version=1", "version"));
         assert!(!is_toml_key("version", "version"));
         assert!(!is_toml_key("version = 1", "name"));
+        assert!(!is_toml_key("version.workspace = true", "version"));
+        assert!(is_toml_key("version={workspace=true}", "version"));
+        assert!(!is_toml_key("versions = 1", "version"));
+    }
+
+    #[test]
+    fn test_cargo_toml_extract_version() {
+        let processor = CargoTomlContentProcessor::new();
+        assert_eq!(
+            processor.extract_version(
+                "[package]\nname = \"app\"\nversion = \"1.2.3\"\n\n[dependencies]\nversion = \"0.1.0\"\n"
+            ),
+            Some(SemVer::new(1, 2, 3).into())
+        );
+        assert_eq!(
+            processor.extract_version("[package]\nname = \"app\"\nversion.workspace = true\n"),
+            None
+        );
+        assert_eq!(
+            processor.extract_version("[package]\nversion = \"1.2\"\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cargo_toml_content_processor_workspace_member() {
+        let toml = r#"[package]
+name = "member"
+version.workspace = true
+edition.workspace = true
+
+[dependencies]
+serde = { workspace = true }
+"#;
+        let processor = CargoTomlContentProcessor::new();
+        let result = processor.process(toml, SemVer::new(1, 0, 0)).unwrap();
+        assert_eq!(result, toml);
+    }
+
+    #[test]
+    fn test_cargo_toml_content_processor_workspace_member_inline_table() {
+        let toml = r#"[package]
+name = "member"
+version = { workspace = true }
+"#;
+        let processor = CargoTomlContentProcessor::new();
+        let result = processor.process(toml, SemVer::new(1, 0, 0)).unwrap();
+        assert_eq!(result, toml);
+    }
+
+    #[test]
+    fn test_cargo_toml_content_processor_without_spaces() {
+        let processor = CargoTomlContentProcessor::new();
+        let toml = "[package]\nname=\"member\"\nversion={workspace=true}\n";
+        let result = processor.process(toml, SemVer::new(1, 0, 0)).unwrap();
+        assert_eq!(result, toml);
+
+        let toml = "[package]\nname=\"app\"\nversion=\"0.1.0\"\n";
+        let result = processor.process(toml, SemVer::new(1, 0, 0)).unwrap();
+        assert_eq!(result, "[package]\nname=\"app\"\nversion=\"1.0.0\"\n");
+    }
+
+    #[test]
+    fn test_cargo_toml_explicit_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let crate_dir = dir.path().join("crates").join("core");
+        fs::create_dir_all(&crate_dir).unwrap();
+        fs::write(
+            crate_dir.join("Cargo.toml"),
+            "[package]\nname = \"core\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+
+        let updater = FileUpdater {
+            finder: ExplicitFileFinder::new(vec![PathBuf::from("crates/core/Cargo.toml")]),
+            processor: CargoTomlContentProcessor::new(),
+        };
+        let result = updater
+            .update(dir.path().to_str().unwrap(), SemVer::new(0, 2, 0))
+            .unwrap();
+
+        assert_eq!(
+            result,
+            vec![(
+                crate_dir.join("Cargo.toml"),
+                "[package]\nname = \"core\"\nversion = \"0.2.0\"\n".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_cargo_dir_updater_workspace_members() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\n    \"cli\",\n    \"crates/*\",\n]\n",
+        )
+        .unwrap();
+        for (member, contents) in &[
+            ("cli", "[package]\nname = \"cli\"\nversion = \"0.1.0\"\n"),
+            (
+                "crates/core",
+                "[package]\nname = \"core\"\nversion = \"0.1.0\"\n",
+            ),
+            (
+                "crates/internal",
+                "[package]\nname = \"internal\"\nversion = \"0.1.0\"\npublish = false\n",
+            ),
+        ] {
+            fs::create_dir_all(dir.path().join(member)).unwrap();
+            fs::write(dir.path().join(member).join("Cargo.toml"), contents).unwrap();
+        }
+        fs::write(
+            dir.path().join("Cargo.lock"),
+            "[[package]]\nname = \"cli\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"core\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"internal\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+
+        let result = CargoDirUpdater::new()
+            .update(dir_str, SemVer::new(0, 2, 0))
+            .unwrap();
+        assert_eq!(
+            result
+                .iter()
+                .map(|(path, _)| path.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                dir.path().join("cli/Cargo.toml"),
+                dir.path().join("crates/core/Cargo.toml"),
+                dir.path().join("crates/internal/Cargo.toml"),
+                dir.path().join("Cargo.lock"),
+            ]
+        );
+        assert!(!result[3].1.text.contains("0.1.0"));
+
+        let result = CargoDirUpdater {
+            skip_unpublished: true,
+        }
+        .update(dir_str, SemVer::new(0, 2, 0))
+        .unwrap();
+        assert_eq!(
+            result
+                .iter()
+                .map(|(path, _)| path.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                dir.path().join("cli/Cargo.toml"),
+                dir.path().join("crates/core/Cargo.toml"),
+                dir.path().join("Cargo.lock"),
+            ]
+        );
+        assert_eq!(
+            result[2].1.text,
+            "[[package]]\nname = \"cli\"\nversion = \"0.2.0\"\n\n[[package]]\nname = \"core\"\nversion = \"0.2.0\"\n\n[[package]]\nname = \"internal\"\nversion = \"0.1.0\"\n"
+        );
+    }
+
+    #[test]
+    fn test_is_unpublished() {
+        assert!(is_unpublished(
+            "[package]\nname = \"app\"\npublish = false\n"
+        ));
+        assert!(is_unpublished("[package]\npublish = [ ]\n"));
+        assert!(!is_unpublished("[package]\npublish = [\"internal\"]\n"));
+        assert!(!is_unpublished(
+            "[package]\nname = \"app\"\n\n[other]\npublish = false\n"
+        ));
+    }
+
+    #[test]
+    fn test_cargo_dir_updater_ignored_cargo_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join(".yartignore"), "/Cargo.toml\n").unwrap();
+
+        let result = CargoDirUpdater::new()
+            .update(dir.path().to_str().unwrap(), SemVer::new(0, 2, 0))
+            .unwrap();
+
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_cargo_toml_extra_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            r#"[package]
+name = "app"
+version = "0.1.0"
+
+[package.metadata.docs]
+version = "0.1.0" # shown in the docs
+
+[dependencies]
+serde = { version = "1.0" }
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(".yart.toml"),
+            "[cargo]\nextra_keys = [\"package.metadata.docs.version\"]\n",
+        )
+        .unwrap();
+
+        let result = CargoDirUpdater::new()
+            .update(dir.path().to_str().unwrap(), SemVer::new(0, 2, 0))
+            .unwrap();
+
+        assert_eq!(
+            result,
+            vec![(
+                dir.path().join("Cargo.toml"),
+                r#"[package]
+name = "app"
+version = "0.2.0"
+
+[package.metadata.docs]
+version = "0.2.0" # shown in the docs
+
+[dependencies]
+serde = { version = "1.0" }
+"#
+                .into()
+            )]
+        );
     }
 }
@@ -0,0 +1,159 @@
+//! Handles Scala sbt builds (build.sbt and version.sbt)
+use crate::files::{ContentProcessor, FileFinder};
+use crate::sem_ver::{ExtractedVersion, SNAPSHOT_SUFFIX};
+use crate::SemVer;
+use std::path::PathBuf;
+
+/// Updates the version settings of sbt builds.
+///
+/// Supports `version := "..."`, `version in ThisBuild := "..."` and
+/// `ThisBuild / version := "..."`, including settings of sub-projects
+/// (e.g. `.settings(version := "...")`).
+///
+/// A `-SNAPSHOT` suffix is dropped, unless `snapshot` is set,
+/// in which case it is added (e.g. for the next development version).
+pub struct SbtUpdater {
+    pub snapshot: bool,
+}
+
+impl FileFinder for SbtUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let dir_path_buf = PathBuf::from(dir);
+        Ok(["build.sbt", "version.sbt"]
+            .iter()
+            .map(|file_name| dir_path_buf.join(file_name))
+            .filter(|path| path.is_file())
+            .collect())
+    }
+}
+
+impl ContentProcessor for SbtUpdater {
+    type Err = std::io::Error; // does not really throw error
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        let new_value = if self.snapshot {
+            format!("{}{}", version, SNAPSHOT_SUFFIX)
+        } else {
+            version.to_string()
+        };
+        let mut result = String::new();
+        for line in old_contents.split_inclusive('\n') {
+            match find_version_value(line) {
+                Some((start, end)) => {
+                    result.push_str(&line[..start]);
+                    result.push_str(&new_value);
+                    result.push_str(&line[end..]);
+                }
+                None => result.push_str(line),
+            }
+        }
+        Ok(result)
+    }
+
+    /// A `-SNAPSHOT` version is read with its snapshot marker.
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        contents.lines().find_map(|line| {
+            let (start, end) = find_version_value(line)?;
+            ExtractedVersion::parse_snapshot(&line[start..end]).ok()
+        })
+    }
+}
+
+/// Finds the byte span of the quoted value of a version setting.
+fn find_version_value(line: &str) -> Option<(usize, usize)> {
+    let mut search_from = 0;
+    while let Some(idx) = line[search_from..].find("version") {
+        let pos = search_from + idx;
+        search_from = pos + "version".len();
+        let is_token_start = !matches!(
+            line[..pos].chars().next_back(),
+            Some(ch) if ch.is_alphanumeric() || ch == '_'
+        );
+        if !is_token_start {
+            continue;
+        }
+        let rest = line[search_from..].trim_start();
+        let rest = match rest.strip_prefix("in ThisBuild") {
+            Some(after_scope) => after_scope.trim_start(),
+            None => rest,
+        };
+        if let Some(value) = rest.strip_prefix(":=") {
+            let value = value.trim_start().strip_prefix('"')?;
+            let start = line.len() - value.len();
+            let end = start + value.find('"')?;
+            return Some((start, end));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_sbt_snapshot_round_trip() {
+        let input = "version in ThisBuild := \"1.2.3-SNAPSHOT\"\n";
+
+        // release drops the suffix
+        let release = SbtUpdater { snapshot: false }
+            .process(input, SemVer::new(1, 2, 3))
+            .unwrap();
+        assert_eq!(release, "version in ThisBuild := \"1.2.3\"\n");
+
+        // next development version adds it again
+        let next_dev = SbtUpdater { snapshot: true }
+            .process(&release, SemVer::new(1, 2, 4))
+            .unwrap();
+        assert_eq!(next_dev, "version in ThisBuild := \"1.2.4-SNAPSHOT\"\n");
+    }
+
+    #[test]
+    fn test_extract_version() {
+        let updater = SbtUpdater { snapshot: false };
+        assert_eq!(
+            updater.extract_version("ThisBuild / version := \"1.2.3-SNAPSHOT\"\n"),
+            Some(ExtractedVersion {
+                version: SemVer::new(1, 2, 3),
+                snapshot: true,
+            })
+        );
+        assert_eq!(
+            updater.extract_version("name := \"core\"\nversion := \"0.1.0\"\n"),
+            Some(SemVer::new(0, 1, 0).into())
+        );
+        assert_eq!(updater.extract_version("name := \"core\"\n"), None);
+    }
+
+    #[test]
+    fn test_build_sbt_multi_module() {
+        let input = r#"ThisBuild / version := "0.1.0-SNAPSHOT"
+ThisBuild / scalaVersion := "2.13.10"
+
+lazy val core = (project in file("core"))
+  .settings(name := "core", version := "0.1.0-SNAPSHOT")
+
+lazy val api = (project in file("api"))
+  .settings(
+    name := "api",
+    libraryDependencies += "org.typelevel" %% "cats-core" % "2.9.0"
+  )
+"#;
+        let expected = r#"ThisBuild / version := "0.1.0"
+ThisBuild / scalaVersion := "2.13.10"
+
+lazy val core = (project in file("core"))
+  .settings(name := "core", version := "0.1.0")
+
+lazy val api = (project in file("api"))
+  .settings(
+    name := "api",
+    libraryDependencies += "org.typelevel" %% "cats-core" % "2.9.0"
+  )
+"#;
+        let result = SbtUpdater { snapshot: false }
+            .process(input, SemVer::new(0, 1, 0))
+            .unwrap();
+        assert_eq!(result, expected);
+    }
+}
@@ -3,6 +3,10 @@ use std::fmt::{Display, Formatter};
 use std::num::ParseIntError;
 use std::str::FromStr;
 
+/// The suffix of the development versions of JVM builds (Maven, Gradle, sbt),
+/// which is their pre-release convention instead of a SemVer pre-release.
+pub const SNAPSHOT_SUFFIX: &str = "-SNAPSHOT";
+
 /// Defines the possible components of a semantic version.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum SemVerComponent {
@@ -26,6 +30,7 @@ impl FromStr for SemVerComponent {
 
 /// A set of semantic version components
 /// implemented by bit flags.
+#[derive(Default)]
 pub struct SemVerComponentSet(u8);
 
 impl SemVerComponentSet {
@@ -50,6 +55,11 @@ impl SemVerComponentSet {
         Self::new().missing()
     }
 
+    /// Adds the given component to the set.
+    pub fn insert(&mut self, component: SemVerComponent) {
+        self.0 |= Self::component_to_flag(component);
+    }
+
     /// Maps the sem ver component to a bit flag.
     fn component_to_flag(component: SemVerComponent) -> u8 {
         match component {
@@ -62,7 +72,7 @@ impl SemVerComponentSet {
 
 impl std::ops::AddAssign<SemVerComponent> for SemVerComponentSet {
     fn add_assign(&mut self, rhs: SemVerComponent) {
-        self.0 |= Self::component_to_flag(rhs);
+        self.insert(rhs);
     }
 }
 
@@ -115,6 +125,25 @@ impl SemVer {
         }
     }
 
+    /// Bumps the given component, or returns `None` if it is already `u16::MAX`.
+    pub fn checked_bump(&self, step: SemVerComponent) -> Option<Self> {
+        match step {
+            SemVerComponent::Major => Some(Self::new(self.major.checked_add(1)?, 0, 0)),
+            SemVerComponent::Minor => Some(Self::new(self.major, self.minor.checked_add(1)?, 0)),
+            SemVerComponent::Patch => Some(Self::new(
+                self.major,
+                self.minor,
+                self.patch.checked_add(1)?,
+            )),
+        }
+    }
+
+    /// Bumps the given component, or keeps the version as-is if the component
+    /// is already `u16::MAX`. The version never decreases.
+    pub fn saturating_bump(&self, step: SemVerComponent) -> Self {
+        self.checked_bump(step).unwrap_or(*self)
+    }
+
     pub fn get_component(&self, component: SemVerComponent) -> u16 {
         match component {
             SemVerComponent::Major => self.major,
@@ -122,6 +151,44 @@ impl SemVer {
             SemVerComponent::Patch => self.patch,
         }
     }
+
+    /// Checks if the two versions are compatible according to Cargo's caret semantics:
+    ///
+    /// - from `1.0.0` on, versions with the same major are compatible (`1.2.3` and `1.5.0`)
+    /// - for `0.x` with `x > 0`, versions with the same minor are compatible (`0.2.1` and `0.2.5`)
+    /// - for `0.0.x`, only the exact same version is compatible
+    pub fn is_compatible_with(&self, other: &Self) -> bool {
+        if self.major != other.major {
+            false
+        } else if self.major > 0 {
+            true
+        } else if self.minor != other.minor {
+            false
+        } else {
+            self.minor > 0 || self.patch == other.patch
+        }
+    }
+
+    /// Parses a version, recovering common mistakes of real-world tags:
+    ///
+    /// - a missing patch component (`1.2` is parsed as `1.2.0`)
+    /// - an extra fourth component (`1.2.3.4` is parsed as `1.2.3`,
+    ///   with `4` returned as the build)
+    /// - trailing separators (`1.2.3-` is parsed as `1.2.3`)
+    pub fn parse_lenient(s: &str) -> Result<(Self, Option<u16>), SemVerParseError> {
+        let trimmed = s.trim_end_matches(['.', '-', '_', '+']);
+        let parts: Vec<u16> = trimmed
+            .split('.')
+            .map(u16::from_str)
+            .collect::<Result<Vec<u16>, ParseIntError>>()
+            .map_err(SemVerParseError::ParseIntError)?;
+        match parts.len() {
+            2 => Ok((Self::new(parts[0], parts[1], 0), None)),
+            3 => Ok((Self::new(parts[0], parts[1], parts[2]), None)),
+            4 => Ok((Self::new(parts[0], parts[1], parts[2]), Some(parts[3]))),
+            count => Err(SemVerParseError::IllegalLenientComponentCount(count)),
+        }
+    }
 }
 
 impl Display for SemVer {
@@ -130,6 +197,50 @@ impl Display for SemVer {
     }
 }
 
+/// A version as read from a file.
+///
+/// The development versions of JVM builds end with [SNAPSHOT_SUFFIX], which is
+/// kept as a pre-release marker: `1.3.0-SNAPSHOT` leads to `1.3.0`,
+/// but it is not the same version as the release `1.3.0`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ExtractedVersion {
+    pub version: SemVer,
+    pub snapshot: bool,
+}
+
+impl ExtractedVersion {
+    /// Parses a version of JVM builds, which may end with [SNAPSHOT_SUFFIX].
+    pub fn parse_snapshot(s: &str) -> Result<Self, SemVerParseError> {
+        match s.strip_suffix(SNAPSHOT_SUFFIX) {
+            Some(version) => Ok(Self {
+                version: SemVer::from_str(version)?,
+                snapshot: true,
+            }),
+            None => Ok(SemVer::from_str(s)?.into()),
+        }
+    }
+}
+
+/// A release version, without a snapshot marker.
+impl From<SemVer> for ExtractedVersion {
+    fn from(version: SemVer) -> Self {
+        Self {
+            version,
+            snapshot: false,
+        }
+    }
+}
+
+impl Display for ExtractedVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.snapshot {
+            write!(f, "{}{}", self.version, SNAPSHOT_SUFFIX)
+        } else {
+            Display::fmt(&self.version, f)
+        }
+    }
+}
+
 impl PartialOrd for SemVer {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -160,8 +271,27 @@ impl Ord for SemVer {
 pub enum SemVerParseError {
     ParseIntError(ParseIntError),
     IllegalComponentCount(usize),
+
+    /// Lenient parsing (see [SemVer::parse_lenient]) accepts 2 to 4 components.
+    IllegalLenientComponentCount(usize),
 }
 
+impl Display for SemVerParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ParseIntError(e) => std::fmt::Display::fmt(&e, f),
+            Self::IllegalComponentCount(count) => {
+                write!(f, "expected 3 version components, found {}", count)
+            }
+            Self::IllegalLenientComponentCount(count) => {
+                write!(f, "expected 2 to 4 version components, found {}", count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SemVerParseError {}
+
 impl FromStr for SemVer {
     type Err = SemVerParseError;
 
@@ -181,6 +311,23 @@ impl FromStr for SemVer {
     }
 }
 
+/// Serializes as a string, e.g. `"1.2.3"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for SemVer {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes from a string, e.g. `"1.2.3"`.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SemVer {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -225,6 +372,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sem_ver_checked_bump() {
+        let max = u16::MAX;
+        assert_eq!(
+            SemVer::new(1, 2, max - 1).checked_bump(SemVerComponent::Patch),
+            Some(SemVer::new(1, 2, max))
+        );
+        assert_eq!(
+            SemVer::new(1, 2, max).checked_bump(SemVerComponent::Patch),
+            None
+        );
+        assert_eq!(
+            SemVer::new(1, max, 3).checked_bump(SemVerComponent::Minor),
+            None
+        );
+        assert_eq!(
+            SemVer::new(1, max, 3).checked_bump(SemVerComponent::Major),
+            Some(SemVer::new(2, 0, 0))
+        );
+        assert_eq!(
+            SemVer::new(max, 2, 3).checked_bump(SemVerComponent::Major),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sem_ver_saturating_bump() {
+        let max = u16::MAX;
+        assert_eq!(
+            SemVer::new(1, 2, max - 1).saturating_bump(SemVerComponent::Patch),
+            SemVer::new(1, 2, max)
+        );
+        assert_eq!(
+            SemVer::new(1, 2, max).saturating_bump(SemVerComponent::Patch),
+            SemVer::new(1, 2, max)
+        );
+        assert_eq!(
+            SemVer::new(1, max, 3).saturating_bump(SemVerComponent::Minor),
+            SemVer::new(1, max, 3)
+        );
+        assert_eq!(
+            SemVer::new(max, 2, 3).saturating_bump(SemVerComponent::Major),
+            SemVer::new(max, 2, 3)
+        );
+    }
+
     #[test]
     fn test_sem_ver_display() {
         assert_eq!("1.2.3", SemVer::new(1, 2, 3).to_string());
@@ -256,4 +449,140 @@ mod tests {
             Err(SemVerParseError::IllegalComponentCount(2))
         ));
     }
+
+    #[test]
+    fn test_is_compatible_with_same_major() {
+        let version = SemVer::new(1, 2, 3);
+        assert!(version.is_compatible_with(&SemVer::new(1, 2, 3)));
+        assert!(version.is_compatible_with(&SemVer::new(1, 2, 4)));
+        assert!(version.is_compatible_with(&SemVer::new(1, 5, 0)));
+        assert!(version.is_compatible_with(&SemVer::new(1, 0, 0)));
+        assert!(!version.is_compatible_with(&SemVer::new(2, 0, 0)));
+        assert!(!version.is_compatible_with(&SemVer::new(0, 2, 3)));
+    }
+
+    #[test]
+    fn test_is_compatible_with_zero_major() {
+        let version = SemVer::new(0, 2, 1);
+        assert!(version.is_compatible_with(&SemVer::new(0, 2, 1)));
+        assert!(version.is_compatible_with(&SemVer::new(0, 2, 5)));
+        assert!(version.is_compatible_with(&SemVer::new(0, 2, 0)));
+        assert!(!version.is_compatible_with(&SemVer::new(0, 3, 0)));
+        assert!(!version.is_compatible_with(&SemVer::new(0, 1, 9)));
+        assert!(!version.is_compatible_with(&SemVer::new(1, 0, 0)));
+    }
+
+    #[test]
+    fn test_is_compatible_with_zero_major_and_minor() {
+        let version = SemVer::new(0, 0, 3);
+        assert!(version.is_compatible_with(&SemVer::new(0, 0, 3)));
+        assert!(!version.is_compatible_with(&SemVer::new(0, 0, 4)));
+        assert!(!version.is_compatible_with(&SemVer::new(0, 1, 3)));
+        assert!(!SemVer::new(0, 1, 0).is_compatible_with(&SemVer::new(0, 0, 0)));
+    }
+
+    #[test]
+    fn test_is_compatible_with_bump() {
+        let version = SemVer::new(0, 4, 2);
+        assert!(version.is_compatible_with(&version.bump(SemVerComponent::Patch)));
+        assert!(!version.is_compatible_with(&version.bump(SemVerComponent::Minor)));
+        let version = SemVer::new(3, 4, 2);
+        assert!(version.is_compatible_with(&version.bump(SemVerComponent::Minor)));
+        assert!(!version.is_compatible_with(&version.bump(SemVerComponent::Major)));
+    }
+
+    #[test]
+    fn test_sem_ver_parse_lenient() {
+        assert_eq!(
+            SemVer::parse_lenient("1.2.3").unwrap(),
+            (SemVer::new(1, 2, 3), None)
+        );
+    }
+
+    #[test]
+    fn test_sem_ver_parse_lenient_missing_patch() {
+        assert_eq!(
+            SemVer::parse_lenient("1.2").unwrap(),
+            (SemVer::new(1, 2, 0), None)
+        );
+    }
+
+    #[test]
+    fn test_sem_ver_parse_lenient_extra_component() {
+        assert_eq!(
+            SemVer::parse_lenient("1.2.3.4").unwrap(),
+            (SemVer::new(1, 2, 3), Some(4))
+        );
+    }
+
+    #[test]
+    fn test_sem_ver_parse_lenient_trailing_separator() {
+        assert_eq!(
+            SemVer::parse_lenient("1.2.3-").unwrap(),
+            (SemVer::new(1, 2, 3), None)
+        );
+        assert_eq!(
+            SemVer::parse_lenient("1.2.").unwrap(),
+            (SemVer::new(1, 2, 0), None)
+        );
+    }
+
+    #[test]
+    fn test_sem_ver_parse_lenient_invalid() {
+        assert!(matches!(
+            SemVer::parse_lenient("1"),
+            Err(SemVerParseError::IllegalLenientComponentCount(1))
+        ));
+        assert!(matches!(
+            SemVer::parse_lenient("1.2.3.4.5"),
+            Err(SemVerParseError::IllegalLenientComponentCount(5))
+        ));
+        assert_eq!(
+            SemVer::parse_lenient("1.2.3.4.5").unwrap_err().to_string(),
+            "expected 2 to 4 version components, found 5"
+        );
+        assert!(matches!(
+            SemVer::parse_lenient("1.2.3-beta"),
+            Err(SemVerParseError::ParseIntError(_))
+        ));
+    }
+
+    #[test]
+    fn test_extracted_version_parse_snapshot() {
+        let snapshot = ExtractedVersion::parse_snapshot("1.2.3-SNAPSHOT").unwrap();
+        assert_eq!(
+            snapshot,
+            ExtractedVersion {
+                version: SemVer::new(1, 2, 3),
+                snapshot: true
+            }
+        );
+        assert_eq!(snapshot.to_string(), "1.2.3-SNAPSHOT");
+        let release = ExtractedVersion::parse_snapshot("1.2.3").unwrap();
+        assert_eq!(release, SemVer::new(1, 2, 3).into());
+        assert_eq!(release.to_string(), "1.2.3");
+        assert_ne!(snapshot, release);
+        assert!(ExtractedVersion::parse_snapshot("1.2-SNAPSHOT").is_err());
+        assert!(ExtractedVersion::parse_snapshot("1.2.3-beta").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let version = SemVer::new(1, 2, 3);
+        let json = serde_json::to_string(&version).unwrap();
+        assert_eq!(json, "\"1.2.3\"");
+        let parsed: SemVer = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, version);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_invalid() {
+        let err = serde_json::from_str::<SemVer>("\"1.2\"").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("expected 3 version components, found 2"));
+        assert!(serde_json::from_str::<SemVer>("123").is_err());
+    }
 }
@@ -0,0 +1,239 @@
+//! Handles Sparkle appcast files of macOS apps
+extern crate xml;
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::config;
+use crate::files::{ContentProcessor, FileFinder, RecursiveFileFinderByExt};
+use crate::sem_ver::SemVer;
+use crate::xml_util::{echo, transform_xml, ElementPath, XmlError};
+use xml::name::OwnedName;
+use xml::reader::{EventReader, XmlEvent};
+use xml::writer::EventWriter;
+
+const SPARKLE_NAMESPACE: &str = "http://www.andymatuschak.org/xml-namespaces/sparkle";
+
+/// Handles versioning found in `appcast.xml` files.
+/// Only the newest item of the feed is updated, which is the item with the
+/// biggest version, or else the first item.
+///
+/// Files are searched up to `max_depth` folders deep (0 means only the root folder).
+pub struct AppcastUpdater {
+    pub max_depth: usize,
+}
+
+impl FileFinder for AppcastUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let config = config::load(dir)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(RecursiveFileFinderByExt::new(&["xml"], self.max_depth)
+            .with_ignore_dirs(&config.ignore_dirs)
+            .find(dir)?
+            .into_iter()
+            .filter(|path| path.file_name().is_some_and(|name| name == "appcast.xml"))
+            .collect())
+    }
+}
+
+impl ContentProcessor for AppcastUpdater {
+    type Err = XmlError;
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        process_str(old_contents, version)
+    }
+}
+
+fn process_str(old_contents: &str, version: SemVer) -> Result<String, XmlError> {
+    let newest_item = find_newest_item(old_contents.as_bytes())?;
+    transform_xml(old_contents, |parser, writer| {
+        do_process(parser, writer, version, newest_item)
+    })
+}
+
+/// Finds the (1-based) index of the item with the biggest version.
+/// Items are usually sorted newest first, so the first item wins when no version can be read.
+fn find_newest_item<R: Read>(source: R) -> Result<usize, XmlError> {
+    let mut element_path = ElementPath::Empty;
+    let mut item_count = 0;
+    let mut in_version = false;
+    let mut newest: Option<(SemVer, usize)> = None;
+    for xml_event in EventReader::new(source) {
+        match xml_event? {
+            XmlEvent::StartElement { name, .. } => {
+                element_path = element_path.push(&name.local_name);
+                if element_path.matches(&["rss", "channel", "item"]) {
+                    item_count += 1;
+                }
+                in_version = is_version_element(&name, &element_path);
+            }
+            XmlEvent::EndElement { .. } => {
+                element_path = element_path.pop();
+                in_version = false;
+            }
+            XmlEvent::Characters(text) | XmlEvent::CData(text) if in_version => {
+                if let Ok(version) = SemVer::from_str(text.trim()) {
+                    let is_newer = match newest {
+                        Some((newest_version, _)) => version > newest_version,
+                        None => true,
+                    };
+                    if is_newer {
+                        newest = Some((version, item_count));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(newest.map_or(1, |(_, item)| item))
+}
+
+fn do_process<R: Read, W: Write>(
+    parser: EventReader<R>,
+    writer: &mut EventWriter<W>,
+    version: SemVer,
+    newest_item: usize,
+) -> Result<(), XmlError> {
+    let version_as_str = version.to_string();
+    let mut element_path = ElementPath::Empty;
+    let mut item_count = 0;
+    let mut replacing = false;
+    for result_xml_event in parser {
+        let xml_event = result_xml_event?;
+        match &xml_event {
+            XmlEvent::StartElement { name, .. } => {
+                element_path = element_path.push(&name.local_name);
+                if element_path.matches(&["rss", "channel", "item"]) {
+                    item_count += 1;
+                }
+                echo(&xml_event, writer)?;
+                if item_count == newest_item && is_version_element(name, &element_path) {
+                    writer.write(xml::writer::XmlEvent::characters(&version_as_str))?;
+                    replacing = true;
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                element_path = element_path.pop();
+                replacing = false;
+                echo(&xml_event, writer)?;
+            }
+            XmlEvent::Characters(_) | XmlEvent::CData(_) if replacing => {
+                // the old version is discarded
+            }
+            XmlEvent::Whitespace(_) => {
+                // discarding whitespace because it confuses indentation
+            }
+            _ => {
+                echo(&xml_event, writer)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_version_element(name: &OwnedName, element_path: &ElementPath) -> bool {
+    name.namespace.as_deref() == Some(SPARKLE_NAMESPACE)
+        && ["version", "shortVersionString"]
+            .iter()
+            .any(|local_name| element_path.matches(&["rss", "channel", "item", local_name]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_only_newest_item() {
+        let input = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0" xmlns:sparkle="http://www.andymatuschak.org/xml-namespaces/sparkle">
+  <channel>
+    <title>App Changelog</title>
+    <item>
+      <title>Version 1.1.0</title>
+      <sparkle:version>1.1.0</sparkle:version>
+      <sparkle:shortVersionString>1.1.0</sparkle:shortVersionString>
+      <enclosure url="https://example.com/app-1.1.0.zip" length="1623481" type="application/octet-stream" />
+    </item>
+    <item>
+      <title>Version 1.0.0</title>
+      <sparkle:version>1.0.0</sparkle:version>
+      <sparkle:shortVersionString>1.0.0</sparkle:shortVersionString>
+    </item>
+  </channel>
+</rss>
+"#;
+        let expected = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss xmlns:sparkle="http://www.andymatuschak.org/xml-namespaces/sparkle" version="2.0">
+  <channel>
+    <title>App Changelog</title>
+    <item>
+      <title>Version 1.1.0</title>
+      <sparkle:version>1.2.0</sparkle:version>
+      <sparkle:shortVersionString>1.2.0</sparkle:shortVersionString>
+      <enclosure url="https://example.com/app-1.1.0.zip" length="1623481" type="application/octet-stream" />
+    </item>
+    <item>
+      <title>Version 1.0.0</title>
+      <sparkle:version>1.0.0</sparkle:version>
+      <sparkle:shortVersionString>1.0.0</sparkle:shortVersionString>
+    </item>
+  </channel>
+</rss>
+"#;
+        let result = process_str(input, SemVer::new(1, 2, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn updates_the_item_with_the_biggest_version() {
+        let input = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss xmlns:sparkle="http://www.andymatuschak.org/xml-namespaces/sparkle" version="2.0">
+  <channel>
+    <item>
+      <sparkle:version>1.0.0</sparkle:version>
+    </item>
+    <item>
+      <sparkle:version>1.1.0</sparkle:version>
+    </item>
+  </channel>
+</rss>
+"#;
+        let expected = input.replace("1.1.0", "1.2.0");
+        let result = process_str(input, SemVer::new(1, 2, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn finds_nested_appcast_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("docs").join("appcast.xml"), "<rss/>").unwrap();
+        std::fs::write(dir.path().join("docs").join("other.xml"), "<rss/>").unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+
+        assert!(AppcastUpdater { max_depth: 0 }
+            .find(dir_str)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            AppcastUpdater { max_depth: 1 }.find(dir_str).unwrap(),
+            vec![dir.path().join("docs").join("appcast.xml")]
+        );
+    }
+
+    #[test]
+    fn ignores_version_elements_in_other_namespaces() {
+        let input = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0">
+  <channel>
+    <item>
+      <version>1.1.0</version>
+    </item>
+  </channel>
+</rss>
+"#;
+        let result = process_str(input, SemVer::new(1, 2, 0)).unwrap();
+        assert_eq!(result, input);
+    }
+}
@@ -0,0 +1,224 @@
+//! Stamps the date of the release (e.g. `BUILD_DATE`) or a build counter into a file
+
+use crate::date::{Date, DEFAULT_DATE_FORMAT};
+use crate::encoding::TextContents;
+use crate::files::{
+    keep_trailing_newline, read_legacy_contents, ContentProcessor, DirUpdater, UpdateError,
+};
+use crate::{config, SemVer};
+use std::path::PathBuf;
+
+/// The file that gets the date, unless `build_date_file` is configured in `.yart.toml`.
+pub const DEFAULT_BUILD_DATE_FILE: &str = "BUILD_DATE";
+
+/// The file that holds the build counter, which is incremented on every release.
+pub const BUILD_COUNTER_FILE: &str = ".yart-build";
+
+/// Replaces the contents of a file with a date (`YYYY-MM-DD`).
+/// The version is not used; a trailing newline is kept if the file had one.
+pub struct BuildDateProcessor {
+    date: Date,
+}
+
+impl BuildDateProcessor {
+    pub fn new(date: Date) -> Self {
+        Self { date }
+    }
+}
+
+impl ContentProcessor for BuildDateProcessor {
+    type Err = std::io::Error; // does not really throw error
+
+    fn process(&self, old_contents: &str, _new_version: SemVer) -> Result<String, Self::Err> {
+        Ok(keep_trailing_newline(
+            new_file_ending(old_contents),
+            self.date.format(DEFAULT_DATE_FORMAT),
+        ))
+    }
+
+    /// The date does not indicate a version.
+    fn is_at_version(&self, _contents: &str, _version: SemVer) -> bool {
+        true
+    }
+}
+
+/// A file that is created (i.e. has no contents yet) gets a final line break.
+fn new_file_ending(old_contents: &str) -> &str {
+    if old_contents.is_empty() {
+        "\n"
+    } else {
+        old_contents
+    }
+}
+
+/// Writes the date into the build date file of the folder,
+/// creating the file if it does not exist.
+pub struct BuildDateDirUpdater {
+    date: Date,
+}
+
+impl BuildDateDirUpdater {
+    pub fn new(date: Date) -> Self {
+        Self { date }
+    }
+}
+
+impl DirUpdater for BuildDateDirUpdater {
+    fn update(
+        &self,
+        dir: &str,
+        new_version: SemVer,
+    ) -> Result<Vec<(PathBuf, TextContents)>, UpdateError> {
+        let config = config::load(dir).map_err(UpdateError::new_boxing_other)?;
+        let file = config
+            .build_date_file
+            .as_deref()
+            .unwrap_or(DEFAULT_BUILD_DATE_FILE);
+        let path = PathBuf::from(dir).join(file);
+        let old_contents = if path.is_file() {
+            read_legacy_contents(&path)?
+        } else {
+            TextContents::from(String::new())
+        };
+        let new_contents =
+            BuildDateProcessor::new(self.date).process(&old_contents.text, new_version)?;
+        if old_contents.text == new_contents {
+            Ok(vec![])
+        } else {
+            Ok(vec![(
+                path,
+                TextContents::new(new_contents, old_contents.encoding),
+            )])
+        }
+    }
+
+    fn find_outdated(&self, _dir: &str, _version: SemVer) -> Result<Vec<PathBuf>, UpdateError> {
+        Ok(vec![])
+    }
+}
+
+/// Reads the build counter of the folder and returns the next one.
+/// The first build is 1, if the build counter file does not exist yet.
+pub fn next_build_counter(dir: &str) -> Result<u64, UpdateError> {
+    let path = PathBuf::from(dir).join(BUILD_COUNTER_FILE);
+    let contents = if path.is_file() {
+        std::fs::read_to_string(&path)?
+    } else {
+        String::new()
+    };
+    parse_build_counter(&contents)
+        .and_then(|counter| counter.checked_add(1))
+        .ok_or_else(|| {
+            UpdateError::IOError(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "{} does not hold a valid build counter: {}",
+                    path.to_string_lossy(),
+                    contents.trim()
+                ),
+            ))
+        })
+}
+
+/// Parses the contents of the build counter file, where an empty file means no builds.
+fn parse_build_counter(contents: &str) -> Option<u64> {
+    let contents = contents.trim();
+    if contents.is_empty() {
+        Some(0)
+    } else {
+        contents.parse().ok()
+    }
+}
+
+/// Writes the build counter into the build counter file of the folder,
+/// creating the file if it does not exist.
+pub struct BuildCounterDirUpdater {
+    counter: u64,
+}
+
+impl BuildCounterDirUpdater {
+    pub fn new(counter: u64) -> Self {
+        Self { counter }
+    }
+}
+
+impl DirUpdater for BuildCounterDirUpdater {
+    fn update(
+        &self,
+        dir: &str,
+        _new_version: SemVer,
+    ) -> Result<Vec<(PathBuf, TextContents)>, UpdateError> {
+        let path = PathBuf::from(dir).join(BUILD_COUNTER_FILE);
+        let old_contents = if path.is_file() {
+            std::fs::read_to_string(&path)?
+        } else {
+            String::new()
+        };
+        let new_contents =
+            keep_trailing_newline(new_file_ending(&old_contents), self.counter.to_string());
+        if old_contents == new_contents {
+            Ok(vec![])
+        } else {
+            Ok(vec![(path, new_contents.into())])
+        }
+    }
+
+    fn find_outdated(&self, _dir: &str, _version: SemVer) -> Result<Vec<PathBuf>, UpdateError> {
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_date_processor() {
+        let processor = BuildDateProcessor::new(Date::new(2024, 3, 7).unwrap());
+        let version = SemVer::new(1, 2, 3);
+        assert_eq!(
+            processor.process("2024-01-31\n", version).unwrap(),
+            "2024-03-07\n"
+        );
+        assert_eq!(
+            processor.process("2024-01-31", version).unwrap(),
+            "2024-03-07"
+        );
+        assert_eq!(processor.process("", version).unwrap(), "2024-03-07\n");
+        assert_eq!(
+            processor.process("2024-01-31\r\n", version).unwrap(),
+            "2024-03-07\r\n"
+        );
+    }
+
+    #[test]
+    fn test_build_counter() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let path = dir.path().join(BUILD_COUNTER_FILE);
+        let version = SemVer::new(1, 2, 3);
+
+        // the first build creates the file
+        assert_eq!(next_build_counter(dir_str).unwrap(), 1);
+        assert_eq!(
+            BuildCounterDirUpdater::new(1)
+                .update(dir_str, version)
+                .unwrap(),
+            vec![(path.clone(), "1\n".into())]
+        );
+
+        std::fs::write(&path, "41\n").unwrap();
+        assert_eq!(next_build_counter(dir_str).unwrap(), 42);
+        assert_eq!(
+            BuildCounterDirUpdater::new(42)
+                .update(dir_str, version)
+                .unwrap(),
+            vec![(path.clone(), "42\n".into())]
+        );
+
+        std::fs::write(&path, "forty-one").unwrap();
+        assert!(next_build_counter(dir_str).is_err());
+        std::fs::write(&path, u64::MAX.to_string()).unwrap();
+        assert!(next_build_counter(dir_str).is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! Minimal line-oriented TOML handling that preserves the original formatting
+
+use crate::files::ContentProcessor;
+use crate::SemVer;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+pub enum TomlError {
+    /// The requested path does not exist. Holds the dotted path.
+    PathNotFound(String),
+
+    /// The value at the requested path is not a string.
+    NotAString(String),
+}
+
+impl Display for TomlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PathNotFound(path) => write!(f, "TOML path {} not found", path),
+            Self::NotAString(path) => write!(f, "TOML path {} does not point to a string", path),
+        }
+    }
+}
+
+impl std::error::Error for TomlError {}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum TomlPathState {
+    InSection,
+    OutsideSection,
+    Stop,
+}
+
+/// Replaces the string value found at the given dotted path with the given value.
+///
+/// The last part of the path is the key and the rest is the table that holds it,
+/// e.g. `tool.poetry.version` is the `version` key of the `[tool.poetry]` table.
+/// A path without dots is a key of the root table.
+pub fn set_string_at_path(contents: &str, path: &str, value: &str) -> Result<String, TomlError> {
+    let processor = match path.rsplit_once('.') {
+        Some((table, key)) => TomlSectionProcessor::new(table, key),
+        None => TomlSectionProcessor::new("", path),
+    };
+    processor.set_string(contents, value)
+}
+
+/// Reads the string value found at the given dotted path,
+/// as described in [set_string_at_path].
+pub fn get_string_at_path(contents: &str, path: &str) -> Option<String> {
+    let processor = match path.rsplit_once('.') {
+        Some((table, key)) => TomlSectionProcessor::new(table, key),
+        None => TomlSectionProcessor::new("", path),
+    };
+    processor.get_string(contents)
+}
+
+/// Sets the string value of a key of a table (e.g. `version` of `[tool.poetry]`).
+///
+/// An empty table name means the root table. For an array of tables
+/// (e.g. `[[tool.poetry]]`), the first entry is updated.
+/// Everything else in the document stays as-is, including the quote style and comments.
+pub struct TomlSectionProcessor {
+    table: String,
+    key: String,
+}
+
+impl TomlSectionProcessor {
+    pub fn new(table: &str, key: &str) -> Self {
+        Self {
+            table: table.to_owned(),
+            key: key.to_owned(),
+        }
+    }
+
+    fn path(&self) -> String {
+        if self.table.is_empty() {
+            self.key.clone()
+        } else {
+            format!("{}.{}", self.table, self.key)
+        }
+    }
+
+    fn is_table_header(&self, line: &str) -> bool {
+        is_toml_section(line, &format!("[{}]", self.table))
+            || is_toml_section(line, &format!("[[{}]]", self.table))
+    }
+
+    /// Reads the string value of the key.
+    /// Returns `None` if the key is missing or its value is not a string.
+    pub fn get_string(&self, contents: &str) -> Option<String> {
+        let mut in_section = self.table.is_empty();
+        for line in contents.lines() {
+            if in_section {
+                if is_toml_section_header(line) {
+                    return None;
+                } else if is_toml_key(line.trim_start(), &self.key) {
+                    return get_string_value(line, &self.key).map(str::to_owned);
+                }
+            } else if self.is_table_header(line) {
+                in_section = true;
+            }
+        }
+        None
+    }
+
+    /// Replaces the string value of the key with the given value.
+    pub fn set_string(&self, contents: &str, value: &str) -> Result<String, TomlError> {
+        let mut state = if self.table.is_empty() {
+            TomlPathState::InSection
+        } else {
+            TomlPathState::OutsideSection
+        };
+        let mut result = String::with_capacity(contents.len() + value.len());
+        let mut found = false;
+        for line in contents.split_inclusive('\n') {
+            match state {
+                TomlPathState::OutsideSection => {
+                    if self.is_table_header(line) {
+                        state = TomlPathState::InSection;
+                    }
+                }
+                TomlPathState::InSection => {
+                    if is_toml_section_header(line) {
+                        state = TomlPathState::Stop;
+                    } else if let Some(new_line) = replace_string_value(line, &self.key, value) {
+                        result.push_str(&new_line);
+                        found = true;
+                        state = TomlPathState::Stop;
+                        continue;
+                    } else if is_toml_key(line.trim_start(), &self.key) {
+                        return Err(TomlError::NotAString(self.path()));
+                    }
+                }
+                TomlPathState::Stop => {}
+            }
+            result.push_str(line);
+        }
+        if found {
+            Ok(result)
+        } else {
+            Err(TomlError::PathNotFound(self.path()))
+        }
+    }
+}
+
+impl ContentProcessor for TomlSectionProcessor {
+    type Err = TomlError;
+
+    fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
+        self.set_string(old_contents, &new_version.to_string())
+    }
+}
+
+/// Reads the quoted string value of the given key, without the quotes.
+fn get_string_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let value = get_toml_key_value(line.trim_start(), key)?;
+    let quote = value
+        .chars()
+        .next()
+        .filter(|ch| *ch == '"' || *ch == '\'')?;
+    let len = value[1..].find(quote)?;
+    Some(&value[1..len + 1])
+}
+
+/// Replaces the quoted string value of the given key, keeping the quote style.
+fn replace_string_value(line: &str, key: &str, new_value: &str) -> Option<String> {
+    let value = get_toml_key_value(line.trim_start(), key)?;
+    let quote = value
+        .chars()
+        .next()
+        .filter(|ch| *ch == '"' || *ch == '\'')?;
+    let start = line.len() - value.len() + 1;
+    let len = line[start..].find(quote)?;
+    Some(format!(
+        "{}{}{}",
+        &line[..start],
+        new_value,
+        &line[start + len..]
+    ))
+}
+
+/// Checks if the given line is the header of the given section
+/// (e.g. `[package]`), ignoring surrounding whitespace and a trailing comment.
+pub fn is_toml_section(line: &str, section: &str) -> bool {
+    strip_toml_comment(line).trim() == section
+}
+
+/// Checks if the given line is the header of any section.
+pub fn is_toml_section_header(line: &str) -> bool {
+    line.trim_start().starts_with('[')
+}
+
+/// Removes a trailing comment, ignoring `#` characters inside basic (`"..."`)
+/// and literal (`'...'`) strings.
+pub fn strip_toml_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (idx, ch) in line.char_indices() {
+        match quote {
+            // only basic strings have escapes
+            Some('"') if escaped => escaped = false,
+            Some('"') if ch == '\\' => escaped = true,
+            Some(q) if ch == q => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '#' => return line.split_at(idx).0,
+            None => {}
+        }
+    }
+    line
+}
+
+pub fn is_toml_key(line: &str, key: &str) -> bool {
+    if line.is_empty() || key.is_empty() {
+        false
+    } else if line.starts_with(key) {
+        let (_, second) = line.split_at(key.len());
+        second.trim_start().starts_with('=')
+    } else {
+        false
+    }
+}
+
+pub fn get_toml_key_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    if line.is_empty() || key.is_empty() {
+        None
+    } else if line.starts_with(key) {
+        let (_, second) = line.split_at(key.len());
+        let second = second.trim_start();
+        if second.starts_with('=') {
+            let (_, second) = second.split_at(1);
+            Some(second.trim_start())
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_toml_comment() {
+        assert_eq!(strip_toml_comment("[package] # the crate"), "[package] ");
+        assert_eq!(strip_toml_comment("[\"a#b\"] # c"), "[\"a#b\"] ");
+        assert_eq!(strip_toml_comment("k = 'a#b' # c"), "k = 'a#b' ");
+        assert_eq!(
+            strip_toml_comment(r##"k = "a\"#b" # c"##),
+            r##"k = "a\"#b" "##
+        );
+        assert_eq!(strip_toml_comment(r"k = 'a\' # c"), r"k = 'a\' ");
+        assert_eq!(strip_toml_comment("k = 1"), "k = 1");
+    }
+
+    #[test]
+    fn test_set_string_at_path_two_levels() {
+        let input = r#"[project]
+name = "app"
+version = "0.0.0"
+
+[tool.poetry]
+name = "app"
+version = "0.1.0" # bumped by yart
+description = ""
+
+[tool.poetry.dependencies]
+version = "1.0.0"
+"#;
+        let expected = r#"[project]
+name = "app"
+version = "0.0.0"
+
+[tool.poetry]
+name = "app"
+version = "1.2.3" # bumped by yart
+description = ""
+
+[tool.poetry.dependencies]
+version = "1.0.0"
+"#;
+        let result = set_string_at_path(input, "tool.poetry.version", "1.2.3").unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_set_string_at_path_root_and_literal_string() {
+        let input = "version = '0.1.0'\n\n[tool]\nversion = '0.0.1'\n";
+        let expected = "version = '2.0.0'\n\n[tool]\nversion = '0.0.1'\n";
+        let result = set_string_at_path(input, "version", "2.0.0").unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_set_string_at_path_array_of_tables() {
+        let input = "[[tool.app]]\nversion = \"0.1.0\"\n\n[[tool.app]]\nversion = \"0.1.0\"\n";
+        let expected = "[[tool.app]]\nversion = \"0.2.0\"\n\n[[tool.app]]\nversion = \"0.1.0\"\n";
+        let result = set_string_at_path(input, "tool.app.version", "0.2.0").unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_set_string_at_path_not_found() {
+        let input = "[tool.poetry]\nname = \"app\"\n\n[tool.other]\nversion = \"0.1.0\"\n";
+        let err = set_string_at_path(input, "tool.poetry.version", "1.2.3").unwrap_err();
+        assert_eq!(err.to_string(), "TOML path tool.poetry.version not found");
+    }
+
+    #[test]
+    fn test_toml_section_processor() {
+        let input = "[package]\nname = \"app\"\n  version   =   \"0.1.0\"\n\n[dependencies]\nversion = \"0.1.0\"\n";
+        let expected = "[package]\nname = \"app\"\n  version   =   \"0.2.0\"\n\n[dependencies]\nversion = \"0.1.0\"\n";
+        let processor = TomlSectionProcessor::new("package", "version");
+        let result = processor.process(input, SemVer::new(0, 2, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_toml_section_processor_crlf() {
+        let input = "[package]\r\nversion = \"0.1.0\"\r\n";
+        let expected = "[package]\r\nversion = \"0.2.0\"\r\n";
+        let processor = TomlSectionProcessor::new("package", "version");
+        let result = processor.process(input, SemVer::new(0, 2, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_toml_section_processor_key_in_other_table_only() {
+        let input = "[package]\nname = \"app\"\n\n[dependencies.clap]\nversion = \"3.2\"\n";
+        let processor = TomlSectionProcessor::new("package", "version");
+        let err = processor.process(input, SemVer::new(0, 2, 0)).unwrap_err();
+        assert_eq!(err.to_string(), "TOML path package.version not found");
+    }
+
+    #[test]
+    fn test_get_string_at_path() {
+        let input = "version = '0.0.1'\n\n[tool.poetry]\nname = \"app\"\nversion = \"0.1.0\" # bumped by yart\n\n[package]\nversion = { workspace = true }\n";
+        assert_eq!(
+            get_string_at_path(input, "tool.poetry.version"),
+            Some("0.1.0".to_owned())
+        );
+        assert_eq!(
+            get_string_at_path(input, "version"),
+            Some("0.0.1".to_owned())
+        );
+        assert_eq!(get_string_at_path(input, "tool.poetry.description"), None);
+        assert_eq!(get_string_at_path(input, "package.version"), None);
+    }
+
+    #[test]
+    fn test_set_string_at_path_not_a_string() {
+        let input = "[package]\nversion = { workspace = true }\n";
+        let err = set_string_at_path(input, "package.version", "1.2.3").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "TOML path package.version does not point to a string"
+        );
+    }
+}
@@ -1,31 +1,487 @@
 //! Updates files
 
-use crate::files::{DirUpdater, UpdateError};
-use crate::writers::create_writer;
-use crate::{delphi, rust, vb6, SemVer};
-use std::path::PathBuf;
+use crate::date::Date;
+use crate::encoding::TextContents;
+use crate::files::{
+    dedup_and_sort, has_extension, ContentProcessor, DirUpdater, ExplicitFileFinder, FileUpdater,
+    UpdateError,
+};
+use crate::git::GitRunner;
+use crate::logger::Logger;
+use crate::progress::Event;
+use crate::sem_ver::ExtractedVersion;
+use crate::writers::{create_writer, WriteOptions};
+use crate::{
+    clojure, config, dart, delphi, dotnet, elixir, generic, godot, gradle, helm, homebrew, infra,
+    maven, python, ruby, rust, scala, sparkle, stamp, vb6, zig, SemVer,
+};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
+/// Options that affect how files are updated.
+#[derive(Clone, Debug, Default)]
+pub struct UpdateOptions {
+    /// Increment the Android `versionCode` of Gradle build files.
+    pub bump_version_code: bool,
+
+    /// Write `-SNAPSHOT` versions to sbt builds (e.g. for the next development version).
+    pub snapshot: bool,
+
+    /// Overrides the build number of Dart packages (e.g. `1.2.3+42`).
+    pub build_number: Option<u64>,
+
+    /// Increment the `RevisionVer` of VB6 projects instead of setting it to the patch.
+    pub vb6_auto_revision: bool,
+
+    /// Write this date into the build date file (e.g. `BUILD_DATE`).
+    pub stamp_date: Option<Date>,
+
+    /// Write this number into the build counter file (`.yart-build`).
+    pub build_counter: Option<u64>,
+
+    /// Update this property of `pom.xml` (e.g. `revision`) instead of the project version.
+    pub maven_property: Option<String>,
+
+    /// Also set the `scm/tag` of `pom.xml` to the new tag.
+    pub update_scm_tag: bool,
+
+    /// Also set the `image.tag` of the `values.yaml` of Helm charts.
+    pub update_values_image_tag: bool,
+
+    /// Set the `image.tag` of these values files instead of the `values.yaml`.
+    pub values_files: Vec<PathBuf>,
+
+    /// Leave the versions of crates with `publish = false` unchanged.
+    pub skip_unpublished: bool,
+
+    /// How many folders deep to search for files that can be nested (e.g. `.csproj`).
+    /// 0 means only the root folder.
+    pub max_depth: usize,
+
+    /// Receives the messages about files that are deliberately left alone.
+    pub logger: Logger,
+
+    /// Continue with the other updaters when one fails, reporting the failures at the end.
+    pub keep_going: bool,
+}
+
+/// The format of a file that is given explicitly, which determines how it is updated.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FileKind {
+    Cargo,
+    Vb6,
+    Lpi,
+    Lpk,
+    Appcast,
+    Zig,
+    Mix,
+    Boot,
+    Gradle,
+    Sbt,
+    Pubspec,
+    Puppet,
+    Nuspec,
+    Maven,
+    Csproj,
+    PyProject,
+    Homebrew,
+    Godot,
+}
+
+impl FileKind {
+    /// The names of the kinds, as accepted by [FileKind::from_str].
+    pub const NAMES: [&'static str; 18] = [
+        "cargo",
+        "vb6",
+        "lpi",
+        "lpk",
+        "appcast",
+        "zig",
+        "mix",
+        "boot",
+        "gradle",
+        "sbt",
+        "pubspec",
+        "puppet",
+        "nuspec",
+        "maven",
+        "csproj",
+        "pyproject",
+        "homebrew",
+        "godot",
+    ];
+
+    /// Infers the kind of the file from its name.
+    pub fn infer(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_string_lossy();
+        match file_name.as_ref() {
+            "Cargo.toml" => Some(Self::Cargo),
+            "appcast.xml" => Some(Self::Appcast),
+            "build.zig.zon" => Some(Self::Zig),
+            "mix.exs" => Some(Self::Mix),
+            "build.boot" => Some(Self::Boot),
+            "build.gradle" | "build.gradle.kts" => Some(Self::Gradle),
+            "pubspec.yaml" => Some(Self::Pubspec),
+            "metadata.json" => Some(Self::Puppet),
+            "pom.xml" => Some(Self::Maven),
+            "pyproject.toml" => Some(Self::PyProject),
+            "project.godot" => Some(Self::Godot),
+            _ if has_extension(path, "vbp") => Some(Self::Vb6),
+            _ if has_extension(path, "lpi") => Some(Self::Lpi),
+            _ if has_extension(path, "lpk") => Some(Self::Lpk),
+            _ if has_extension(path, "sbt") => Some(Self::Sbt),
+            _ if has_extension(path, "nuspec") => Some(Self::Nuspec),
+            _ if has_extension(path, "csproj") => Some(Self::Csproj),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for FileKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cargo" => Ok(Self::Cargo),
+            "vb6" => Ok(Self::Vb6),
+            "lpi" => Ok(Self::Lpi),
+            "lpk" => Ok(Self::Lpk),
+            "appcast" => Ok(Self::Appcast),
+            "zig" => Ok(Self::Zig),
+            "mix" => Ok(Self::Mix),
+            "boot" => Ok(Self::Boot),
+            "gradle" => Ok(Self::Gradle),
+            "sbt" => Ok(Self::Sbt),
+            "pubspec" => Ok(Self::Pubspec),
+            "puppet" => Ok(Self::Puppet),
+            "nuspec" => Ok(Self::Nuspec),
+            "maven" => Ok(Self::Maven),
+            "csproj" => Ok(Self::Csproj),
+            "pyproject" => Ok(Self::PyProject),
+            "homebrew" => Ok(Self::Homebrew),
+            "godot" => Ok(Self::Godot),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ExplicitFileError {
+    /// The kind of the file could not be inferred from its name.
+    UnknownKind(PathBuf),
+}
+
+impl Display for ExplicitFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownKind(path) => write!(
+                f,
+                "cannot infer how to update {}, use --as to specify its format",
+                path.to_string_lossy()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExplicitFileError {}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The files do not indicate the expected version after they were written.
+    Outdated(SemVer, Vec<PathBuf>),
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Outdated(version, files) => {
+                write!(
+                    f,
+                    "files do not indicate version {} after writing:",
+                    version
+                )?;
+                for file in files {
+                    write!(f, " {}", file.to_string_lossy())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+#[derive(Debug)]
+pub enum KeepGoingError {
+    /// Some updaters failed, while the files of the rest were written.
+    Failed(Vec<UpdateError>),
+}
+
+impl Display for KeepGoingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Failed(errors) => {
+                write!(f, "{} updater(s) failed:", errors.len())?;
+                for err in errors {
+                    write!(f, "\n  {}", err)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeepGoingError {}
+
+#[derive(Debug)]
+pub enum ValidateError {
+    /// None of the files indicates a version that could be read.
+    NoVersion,
+
+    /// The files indicate different versions. Holds every file with its version.
+    Mismatch(Vec<(PathBuf, ExtractedVersion)>),
+}
+
+impl Display for ValidateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoVersion => write!(f, "no file indicates a version"),
+            Self::Mismatch(files) => {
+                write!(f, "files indicate different versions:")?;
+                for (file, version) in files {
+                    write!(f, " {} ({})", file.to_string_lossy(), version)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidateError {}
+
+/// Selects which files are updated.
+pub enum FileSelection {
+    /// Find the supported files of the directory.
+    Discover,
+
+    /// Update only the given files, relative to the directory.
+    /// Their format is inferred from their names, unless it is given.
+    Explicit(Vec<PathBuf>, Option<FileKind>),
+}
+
+/// Updates the selected files of `dir` and stages them with the given runner
+/// in the repository at `git_dir`, if given.
+/// In dry-run, the changes are printed instead.
+///
+/// With [UpdateOptions::keep_going], the files of the updaters that succeeded are written
+/// before the failures are returned.
 pub fn update_files(
+    runner: &dyn GitRunner,
     dir: &str,
+    git_dir: Option<PathBuf>,
+    selection: &FileSelection,
     new_version: SemVer,
-    dry_run: bool,
-) -> Result<Vec<(PathBuf, String)>, UpdateError> {
-    let composite = CompositeDirUpdater {};
-    let files = composite.update(dir, new_version)?;
-    let writer = create_writer(PathBuf::from(dir), dry_run);
+    options: &UpdateOptions,
+    write_options: WriteOptions,
+) -> Result<Vec<(PathBuf, TextContents)>, UpdateError> {
+    let (files, errors) = if options.keep_going {
+        dir_updater(selection, options).update_keep_going(dir, new_version)
+    } else {
+        (
+            find_updates(dir, selection, new_version, options)?,
+            Vec::new(),
+        )
+    };
+    let progress = write_options.progress;
+    let dry_run = write_options.dry_run;
+    for (path_buf, _) in files.iter() {
+        progress.emit(Event::FileDiscovered(path_buf.clone()));
+    }
+    let writer = create_writer(runner, dir, git_dir, write_options);
+    for (path_buf, new_contents) in files.iter() {
+        writer.write(path_buf, new_contents)?;
+        if !dry_run {
+            progress.emit(Event::FileChanged(path_buf.clone()));
+        }
+    }
+    if errors.is_empty() {
+        Ok(files)
+    } else {
+        Err(UpdateError::new_boxing_other(KeepGoingError::Failed(
+            errors,
+        )))
+    }
+}
+
+/// Sets the next development version (e.g. `1.3.0-SNAPSHOT`) in the files that have one,
+/// i.e. the version settings of sbt builds, and stages them like [update_files].
+pub fn update_dev_version_files(
+    runner: &dyn GitRunner,
+    dir: &str,
+    git_dir: Option<PathBuf>,
+    dev_version: SemVer,
+    write_options: WriteOptions,
+) -> Result<Vec<(PathBuf, TextContents)>, UpdateError> {
+    let files = scala::SbtUpdater { snapshot: true }.update(dir, dev_version)?;
+    let writer = create_writer(runner, dir, git_dir, write_options);
     for (path_buf, new_contents) in files.iter() {
         writer.write(path_buf, new_contents)?;
     }
     Ok(files)
 }
 
-struct CompositeDirUpdater {}
+/// Checks if all selected files already indicate the given version.
+/// Parts that are always changed (e.g. an incremented `versionCode`) are ignored.
+pub fn files_match_version(
+    dir: &str,
+    selection: &FileSelection,
+    version: SemVer,
+    options: &UpdateOptions,
+) -> Result<bool, UpdateError> {
+    Ok(dir_updater(selection, options)
+        .find_outdated(dir, version)?
+        .is_empty())
+}
 
-macro_rules! add_files {
-    ($updater:expr, $dir: expr, $new_version: expr, $result: expr) => {
-        let mut partial_files = $updater.update($dir, $new_version)?;
-        $result.append(&mut partial_files);
-    };
+/// Re-reads the selected files after they were written and checks that
+/// they indicate the given version, catching processors that wrote malformed output.
+pub fn verify_files(
+    dir: &str,
+    selection: &FileSelection,
+    version: SemVer,
+    options: &UpdateOptions,
+) -> Result<(), UpdateError> {
+    verify(dir_updater(selection, options).as_ref(), dir, version)
+}
+
+/// Checks that the selected files all indicate the same version, returning that version.
+/// A `-SNAPSHOT` version does not agree with the release it leads to.
+pub fn validate_versions(
+    dir: &str,
+    selection: &FileSelection,
+    options: &UpdateOptions,
+) -> Result<ExtractedVersion, UpdateError> {
+    validate(dir_updater(selection, options).as_ref(), dir)
+}
+
+fn validate(updater: &dyn DirUpdater, dir: &str) -> Result<ExtractedVersion, UpdateError> {
+    let versions = updater.extract_versions(dir)?;
+    match versions.first() {
+        None => Err(UpdateError::new_boxing_other(ValidateError::NoVersion)),
+        Some((_, version)) if versions.iter().all(|(_, other)| other == version) => Ok(*version),
+        Some(_) => Err(UpdateError::new_boxing_other(ValidateError::Mismatch(
+            versions,
+        ))),
+    }
+}
+
+fn verify(updater: &dyn DirUpdater, dir: &str, version: SemVer) -> Result<(), UpdateError> {
+    let outdated = updater.find_outdated(dir, version)?;
+    if outdated.is_empty() {
+        Ok(())
+    } else {
+        Err(UpdateError::new_boxing_other(VerifyError::Outdated(
+            version, outdated,
+        )))
+    }
+}
+
+fn find_updates(
+    dir: &str,
+    selection: &FileSelection,
+    new_version: SemVer,
+    options: &UpdateOptions,
+) -> Result<Vec<(PathBuf, TextContents)>, UpdateError> {
+    dir_updater(selection, options).update(dir, new_version)
+}
+
+/// Creates the updater of the selected files.
+/// It only computes the new contents, without writing anything.
+pub fn dir_updater<'a>(
+    selection: &'a FileSelection,
+    options: &UpdateOptions,
+) -> Box<dyn DirUpdater + 'a> {
+    match selection {
+        FileSelection::Discover => Box::new(CompositeDirUpdater {
+            options: options.clone(),
+        }),
+        FileSelection::Explicit(files, kind) => Box::new(ExplicitDirUpdater {
+            files,
+            kind: *kind,
+            options: options.clone(),
+        }),
+    }
+}
+
+struct CompositeDirUpdater {
+    options: UpdateOptions,
+}
+
+impl CompositeDirUpdater {
+    fn updaters(&self) -> Vec<Box<dyn DirUpdater>> {
+        let mut updaters: Vec<Box<dyn DirUpdater>> = vec![
+            Box::new(vb6::VB6Updater {
+                auto_revision: self.options.vb6_auto_revision,
+            }),
+            Box::new(delphi::LpiUpdater {}),
+            Box::new(delphi::LpkUpdater {}),
+            Box::new(rust::CargoDirUpdater {
+                skip_unpublished: self.options.skip_unpublished,
+            }),
+            Box::new(sparkle::AppcastUpdater {
+                max_depth: self.options.max_depth,
+            }),
+            Box::new(ruby::RubyDirUpdater::new()),
+            Box::new(zig::ZigUpdater {}),
+            Box::new(elixir::MixUpdater {}),
+            Box::new(clojure::BootUpdater {}),
+            Box::new(gradle::GradleUpdater {
+                bump_version_code: self.options.bump_version_code,
+            }),
+            Box::new(scala::SbtUpdater {
+                snapshot: self.options.snapshot,
+            }),
+            Box::new(dart::PubspecUpdater {
+                build_number: self.options.build_number,
+            }),
+            Box::new(infra::PuppetMetadataUpdater {}),
+            Box::new(homebrew::HomebrewUpdater {}),
+            Box::new(godot::GodotUpdater {}),
+            Box::new(dotnet::NuspecUpdater {
+                max_depth: self.options.max_depth,
+            }),
+            Box::new(dotnet::CsprojUpdater {
+                max_depth: self.options.max_depth,
+            }),
+            Box::new(dotnet::AssemblyInfoVbUpdater {
+                max_depth: self.options.max_depth,
+                build_counter: self.options.build_counter,
+            }),
+            Box::new(maven::PomUpdater {
+                property: self.options.maven_property.clone(),
+                scm_tag: self.options.update_scm_tag,
+            }),
+            Box::new(generic::GenericDirUpdater::new()),
+            // after the configured files, so that a configured pyproject.toml takes precedence
+            Box::new(python::PyProjectUpdater {
+                logger: self.options.logger,
+            }),
+            Box::new(python::HatchVersionUpdater {}),
+        ];
+        if self.options.update_values_image_tag {
+            updaters.push(Box::new(helm::HelmValuesUpdater {
+                values_files: self.options.values_files.clone(),
+            }));
+        }
+        if let Some(date) = self.options.stamp_date {
+            updaters.push(Box::new(stamp::BuildDateDirUpdater::new(date)));
+        }
+        if let Some(counter) = self.options.build_counter {
+            updaters.push(Box::new(stamp::BuildCounterDirUpdater::new(counter)));
+        }
+        updaters
+    }
 }
 
 impl DirUpdater for CompositeDirUpdater {
@@ -33,11 +489,574 @@ impl DirUpdater for CompositeDirUpdater {
         &self,
         dir: &str,
         new_version: SemVer,
-    ) -> Result<Vec<(PathBuf, String)>, UpdateError> {
-        let mut result = Vec::<(PathBuf, String)>::new();
-        add_files!(vb6::VB6Updater {}, dir, new_version, result);
-        add_files!(delphi::LpiUpdater {}, dir, new_version, result);
-        add_files!(rust::CargoDirUpdater::new(), dir, new_version, result);
+    ) -> Result<Vec<(PathBuf, TextContents)>, UpdateError> {
+        let mut result = Vec::<(PathBuf, TextContents)>::new();
+        for updater in self.updaters() {
+            result.append(&mut updater.update(dir, new_version)?);
+        }
+        Ok(dedup_and_sort(result))
+    }
+
+    fn update_keep_going(
+        &self,
+        dir: &str,
+        new_version: SemVer,
+    ) -> (Vec<(PathBuf, TextContents)>, Vec<UpdateError>) {
+        let mut result = Vec::<(PathBuf, TextContents)>::new();
+        let mut errors = Vec::<UpdateError>::new();
+        for updater in self.updaters() {
+            let (mut files, mut updater_errors) = updater.update_keep_going(dir, new_version);
+            result.append(&mut files);
+            errors.append(&mut updater_errors);
+        }
+        (dedup_and_sort(result), errors)
+    }
+
+    fn find_files(&self, dir: &str) -> Result<Vec<PathBuf>, UpdateError> {
+        let mut result = Vec::<PathBuf>::new();
+        for updater in self.updaters() {
+            result.append(&mut updater.find_files(dir)?);
+        }
+        result.sort();
+        result.dedup();
+        Ok(result)
+    }
+
+    fn find_outdated(&self, dir: &str, version: SemVer) -> Result<Vec<PathBuf>, UpdateError> {
+        let mut result = Vec::<PathBuf>::new();
+        for updater in self.updaters() {
+            result.append(&mut updater.find_outdated(dir, version)?);
+        }
+        result.sort();
+        result.dedup();
+        Ok(result)
+    }
+
+    fn extract_versions(&self, dir: &str) -> Result<Vec<(PathBuf, ExtractedVersion)>, UpdateError> {
+        let mut result = Vec::<(PathBuf, ExtractedVersion)>::new();
+        for updater in self.updaters() {
+            result.append(&mut updater.extract_versions(dir)?);
+        }
+        result.sort_by(|(left, _), (right, _)| left.cmp(right));
+        result.dedup_by(|(left, _), (right, _)| left == right);
+        Ok(result)
+    }
+}
+
+/// Updates an explicit list of files, each one with the processor of its format.
+struct ExplicitDirUpdater<'a> {
+    files: &'a [PathBuf],
+    kind: Option<FileKind>,
+    options: UpdateOptions,
+}
+
+impl<'a> ExplicitDirUpdater<'a> {
+    fn updater(&self, dir: &str, file: &Path) -> Result<Box<dyn DirUpdater>, UpdateError> {
+        let kind = self.kind.or_else(|| FileKind::infer(file)).ok_or_else(|| {
+            UpdateError::new_boxing_other(ExplicitFileError::UnknownKind(file.to_path_buf()))
+        })?;
+        // only Cargo.toml has configurable extra keys
+        let config = if kind == FileKind::Cargo {
+            config::load(dir).map_err(UpdateError::new_boxing_other)?
+        } else {
+            config::Config::default()
+        };
+        Ok(Box::new(FileUpdater {
+            finder: ExplicitFileFinder::new(vec![file.to_path_buf()]),
+            processor: processor(kind, &self.options, &config),
+        }))
+    }
+}
+
+/// A processor of any format, see [processor].
+pub type BoxedProcessor = Box<dyn ContentProcessor<Err = UpdateError>>;
+
+/// Creates the processor of the given format, e.g. to update a file that is given explicitly.
+pub fn processor(
+    kind: FileKind,
+    options: &UpdateOptions,
+    config: &config::Config,
+) -> BoxedProcessor {
+    match kind {
+        FileKind::Cargo => Box::new(AnyProcessor(
+            rust::CargoTomlContentProcessor::with_extra_keys(config.cargo_extra_keys.clone()),
+        )),
+        FileKind::Vb6 => Box::new(AnyProcessor(vb6::VB6Updater {
+            auto_revision: options.vb6_auto_revision,
+        })),
+        FileKind::Lpi => Box::new(AnyProcessor(delphi::LpiUpdater {})),
+        FileKind::Lpk => Box::new(AnyProcessor(delphi::LpkUpdater {})),
+        FileKind::Appcast => Box::new(AnyProcessor(sparkle::AppcastUpdater { max_depth: 0 })),
+        FileKind::Zig => Box::new(AnyProcessor(zig::ZigUpdater {})),
+        FileKind::Mix => Box::new(AnyProcessor(elixir::MixUpdater {})),
+        FileKind::Boot => Box::new(AnyProcessor(clojure::BootUpdater {})),
+        FileKind::Gradle => Box::new(AnyProcessor(gradle::GradleUpdater {
+            bump_version_code: options.bump_version_code,
+        })),
+        FileKind::Sbt => Box::new(AnyProcessor(scala::SbtUpdater {
+            snapshot: options.snapshot,
+        })),
+        FileKind::Pubspec => Box::new(AnyProcessor(dart::PubspecUpdater {
+            build_number: options.build_number,
+        })),
+        FileKind::Puppet => Box::new(AnyProcessor(infra::PuppetMetadataUpdater {})),
+        FileKind::Nuspec => Box::new(AnyProcessor(dotnet::NuspecUpdater { max_depth: 0 })),
+        FileKind::Csproj => Box::new(AnyProcessor(dotnet::CsprojUpdater { max_depth: 0 })),
+        FileKind::Maven => Box::new(AnyProcessor(maven::PomUpdater {
+            property: options.maven_property.clone(),
+            scm_tag: options.update_scm_tag,
+        })),
+        FileKind::PyProject => Box::new(AnyProcessor(python::PyProjectUpdater {
+            logger: options.logger,
+        })),
+        FileKind::Homebrew => Box::new(AnyProcessor(homebrew::HomebrewUpdater {})),
+        FileKind::Godot => Box::new(AnyProcessor(godot::GodotUpdater {})),
+    }
+}
+
+/// Reports the errors of a processor as [UpdateError],
+/// so that the processors of all formats have the same type.
+struct AnyProcessor<P>(P);
+
+impl<P> ContentProcessor for AnyProcessor<P>
+where
+    P: ContentProcessor,
+    P::Err: 'static + std::error::Error,
+{
+    type Err = UpdateError;
+
+    fn process(&self, old_contents: &str, new_version: SemVer) -> Result<String, Self::Err> {
+        self.0
+            .process(old_contents, new_version)
+            .map_err(UpdateError::new_boxing_other)
+    }
+
+    fn is_at_version(&self, contents: &str, version: SemVer) -> bool {
+        self.0.is_at_version(contents, version)
+    }
+
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        self.0.extract_version(contents)
+    }
+
+    fn supports_legacy_encoding(&self) -> bool {
+        self.0.supports_legacy_encoding()
+    }
+}
+
+impl<'a> DirUpdater for ExplicitDirUpdater<'a> {
+    fn update(
+        &self,
+        dir: &str,
+        new_version: SemVer,
+    ) -> Result<Vec<(PathBuf, TextContents)>, UpdateError> {
+        let mut result = Vec::<(PathBuf, TextContents)>::new();
+        for file in self.files {
+            result.append(&mut self.updater(dir, file)?.update(dir, new_version)?);
+        }
+        Ok(dedup_and_sort(result))
+    }
+
+    fn update_keep_going(
+        &self,
+        dir: &str,
+        new_version: SemVer,
+    ) -> (Vec<(PathBuf, TextContents)>, Vec<UpdateError>) {
+        let mut result = Vec::<(PathBuf, TextContents)>::new();
+        let mut errors = Vec::<UpdateError>::new();
+        for file in self.files {
+            match self.updater(dir, file) {
+                Ok(updater) => {
+                    let (mut files, mut updater_errors) =
+                        updater.update_keep_going(dir, new_version);
+                    result.append(&mut files);
+                    errors.append(&mut updater_errors);
+                }
+                Err(err) => errors.push(err),
+            }
+        }
+        (dedup_and_sort(result), errors)
+    }
+
+    fn find_files(&self, dir: &str) -> Result<Vec<PathBuf>, UpdateError> {
+        let mut result = Vec::<PathBuf>::new();
+        for file in self.files {
+            result.append(&mut self.updater(dir, file)?.find_files(dir)?);
+        }
+        Ok(result)
+    }
+
+    fn find_outdated(&self, dir: &str, version: SemVer) -> Result<Vec<PathBuf>, UpdateError> {
+        let mut result = Vec::<PathBuf>::new();
+        for file in self.files {
+            result.append(&mut self.updater(dir, file)?.find_outdated(dir, version)?);
+        }
+        Ok(result)
+    }
+
+    fn extract_versions(&self, dir: &str) -> Result<Vec<(PathBuf, ExtractedVersion)>, UpdateError> {
+        let mut result = Vec::<(PathBuf, ExtractedVersion)>::new();
+        for file in self.files {
+            result.append(&mut self.updater(dir, file)?.extract_versions(dir)?);
+        }
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::ContentProcessor;
+    use crate::git::ProcessGitRunner;
+    use std::fs;
+
+    #[test]
+    fn vbp_referenced_by_vbg_is_updated_once() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("a.vbp"),
+            "Type=Exe\r\nMajorVer=1\r\nMinorVer=0\r\nRevisionVer=0\r\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("group.vbg"),
+            "VBGROUP 5.0\r\nStartupProject=.\\a.vbp\r\n",
+        )
+        .unwrap();
+
+        let result = CompositeDirUpdater {
+            options: UpdateOptions::default(),
+        }
+        .update(dir.path().to_str().unwrap(), SemVer::new(1, 1, 0))
+        .unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].1.text,
+            "Type=Exe\r\nMajorVer=1\r\nMinorVer=1\r\nRevisionVer=0\r\n"
+        );
+    }
+
+    #[test]
+    fn explicit_files_skip_discovery() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("version.txt"), "version := \"0.1.0\"\n").unwrap();
+
+        let selection = FileSelection::Explicit(vec![PathBuf::from("version.txt")], None);
+        let err = find_updates(
+            dir.path().to_str().unwrap(),
+            &selection,
+            SemVer::new(0, 2, 0),
+            &UpdateOptions::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("cannot infer how to update"));
+
+        let selection =
+            FileSelection::Explicit(vec![PathBuf::from("version.txt")], Some(FileKind::Sbt));
+        let result = find_updates(
+            dir.path().to_str().unwrap(),
+            &selection,
+            SemVer::new(0, 2, 0),
+            &UpdateOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            vec![(
+                dir.path().join("version.txt"),
+                "version := \"0.2.0\"\n".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn keep_going_writes_the_files_of_the_other_updaters() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("pom.xml"), "<project><version>").unwrap();
+        fs::write(dir.path().join("version.sbt"), "version := \"0.1.0\"\n").unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+
+        // by default, nothing is written
+        let mut options = UpdateOptions::default();
+        assert!(update_files(
+            &ProcessGitRunner,
+            dir_str,
+            None,
+            &FileSelection::Discover,
+            SemVer::new(0, 2, 0),
+            &options,
+            WriteOptions::default(),
+        )
+        .is_err());
+        assert!(fs::read_to_string(dir.path().join("Cargo.toml"))
+            .unwrap()
+            .contains("0.1.0"));
+
+        options.keep_going = true;
+        let err = update_files(
+            &ProcessGitRunner,
+            dir_str,
+            None,
+            &FileSelection::Discover,
+            SemVer::new(0, 2, 0),
+            &options,
+            WriteOptions::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("1 updater(s) failed:"));
+        assert!(fs::read_to_string(dir.path().join("Cargo.toml"))
+            .unwrap()
+            .contains("0.2.0"));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("version.sbt")).unwrap(),
+            "version := \"0.2.0\"\n"
+        );
+    }
+
+    #[test]
+    fn validate_agreeing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.3.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("pyproject.toml"),
+            "[project]\nname = \"app\"\nversion = \"0.3.0\"\n",
+        )
+        .unwrap();
+
+        let version = validate_versions(
+            dir.path().to_str().unwrap(),
+            &FileSelection::Discover,
+            &UpdateOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(version, SemVer::new(0, 3, 0).into());
+    }
+
+    #[test]
+    fn validate_disagreeing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_toml = dir.path().join("Cargo.toml");
+        let pyproject_toml = dir.path().join("pyproject.toml");
+        fs::write(
+            &cargo_toml,
+            "[package]\nname = \"app\"\nversion = \"0.3.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            &pyproject_toml,
+            "[project]\nname = \"app\"\nversion = \"0.2.0\"\n",
+        )
+        .unwrap();
+
+        let err = validate_versions(
+            dir.path().to_str().unwrap(),
+            &FileSelection::Discover,
+            &UpdateOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "files indicate different versions: {} (0.3.0) {} (0.2.0)",
+                cargo_toml.display(),
+                pyproject_toml.display()
+            )
+        );
+    }
+
+    #[test]
+    fn validate_snapshot_and_release_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cargo_toml = dir.path().join("Cargo.toml");
+        let pom_xml = dir.path().join("pom.xml");
+        fs::write(
+            &cargo_toml,
+            "[package]\nname = \"app\"\nversion = \"1.3.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            &pom_xml,
+            "<project>\n    <version>1.3.0-SNAPSHOT</version>\n</project>\n",
+        )
+        .unwrap();
+
+        let err = validate_versions(
+            dir.path().to_str().unwrap(),
+            &FileSelection::Discover,
+            &UpdateOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "files indicate different versions: {} (1.3.0) {} (1.3.0-SNAPSHOT)",
+                cargo_toml.display(),
+                pom_xml.display()
+            )
+        );
+    }
+
+    #[test]
+    fn validate_without_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = validate_versions(
+            dir.path().to_str().unwrap(),
+            &FileSelection::Discover,
+            &UpdateOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "no file indicates a version");
+    }
+
+    /// Writes the minor component of the version as the major one,
+    /// the same way every time.
+    struct BrokenProcessor {}
+
+    impl ContentProcessor for BrokenProcessor {
+        type Err = std::io::Error;
+
+        fn process(&self, _old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+            Ok(format!(
+                "version := \"{}.{}.{}\"\n",
+                version.minor, version.major, version.patch
+            ))
+        }
+
+        fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+            scala::SbtUpdater { snapshot: false }.extract_version(contents)
+        }
+    }
+
+    #[test]
+    fn verify_catches_broken_processor() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("version.sbt");
+        fs::write(&path, "version := \"0.1.0\"\n").unwrap();
+        let updater = FileUpdater {
+            finder: ExplicitFileFinder::new(vec![PathBuf::from("version.sbt")]),
+            processor: BrokenProcessor {},
+        };
+        let dir_str = dir.path().to_str().unwrap();
+        let version = SemVer::new(0, 2, 0);
+        for (path_buf, contents) in updater.update(dir_str, version).unwrap() {
+            fs::write(path_buf, contents.text).unwrap();
+        }
+
+        let err = verify(&updater, dir_str, version).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "files do not indicate version 0.2.0 after writing: {}",
+                path.to_string_lossy()
+            )
+        );
+    }
+
+    #[test]
+    fn verify_accepts_written_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("pubspec.yaml"),
+            "name: app\nversion: 0.1.0+7\n",
+        )
+        .unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let version = SemVer::new(0, 2, 0);
+        let options = UpdateOptions::default();
+        for (path_buf, contents) in
+            find_updates(dir_str, &FileSelection::Discover, version, &options).unwrap()
+        {
+            fs::write(path_buf, contents.text).unwrap();
+        }
+
+        verify_files(dir_str, &FileSelection::Discover, version, &options).unwrap();
+    }
+
+    #[test]
+    fn build_date_is_written_only_with_stamp_date() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("BUILD_DATE"), "2024-01-31\n").unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let version = SemVer::new(0, 2, 0);
+
+        let result = find_updates(
+            dir_str,
+            &FileSelection::Discover,
+            version,
+            &UpdateOptions::default(),
+        )
+        .unwrap();
+        assert!(result.is_empty());
+
+        let options = UpdateOptions {
+            stamp_date: Some(Date::new(2024, 3, 7).unwrap()),
+            ..UpdateOptions::default()
+        };
+        let result = find_updates(dir_str, &FileSelection::Discover, version, &options).unwrap();
+        assert_eq!(
+            result,
+            vec![(dir.path().join("BUILD_DATE"), "2024-03-07\n".into())]
+        );
+    }
+
+    #[test]
+    fn values_image_tag_is_updated_only_with_option() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("Chart.yaml"), "apiVersion: v2\nname: app\n").unwrap();
+        fs::write(dir.path().join("values.yaml"), "image:\n  tag: 0.1.0\n").unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let version = SemVer::new(0, 2, 0);
+
+        let result = find_updates(
+            dir_str,
+            &FileSelection::Discover,
+            version,
+            &UpdateOptions::default(),
+        )
+        .unwrap();
+        assert!(result.is_empty());
+
+        let options = UpdateOptions {
+            update_values_image_tag: true,
+            ..UpdateOptions::default()
+        };
+        let result = find_updates(dir_str, &FileSelection::Discover, version, &options).unwrap();
+        assert_eq!(
+            result,
+            vec![(
+                dir.path().join("values.yaml"),
+                "image:\n  tag: 0.2.0\n".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn file_kind_infer() {
+        assert_eq!(
+            FileKind::infer(Path::new("crates/core/Cargo.toml")),
+            Some(FileKind::Cargo)
+        );
+        assert_eq!(FileKind::infer(Path::new("App.VBP")), Some(FileKind::Vb6));
+        assert_eq!(
+            FileKind::infer(Path::new("app/build.gradle.kts")),
+            Some(FileKind::Gradle)
+        );
+        assert_eq!(FileKind::infer(Path::new("README.md")), None);
+    }
+}
@@ -1,11 +1,19 @@
 //! Handles Visual Basic 6.0 VBG and VBP files
 
 use crate::files::{has_extension, ContentProcessor, FileFinder};
+use crate::sem_ver::ExtractedVersion;
 use crate::SemVer;
 use std::fs;
 use std::path::PathBuf;
 
-pub struct VB6Updater {}
+/// Updates the version of VB6 projects.
+///
+/// `RevisionVer` is set to the patch of the new version, unless `auto_revision`
+/// is set, in which case it is incremented instead (like `AutoIncrementVer=1` does
+/// on each compile).
+pub struct VB6Updater {
+    pub auto_revision: bool,
+}
 
 /// Find vbp files in the current directory.
 /// vbp files are detected in two ways:
@@ -33,16 +41,48 @@ impl FileFinder for VB6Updater {
 impl ContentProcessor for VB6Updater {
     type Err = std::io::Error; // does not really throw error
     fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
-        Ok(vbp_parser::set_vbp_version(old_contents, version))
+        Ok(vbp_parser::set_vbp_version(
+            old_contents,
+            version,
+            self.auto_revision,
+        ))
+    }
+
+    /// The revision is not part of the version when it is incremented.
+    fn is_at_version(&self, contents: &str, version: SemVer) -> bool {
+        if self.auto_revision {
+            let get = |property| {
+                vbp_parser::get_property(contents, property).and_then(|value| value.parse().ok())
+            };
+            get("MajorVer") == Some(version.major) && get("MinorVer") == Some(version.minor)
+        } else {
+            vbp_parser::set_vbp_version(contents, version, false) == contents
+        }
+    }
+
+    /// The patch is the `RevisionVer`, unless the revision is incremented independently.
+    fn extract_version(&self, contents: &str) -> Option<ExtractedVersion> {
+        if self.auto_revision {
+            return None;
+        }
+        let get = |property| {
+            vbp_parser::get_property(contents, property).and_then(|value| value.parse().ok())
+        };
+        Some(SemVer::new(get("MajorVer")?, get("MinorVer")?, get("RevisionVer")?).into())
+    }
+
+    /// vbp files are often saved in Windows-1252.
+    fn supports_legacy_encoding(&self) -> bool {
+        true
     }
 }
 
 mod vbg_parser {
-    use std::fs;
+    use crate::files::read_legacy_text;
     use std::path::PathBuf;
 
     pub fn process_vbg_file(path: PathBuf) -> std::io::Result<Vec<PathBuf>> {
-        let contents = fs::read_to_string(&path)?;
+        let contents = read_legacy_text(&path)?;
         let projects = process_vbg_file_contents(path, contents.as_str());
         Ok(projects)
     }
@@ -51,7 +91,7 @@ mod vbg_parser {
         contents
             .lines()
             .map(str::trim)
-            .filter(|s| is_project_line(*s))
+            .filter(|s| is_project_line(s))
             .map(extract_project)
             .map(|s| s.replace("\\", "/"))
             .map(|s| {
@@ -67,7 +107,7 @@ mod vbg_parser {
     }
 
     fn extract_project(s: &str) -> &str {
-        s.split("=").skip(1).next().unwrap()
+        s.split("=").nth(1).unwrap()
     }
 
     #[cfg(test)]
@@ -95,19 +135,32 @@ mod vbg_parser {
 }
 
 mod vbp_parser {
+    use crate::files::keep_trailing_newline;
     use crate::SemVer;
 
-    pub fn set_vbp_version(contents: &str, version: SemVer) -> String {
+    pub fn set_vbp_version(contents: &str, version: SemVer, auto_revision: bool) -> String {
         let mut result = String::new();
         for line in contents.lines() {
-            result.push_str(map_line(line, version).as_str());
+            result.push_str(map_line(line, version, auto_revision).as_str());
             result.push('\r');
             result.push('\n');
         }
-        result
+        keep_trailing_newline(contents, result)
+    }
+
+    /// Gets the trimmed value of the given property (case insensitive).
+    pub fn get_property<'a>(contents: &'a str, property: &str) -> Option<&'a str> {
+        contents.lines().find_map(|line| {
+            let (name, value) = line.split_once('=')?;
+            if name.eq_ignore_ascii_case(property) {
+                Some(value.trim())
+            } else {
+                None
+            }
+        })
     }
 
-    fn map_line(line: &str, version: SemVer) -> String {
+    fn map_line(line: &str, version: SemVer, auto_revision: bool) -> String {
         match line.find("=") {
             Some(idx) => {
                 if idx > 0 {
@@ -117,7 +170,16 @@ mod vbp_parser {
                     } else if property.eq_ignore_ascii_case("MinorVer") {
                         format!("{}={}", property, version.minor)
                     } else if property.eq_ignore_ascii_case("RevisionVer") {
-                        format!("{}={}", property, version.patch)
+                        let revision = if auto_revision {
+                            // falls back to the patch if the current revision is not a number
+                            line[idx + 1..]
+                                .trim()
+                                .parse::<u32>()
+                                .map_or(u32::from(version.patch), |old| old + 1)
+                        } else {
+                            u32::from(version.patch)
+                        };
+                        format!("{}={}", property, revision)
                     } else {
                         line.to_owned()
                     }
@@ -152,8 +214,96 @@ RevisionVer=4
 NoAliasing=0
 "
             .replace("\n", "\r\n");
-            let actual = set_vbp_version(input, SemVer::new(2, 3, 4));
+            let actual = set_vbp_version(input, SemVer::new(2, 3, 4), false);
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn test_set_ver_auto_revision() {
+            let input =
+                "Type=Exe\r\nMajorVer=1\r\nMinorVer=0\r\nRevisionVer=17\r\nAutoIncrementVer=1\r\n";
+            let expected =
+                "Type=Exe\r\nMajorVer=1\r\nMinorVer=1\r\nRevisionVer=18\r\nAutoIncrementVer=1\r\n";
+            let actual = set_vbp_version(input, SemVer::new(1, 1, 0), true);
             assert_eq!(expected, actual);
         }
+
+        #[test]
+        fn test_get_property() {
+            let input = "Type=Exe\r\nmajorver=2\r\nMinorVer= 3\r\n";
+            assert_eq!(get_property(input, "MajorVer"), Some("2"));
+            assert_eq!(get_property(input, "MinorVer"), Some("3"));
+            assert_eq!(get_property(input, "RevisionVer"), None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::Encoding;
+    use crate::files::DirUpdater;
+
+    #[test]
+    fn test_extract_version() {
+        let input = "Type=Exe\r\nMajorVer=1\r\nminorver=2\r\nRevisionVer=3\r\n";
+        let updater = VB6Updater {
+            auto_revision: false,
+        };
+        assert_eq!(
+            updater.extract_version(input),
+            Some(SemVer::new(1, 2, 3).into())
+        );
+        assert_eq!(updater.extract_version("Type=Exe\r\nMajorVer=1\r\n"), None);
+        assert_eq!(
+            VB6Updater {
+                auto_revision: true
+            }
+            .extract_version(input),
+            None
+        );
+    }
+
+    #[test]
+    fn test_trailing_newline_is_preserved() {
+        let updater = VB6Updater {
+            auto_revision: false,
+        };
+        let result = updater
+            .process("Type=Exe\r\nMajorVer=1", SemVer::new(2, 0, 0))
+            .unwrap();
+        assert_eq!(result, "Type=Exe\r\nMajorVer=2");
+        let result = updater
+            .process("Type=Exe\nMajorVer=1\n", SemVer::new(2, 0, 0))
+            .unwrap();
+        assert_eq!(result, "Type=Exe\r\nMajorVer=2\r\n");
+    }
+
+    #[test]
+    fn test_update_windows_1252_vbp() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Billing.vbp"),
+            b"Type=Exe\r\nMajorVer=1\r\nMinorVer=0\r\nRevisionVer=0\r\nVersionComments=\"Facturaci\xF3n \x96 2022\"\r\n",
+        )
+        .unwrap();
+
+        let result = VB6Updater {
+            auto_revision: false,
+        }
+        .update(dir.path().to_str().unwrap(), SemVer::new(1, 1, 0))
+        .unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].1.text,
+            "Type=Exe\r\nMajorVer=1\r\nMinorVer=1\r\nRevisionVer=0\r\nVersionComments=\"Facturación – 2022\"\r\n"
+        );
+        // written back as it was read
+        assert_eq!(result[0].1.encoding, Encoding::Windows1252);
+        assert_eq!(
+            result[0].1.to_bytes().unwrap(),
+            b"Type=Exe\r\nMajorVer=1\r\nMinorVer=1\r\nRevisionVer=0\r\nVersionComments=\"Facturaci\xF3n \x96 2022\"\r\n".to_vec()
+        );
     }
 }
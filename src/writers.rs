@@ -1,30 +1,90 @@
-use crate::git;
-use std::path::PathBuf;
+use crate::encoding::TextContents;
+use crate::files::read_legacy_text;
+use crate::git::GitRunner;
+use crate::logger::Logger;
+use crate::progress::Progress;
+use crate::{diff, git};
+use std::path::{Path, PathBuf};
 
 pub trait FileWriter {
-    fn write(&self, path: &PathBuf, contents: &str) -> std::io::Result<()>;
+    fn write(&self, path: &Path, contents: &TextContents) -> std::io::Result<()>;
+}
 
-    fn compose<B: FileWriter>(self, other: B) -> CompositeWriter<Self, B>
-    where
-        Self: Sized,
-    {
-        CompositeWriter::new(self, other)
-    }
+/// Options that affect how the updated files are written.
+#[derive(Clone, Debug, Default)]
+pub struct WriteOptions {
+    /// Do not write anything, print the changes as a diff instead.
+    pub dry_run: bool,
+
+    /// In dry-run, also write the new contents under this directory,
+    /// at the same paths relative to the project directory.
+    pub output_dir: Option<PathBuf>,
+
+    /// Color the diff of a dry run.
+    pub color: bool,
+
+    /// Receives the progress messages.
+    pub logger: Logger,
+
+    /// Receives the machine-readable progress events.
+    pub progress: Progress,
 }
 
-pub fn create_writer(git_dir: PathBuf, dry_run: bool) -> Box<dyn FileWriter> {
+/// Creates the writer of the updated files of the project at `dir`.
+/// The files are staged with the given runner in the repository at `git_dir`, if given.
+pub fn create_writer<'a>(
+    runner: &'a dyn GitRunner,
+    dir: &str,
+    git_dir: Option<PathBuf>,
+    options: WriteOptions,
+) -> Box<dyn FileWriter + 'a> {
+    let WriteOptions {
+        dry_run,
+        output_dir,
+        color,
+        logger,
+        progress: _,
+    } = options;
+    let mut writer = MultiWriter::new();
     if dry_run {
-        Box::new(DryFileWriter {})
+        writer.push(DryFileWriter { color, logger });
+        if let Some(output_dir) = output_dir {
+            writer.push(OutputDirWriter {
+                dir: PathBuf::from(dir),
+                output_dir,
+                logger,
+            });
+        }
     } else {
-        Box::new(WetFileWriter {}.compose(GitAddWriter { git_dir }))
+        writer.push(WetFileWriter {});
+        if let Some(git_dir) = git_dir {
+            writer.push(GitAddWriter {
+                runner,
+                git_dir,
+                logger,
+            });
+        }
     }
+    Box::new(writer)
 }
 
-struct DryFileWriter {}
+struct DryFileWriter {
+    color: bool,
+    logger: Logger,
+}
 
 impl FileWriter for DryFileWriter {
-    fn write(&self, path: &PathBuf, _contents: &str) -> std::io::Result<()> {
-        println!("Would have written {}", path.to_string_lossy());
+    fn write(&self, path: &Path, contents: &TextContents) -> std::io::Result<()> {
+        self.logger
+            .info(format!("Would have written {}", path.to_string_lossy()));
+        // new files (e.g. a build date file) are shown as fully added
+        let old_contents = if path.is_file() {
+            read_legacy_text(path)?
+        } else {
+            String::new()
+        };
+        let diff = diff::format_diff(&old_contents, &contents.text, self.color);
+        self.logger.info(diff.trim_end());
         Ok(())
     }
 }
@@ -32,49 +92,112 @@ impl FileWriter for DryFileWriter {
 struct WetFileWriter {}
 
 impl FileWriter for WetFileWriter {
-    fn write(&self, path: &PathBuf, contents: &str) -> std::io::Result<()> {
-        std::fs::write(path, contents)
+    fn write(&self, path: &Path, contents: &TextContents) -> std::io::Result<()> {
+        write_encoded(path, contents)
+    }
+}
+
+/// Writes the new contents under another directory, leaving the project untouched,
+/// so that the whole tree can be compared with external tools.
+struct OutputDirWriter {
+    dir: PathBuf,
+    output_dir: PathBuf,
+    logger: Logger,
+}
+
+impl OutputDirWriter {
+    fn rebase(&self, path: &Path) -> std::io::Result<PathBuf> {
+        path.strip_prefix(&self.dir)
+            .map(|relative_path| self.output_dir.join(relative_path))
+            .map_err(std::io::Error::other)
+    }
+}
+
+impl FileWriter for OutputDirWriter {
+    fn write(&self, path: &Path, contents: &TextContents) -> std::io::Result<()> {
+        let target = self.rebase(path)?;
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_encoded(&target, contents)?;
+        self.logger
+            .debug(format!("Wrote {}", target.to_string_lossy()));
+        Ok(())
     }
 }
 
-struct GitAddWriter {
+/// Writes the contents in the encoding their file was read in,
+/// failing if that encoding cannot represent them (e.g. a Greek description in Windows-1252).
+fn write_encoded(target: &Path, contents: &TextContents) -> std::io::Result<()> {
+    let bytes = contents.to_bytes().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "{} cannot be written as {:?}",
+                target.to_string_lossy(),
+                contents.encoding
+            ),
+        )
+    })?;
+    std::fs::write(target, bytes)
+}
+
+struct GitAddWriter<'a> {
+    runner: &'a dyn GitRunner,
     git_dir: PathBuf,
+    logger: Logger,
 }
 
-impl FileWriter for GitAddWriter {
-    fn write(&self, path: &PathBuf, _contents: &str) -> std::io::Result<()> {
-        match path.strip_prefix(&self.git_dir) {
-            Ok(item_to_add) => match git::add(&self.git_dir, item_to_add) {
-                Ok(_) => Ok(()),
-                Err(err) => Err(std::io::Error::new(std::io::ErrorKind::Other, err)),
+impl<'a> FileWriter for GitAddWriter<'a> {
+    fn write(&self, path: &Path, _contents: &TextContents) -> std::io::Result<()> {
+        // the file may have been found relative to a subdirectory of the repository
+        let path = std::fs::canonicalize(path)?;
+        let git_dir = std::fs::canonicalize(&self.git_dir)?;
+        match path.strip_prefix(&git_dir) {
+            Ok(item_to_add) => match git::add(self.runner, &git_dir, item_to_add) {
+                Ok(_) => {
+                    self.logger
+                        .debug(format!("Staged {}", item_to_add.to_string_lossy()));
+                    Ok(())
+                }
+                Err(err) => Err(std::io::Error::other(err)),
             },
-            Err(err) => Err(std::io::Error::new(std::io::ErrorKind::Other, err)),
+            Err(err) => Err(std::io::Error::other(err)),
         }
     }
 }
 
-pub struct CompositeWriter<A, B> {
-    first: A,
-    second: B,
+/// Calls any number of writers in order, stopping at the first one that fails.
+#[derive(Default)]
+pub struct MultiWriter<'a> {
+    writers: Vec<Box<dyn FileWriter + 'a>>,
 }
 
-impl<A: FileWriter, B: FileWriter> CompositeWriter<A, B> {
-    pub fn new(first: A, second: B) -> Self {
-        Self { first, second }
+impl<'a> MultiWriter<'a> {
+    pub fn new() -> Self {
+        Self {
+            writers: Vec::new(),
+        }
+    }
+
+    pub fn push<W: FileWriter + 'a>(&mut self, writer: W) {
+        self.writers.push(Box::new(writer));
     }
 }
 
-impl<A: FileWriter, B: FileWriter> FileWriter for CompositeWriter<A, B> {
-    fn write(&self, path: &PathBuf, contents: &str) -> std::io::Result<()> {
-        self.first
-            .write(path, contents)
-            .and_then(|_| self.second.write(path, contents))
+impl<'a> FileWriter for MultiWriter<'a> {
+    fn write(&self, path: &Path, contents: &TextContents) -> std::io::Result<()> {
+        for writer in &self.writers {
+            writer.write(path, contents)?;
+        }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::encoding::Encoding;
     use std::cell::RefCell;
     use std::rc::Rc;
 
@@ -101,7 +224,7 @@ mod tests {
     }
 
     impl FileWriter for DummyWriter {
-        fn write(&self, _path: &PathBuf, _contents: &str) -> std::io::Result<()> {
+        fn write(&self, _path: &Path, _contents: &TextContents) -> std::io::Result<()> {
             self.mark_called();
             if self.should_fail {
                 Err(std::io::Error::from(std::io::ErrorKind::BrokenPipe))
@@ -112,19 +235,21 @@ mod tests {
     }
 
     #[test]
-    fn composite_writer_both_succeed() {
+    fn multi_writer_both_succeed() {
         // arrange
         let first_called = Rc::new(RefCell::new(false));
         let second_called = Rc::new(RefCell::new(false));
         let first = DummyWriter::new(Rc::clone(&first_called), false);
         let second = DummyWriter::new(Rc::clone(&second_called), false);
-        let composite = first.compose(second);
+        let mut composite = MultiWriter::new();
+        composite.push(first);
+        composite.push(second);
         let path_buf = PathBuf::new();
-        let contents = "";
+        let contents = TextContents::from("");
 
         // act and assert
         composite
-            .write(&path_buf, contents)
+            .write(&path_buf, &contents)
             .expect("composite should succeed");
 
         // assert
@@ -136,19 +261,21 @@ mod tests {
     }
 
     #[test]
-    fn composite_writer_second_fails() {
+    fn multi_writer_second_fails() {
         // arrange
         let first_called = Rc::new(RefCell::new(false));
         let second_called = Rc::new(RefCell::new(false));
         let first = DummyWriter::new(Rc::clone(&first_called), false);
         let second = DummyWriter::new(Rc::clone(&second_called), true);
-        let composite = first.compose(second);
+        let mut composite = MultiWriter::new();
+        composite.push(first);
+        composite.push(second);
         let path_buf = PathBuf::new();
-        let contents = "";
+        let contents = TextContents::from("");
 
         // act and assert
         composite
-            .write(&path_buf, contents)
+            .write(&path_buf, &contents)
             .expect_err("composite should fail");
 
         // assert
@@ -160,19 +287,21 @@ mod tests {
     }
 
     #[test]
-    fn composite_writer_first_fails() {
+    fn multi_writer_first_fails() {
         // arrange
         let first_called = Rc::new(RefCell::new(false));
         let second_called = Rc::new(RefCell::new(false));
         let first = DummyWriter::new(Rc::clone(&first_called), true);
         let second = DummyWriter::new(Rc::clone(&second_called), false);
-        let composite = first.compose(second);
+        let mut composite = MultiWriter::new();
+        composite.push(first);
+        composite.push(second);
         let path_buf = PathBuf::new();
-        let contents = "";
+        let contents = TextContents::from("");
 
         // act and assert
         composite
-            .write(&path_buf, contents)
+            .write(&path_buf, &contents)
             .expect_err("composite should fail");
         assert!(first_called.replace(false), "first writer should be called");
         assert!(
@@ -180,4 +309,183 @@ mod tests {
             "second writer should not have been called"
         );
     }
+
+    #[test]
+    fn multi_writer_middle_fails() {
+        // arrange
+        let called: Vec<Rc<RefCell<bool>>> = (0..3).map(|_| Rc::new(RefCell::new(false))).collect();
+        let mut multi = MultiWriter::new();
+        for (i, c) in called.iter().enumerate() {
+            multi.push(DummyWriter::new(Rc::clone(c), i == 1));
+        }
+        let path_buf = PathBuf::new();
+
+        // act
+        multi
+            .write(&path_buf, &TextContents::from(""))
+            .expect_err("multi writer should fail");
+
+        // assert
+        assert!(called[0].replace(false), "first writer should be called");
+        assert!(called[1].replace(false), "second writer should be called");
+        assert!(
+            !called[2].replace(false),
+            "third writer should not have been called"
+        );
+    }
+
+    #[test]
+    fn multi_writer_empty_succeeds() {
+        MultiWriter::new()
+            .write(&PathBuf::new(), &TextContents::from(""))
+            .expect("empty multi writer should succeed");
+    }
+
+    #[test]
+    fn wet_writer_keeps_windows_1252_encoding() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.vbp");
+
+        WetFileWriter {}
+            .write(
+                &path,
+                &TextContents::new(
+                    "Description=\"Café\"\r\nMajorVer=2\r\n".to_owned(),
+                    Encoding::Windows1252,
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read(&path).unwrap(),
+            b"Description=\"Caf\xE9\"\r\nMajorVer=2\r\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn wet_writer_fails_on_text_outside_windows_1252() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.vbp");
+        std::fs::write(&path, b"Description=\"Caf\xE9\"\r\nMajorVer=1\r\n").unwrap();
+
+        let err = WetFileWriter {}
+            .write(
+                &path,
+                &TextContents::new(
+                    "Description=\"Ελληνικά\"\r\nMajorVer=2\r\n".to_owned(),
+                    Encoding::Windows1252,
+                ),
+            )
+            .unwrap_err();
+
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        // the file is left alone
+        assert_eq!(
+            std::fs::read(&path).unwrap(),
+            b"Description=\"Caf\xE9\"\r\nMajorVer=1\r\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn dry_run_writes_under_output_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("app")).unwrap();
+        let path = dir.path().join("app").join("Cargo.toml");
+        std::fs::write(&path, "version = \"1.0.0\"\n").unwrap();
+        let new_path = dir.path().join(".build-date");
+
+        let writer = create_writer(
+            &git::ProcessGitRunner,
+            dir.path().to_str().unwrap(),
+            None,
+            WriteOptions {
+                dry_run: true,
+                output_dir: Some(output_dir.path().to_path_buf()),
+                ..WriteOptions::default()
+            },
+        );
+        writer
+            .write(&path, &TextContents::from("version = \"1.1.0\"\n"))
+            .unwrap();
+        writer
+            .write(&new_path, &TextContents::from("2024-01-02\n"))
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(output_dir.path().join("app").join("Cargo.toml")).unwrap(),
+            "version = \"1.1.0\"\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(output_dir.path().join(".build-date")).unwrap(),
+            "2024-01-02\n"
+        );
+        // the project is left alone
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "version = \"1.0.0\"\n"
+        );
+        assert!(!new_path.exists());
+    }
+
+    /// Records the git commands and succeeds.
+    #[derive(Default)]
+    struct RecordingRunner {
+        commands: RefCell<Vec<String>>,
+    }
+
+    impl GitRunner for RecordingRunner {
+        fn run(
+            &self,
+            args: &[&std::ffi::OsStr],
+            _dir: &Path,
+        ) -> std::io::Result<std::process::Output> {
+            #[cfg(unix)]
+            use std::os::unix::process::ExitStatusExt;
+            #[cfg(windows)]
+            use std::os::windows::process::ExitStatusExt;
+            let args: Vec<String> = args
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            self.commands.borrow_mut().push(args.join(" "));
+            Ok(std::process::Output {
+                status: std::process::ExitStatus::from_raw(0),
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn staging_goes_through_the_runner() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("app")).unwrap();
+        let path = dir.path().join("app").join("Cargo.toml");
+        std::fs::write(&path, "version = \"1.0.0\"\n").unwrap();
+        let runner = RecordingRunner::default();
+
+        let writer = create_writer(
+            &runner,
+            dir.path().to_str().unwrap(),
+            Some(dir.path().to_path_buf()),
+            WriteOptions::default(),
+        );
+        writer
+            .write(&path, &TextContents::from("version = \"1.1.0\"\n"))
+            .unwrap();
+        drop(writer);
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "version = \"1.1.0\"\n"
+        );
+        assert_eq!(
+            runner.commands.into_inner(),
+            vec![format!(
+                "add {}",
+                Path::new("app").join("Cargo.toml").display()
+            )]
+        );
+    }
 }
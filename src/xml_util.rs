@@ -1,6 +1,8 @@
 extern crate xml;
 
+use self::xml::attribute::OwnedAttribute;
 use self::xml::{EventReader, EventWriter};
+use crate::files::keep_trailing_newline;
 use std::fmt::{Display, Formatter};
 use std::io::Write;
 use std::string::FromUtf8Error;
@@ -10,15 +12,29 @@ pub enum ElementPath {
     /// An empty path.
     Empty,
 
-    /// A leaf element, along side its ancestor path.
-    Leaf(String, Box<ElementPath>),
+    /// A leaf element with its attributes (local name and value),
+    /// along side its ancestor path.
+    Leaf(String, Vec<(String, String)>, Box<ElementPath>),
 }
 
 impl ElementPath {
     /// Pushes a new item to the path.
     /// The current path becomes the ancestor path of the result value.
     pub fn push(self, name: &str) -> Self {
-        Self::Leaf(name.to_owned(), Box::new(self))
+        Self::Leaf(name.to_owned(), Vec::new(), Box::new(self))
+    }
+
+    /// Pushes a new item to the path, keeping its attributes
+    /// so that they can be matched with [ElementPath::matches_with_attr].
+    pub fn push_with_attributes(self, name: &str, attributes: &[OwnedAttribute]) -> Self {
+        Self::Leaf(
+            name.to_owned(),
+            attributes
+                .iter()
+                .map(|attribute| (attribute.name.local_name.clone(), attribute.value.clone()))
+                .collect(),
+            Box::new(self),
+        )
     }
 
     /// Discards the last item of the path.
@@ -26,7 +42,7 @@ impl ElementPath {
     pub fn pop(self) -> Self {
         match self {
             Self::Empty => Self::Empty,
-            Self::Leaf(_, boxed_parent) => *boxed_parent,
+            Self::Leaf(_, _, boxed_parent) => *boxed_parent,
         }
     }
 
@@ -36,27 +52,43 @@ impl ElementPath {
     pub fn matches(&self, names: &[&str]) -> bool {
         match self {
             Self::Empty => names.is_empty(),
-            Self::Leaf(name, boxed_parent) => match names.split_last() {
+            Self::Leaf(name, _, boxed_parent) => match names.split_last() {
                 Some((last, rest)) => last == name && boxed_parent.matches(rest),
                 _ => false,
             },
         }
     }
+
+    /// Checks if the names match (see [ElementPath::matches]) and the last element
+    /// has the given attribute value. A `None` value matches an element
+    /// without the attribute, e.g. a `PropertyGroup` without a `Condition`.
+    pub fn matches_with_attr(&self, names: &[&str], attr: &str, value: Option<&str>) -> bool {
+        match self {
+            Self::Leaf(_, attributes, _) if self.matches(names) => {
+                attributes
+                    .iter()
+                    .find(|(name, _)| name == attr)
+                    .map(|(_, attr_value)| attr_value.as_str())
+                    == value
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum XmlError {
-    ReadError(xml::reader::Error),
-    WriterError(xml::writer::Error),
-    FromUtf8Error(FromUtf8Error),
+    Read(xml::reader::Error),
+    Write(xml::writer::Error),
+    FromUtf8(FromUtf8Error),
 }
 
 impl Display for XmlError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::ReadError(e) => std::fmt::Display::fmt(&e, f),
-            Self::WriterError(e) => std::fmt::Display::fmt(&e, f),
-            Self::FromUtf8Error(e) => std::fmt::Display::fmt(&e, f),
+            Self::Read(e) => std::fmt::Display::fmt(&e, f),
+            Self::Write(e) => std::fmt::Display::fmt(&e, f),
+            Self::FromUtf8(e) => std::fmt::Display::fmt(&e, f),
         }
     }
 }
@@ -65,25 +97,29 @@ impl std::error::Error for XmlError {}
 
 impl From<xml::reader::Error> for XmlError {
     fn from(value: xml::reader::Error) -> Self {
-        Self::ReadError(value)
+        Self::Read(value)
     }
 }
 
 impl From<xml::writer::Error> for XmlError {
     fn from(value: xml::writer::Error) -> Self {
-        Self::WriterError(value)
+        Self::Write(value)
     }
 }
 
 impl From<FromUtf8Error> for XmlError {
     fn from(value: FromUtf8Error) -> Self {
-        Self::FromUtf8Error(value)
+        Self::FromUtf8(value)
     }
 }
 
 /// Transforms the given XML string with the specified processor.
 /// The processor is a function that receives an EventReader and
 /// EventWriter.
+///
+/// Attribute values keep their meaning, but they are written in double quotes
+/// and their special characters (`&`, `<`, `>`, quotes) are always escaped,
+/// so a value in single quotes or with an unescaped `>` is reformatted.
 pub fn transform_xml<F>(contents: &str, processor: F) -> Result<String, XmlError>
 where
     F: FnOnce(EventReader<&[u8]>, &mut EventWriter<&mut Vec<u8>>) -> Result<(), XmlError>,
@@ -94,11 +130,7 @@ where
         .perform_indent(true)
         .create_writer(&mut buf);
     processor(parser, &mut writer)?;
-    let mut result = String::from_utf8(buf)?;
-    if !result.ends_with('\n') {
-        result.push('\n');
-    }
-    Ok(result)
+    Ok(keep_trailing_newline(contents, String::from_utf8(buf)?))
 }
 
 pub fn echo<W: Write>(
@@ -115,10 +147,107 @@ pub fn echo<W: Write>(
 mod tests {
     use super::*;
 
+    fn copy_xml(contents: &str) -> String {
+        transform_xml(contents, |parser, writer| {
+            for event in parser {
+                echo(&event?, writer)?;
+            }
+            Ok(())
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_transform_xml_keeps_trailing_newline() {
+        assert_eq!(
+            copy_xml("<a><b/></a>\n"),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<a>\n  <b />\n</a>\n"
+        );
+        assert_eq!(
+            copy_xml("<a><b/></a>"),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<a>\n  <b />\n</a>"
+        );
+    }
+
+    /// Reads the attribute values of all elements, unescaped.
+    fn attribute_values(contents: &str) -> Vec<String> {
+        EventReader::from_str(contents)
+            .into_iter()
+            .filter_map(|event| match event.unwrap() {
+                xml::reader::XmlEvent::StartElement { attributes, .. } => Some(attributes),
+                _ => None,
+            })
+            .flatten()
+            .map(|attribute| attribute.value)
+            .collect()
+    }
+
+    #[test]
+    fn test_transform_xml_escapes_attributes() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<a>
+  <b Value="Tom &amp; Jerry &lt;&quot;cartoons&quot;&gt;" />
+  <c Value='single "quoted" &amp; it&apos;s' />
+  <d Value="it's > 1" Path="a&#10;b">x &gt; y &amp; 'z'</d>
+</a>
+"#;
+        let result = copy_xml(input);
+        // the values are the same, but they are always written in double quotes
+        // and special characters are always escaped
+        assert_eq!(
+            result,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<a>
+  <b Value="Tom &amp; Jerry &lt;&quot;cartoons&quot;&gt;" />
+  <c Value="single &quot;quoted&quot; &amp; it&apos;s" />
+  <d Value="it&apos;s &gt; 1" Path="a&#xA;b">x &gt; y &amp; 'z'</d>
+</a>
+"#
+        );
+        assert_eq!(attribute_values(&result), attribute_values(input));
+        assert_eq!(
+            attribute_values(input),
+            vec![
+                "Tom & Jerry <\"cartoons\">",
+                "single \"quoted\" & it's",
+                "it's > 1",
+                "a\nb"
+            ]
+        );
+        // the output is stable
+        assert_eq!(copy_xml(&result), result);
+    }
+
     #[test]
     fn test_match() {
         let element_path = ElementPath::Empty.push("project").push("modules");
         assert!(element_path.matches(&["project", "modules"]));
         assert!(!element_path.matches(&["modules", "project"]));
     }
+
+    #[test]
+    fn test_matches_with_attr() {
+        let condition = OwnedAttribute::new(
+            xml::name::OwnedName::local("Condition"),
+            "'$(Configuration)' == 'Debug'",
+        );
+        let conditional = ElementPath::Empty
+            .push("Project")
+            .push_with_attributes("PropertyGroup", &[condition]);
+        let unconditional = ElementPath::Empty
+            .push("Project")
+            .push_with_attributes("PropertyGroup", &[]);
+        let names = ["Project", "PropertyGroup"];
+
+        assert!(unconditional.matches_with_attr(&names, "Condition", None));
+        assert!(!conditional.matches_with_attr(&names, "Condition", None));
+        assert!(conditional.matches_with_attr(
+            &names,
+            "Condition",
+            Some("'$(Configuration)' == 'Debug'")
+        ));
+        assert!(!conditional.matches_with_attr(&names, "Condition", Some("true")));
+        assert!(!unconditional.matches_with_attr(&["Project"], "Condition", None));
+        assert!(!ElementPath::Empty.matches_with_attr(&[], "Condition", None));
+    }
 }
@@ -0,0 +1,263 @@
+//! Minimal line based YAML handling that preserves comments and formatting
+//!
+//! Only block mappings are navigated. Flow collections (`{ ... }`, `[ ... ]`)
+//! and sequence items are not descended into.
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+pub enum YamlError {
+    /// The requested path does not exist. Holds the dotted path.
+    PathNotFound(String),
+
+    /// The value at the requested path is not a plain or quoted scalar.
+    NotAScalar(String),
+}
+
+impl Display for YamlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PathNotFound(path) => write!(f, "YAML path {} not found", path),
+            Self::NotAScalar(path) => write!(f, "YAML path {} does not point to a scalar", path),
+        }
+    }
+}
+
+impl std::error::Error for YamlError {}
+
+/// Replaces the scalar value found at the given dotted path (e.g. `image.tag`)
+/// with the given value. The quoting style of the old value is kept.
+pub fn set_scalar_at_path(contents: &str, path: &str, value: &str) -> Result<String, YamlError> {
+    let keys: Vec<&str> = path.split('.').collect();
+    let (start, end) = find_scalar(contents, &keys)
+        .ok_or_else(|| YamlError::PathNotFound(path.to_owned()))?
+        .ok_or_else(|| YamlError::NotAScalar(path.to_owned()))?;
+    let old_value = &contents[start..end];
+    let quote = match old_value.chars().next() {
+        Some(q @ ('"' | '\'')) => Some(q),
+        _ => None,
+    };
+    let mut result = String::with_capacity(contents.len() + value.len());
+    result.push_str(&contents[..start]);
+    if let Some(q) = quote {
+        result.push(q);
+        result.push_str(value);
+        result.push(q);
+    } else {
+        result.push_str(value);
+    }
+    result.push_str(&contents[end..]);
+    Ok(result)
+}
+
+/// Finds the byte span of the scalar value at the given path.
+/// Returns `None` if the path is not found
+/// and `Some(None)` if the path is found but its value is not a scalar.
+fn find_scalar(contents: &str, keys: &[&str]) -> Option<Option<(usize, usize)>> {
+    // the indentation and name of the keys leading to the current line
+    let mut ancestors: Vec<(usize, &str)> = Vec::new();
+    // while inside a block scalar (`|` or `>`), lines indented deeper than this are skipped
+    let mut block_scalar_indent: Option<usize> = None;
+    let mut offset = 0;
+    for raw_line in contents.split_inclusive('\n') {
+        let line_offset = offset;
+        offset += raw_line.len();
+        let line = raw_line.trim_end_matches(&['\r', '\n'][..]);
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(block_indent) = block_scalar_indent {
+            if indent > block_indent {
+                continue;
+            }
+            block_scalar_indent = None;
+        }
+        if trimmed == "---" || trimmed.starts_with("--- ") {
+            ancestors.clear();
+            continue;
+        }
+        while let Some((ancestor_indent, _)) = ancestors.last() {
+            if *ancestor_indent >= indent {
+                ancestors.pop();
+            } else {
+                break;
+            }
+        }
+        let (key, value_start) = match split_key(trimmed) {
+            Some(x) => x,
+            None => continue,
+        };
+        ancestors.push((indent, key));
+        let value_part = &trimmed[value_start..];
+        let value = strip_comment(value_part).trim();
+        if value.starts_with('|') || value.starts_with('>') {
+            block_scalar_indent = Some(indent);
+        }
+        let is_match = ancestors.len() == keys.len()
+            && ancestors
+                .iter()
+                .zip(keys.iter())
+                .all(|((_, ancestor), key)| ancestor == key);
+        if is_match {
+            if value.is_empty()
+                || value.starts_with('|')
+                || value.starts_with('>')
+                || value.starts_with('{')
+                || value.starts_with('[')
+                || value.starts_with('&')
+                || value.starts_with('*')
+            {
+                return Some(None);
+            }
+            let leading_whitespace = value_part.len() - value_part.trim_start().len();
+            let start = line_offset + indent + value_start + leading_whitespace;
+            return Some(Some((start, start + value.len())));
+        }
+    }
+    None
+}
+
+/// Splits a mapping line into its (unquoted) key
+/// and the byte offset where the value starts.
+fn split_key(trimmed: &str) -> Option<(&str, usize)> {
+    if trimmed.starts_with('-') {
+        // sequence items are not supported
+        return None;
+    }
+    let (key, colon_idx) = match trimmed.chars().next() {
+        Some(q @ ('"' | '\'')) => {
+            let closing = trimmed[1..].find(q)? + 1;
+            if !trimmed[closing + 1..].starts_with(':') {
+                return None;
+            }
+            (&trimmed[1..closing], closing + 1)
+        }
+        _ => {
+            let idx = find_mapping_colon(trimmed)?;
+            (trimmed[..idx].trim_end(), idx)
+        }
+    };
+    let after_colon = colon_idx + 1;
+    match trimmed[after_colon..].chars().next() {
+        None | Some(' ') | Some('\t') => Some((key, after_colon)),
+        _ => None,
+    }
+}
+
+/// Finds the colon that separates an unquoted key from its value.
+fn find_mapping_colon(trimmed: &str) -> Option<usize> {
+    trimmed.char_indices().find_map(|(idx, ch)| {
+        let next = trimmed[idx + 1..].chars().next();
+        if ch == ':' && matches!(next, None | Some(' ') | Some('\t')) {
+            Some(idx)
+        } else {
+            None
+        }
+    })
+}
+
+/// Removes a trailing comment, ignoring `#` characters inside quotes.
+fn strip_comment(value: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut previous = ' ';
+    for (idx, ch) in value.char_indices() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '#' && previous.is_whitespace() => return &value[..idx],
+            None => {}
+        }
+        previous = ch;
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_scalar_at_path_top_level() {
+        let input = "name: app\nversion: 0.1.0\n";
+        let expected = "name: app\nversion: 1.2.3\n";
+        assert_eq!(
+            set_scalar_at_path(input, "version", "1.2.3").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_set_scalar_at_path_preserves_quotes_and_comments() {
+        let input = r#"# image settings
+image:
+  repository: "example/app" # where
+  tag: "0.1.0"   # the tag
+  pullPolicy: 'Always'
+"#;
+        let expected = r#"# image settings
+image:
+  repository: "example/app" # where
+  tag: "1.2.3"   # the tag
+  pullPolicy: 'Always'
+"#;
+        assert_eq!(
+            set_scalar_at_path(input, "image.tag", "1.2.3").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_set_scalar_at_path_skips_other_sections() {
+        let input = r#"sidecar:
+  tag: 0.0.1
+description: |
+  image:
+    tag: 0.0.2
+image:
+
+  # comment
+  tag: '0.1.0'
+"#;
+        let expected = r#"sidecar:
+  tag: 0.0.1
+description: |
+  image:
+    tag: 0.0.2
+image:
+
+  # comment
+  tag: '2.0.0'
+"#;
+        assert_eq!(
+            set_scalar_at_path(input, "image.tag", "2.0.0").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_set_scalar_at_path_not_found() {
+        let input = "image:\n  repository: app\ntag: 1.0.0\n";
+        let err = set_scalar_at_path(input, "image.tag", "1.2.3").unwrap_err();
+        assert!(matches!(err, YamlError::PathNotFound(ref p) if p == "image.tag"));
+    }
+
+    #[test]
+    fn test_set_scalar_at_path_not_a_scalar() {
+        let input = "image:\n  tag:\n    name: x\n";
+        let err = set_scalar_at_path(input, "image.tag", "1.2.3").unwrap_err();
+        assert!(matches!(err, YamlError::NotAScalar(_)));
+    }
+
+    #[test]
+    fn test_set_scalar_at_path_crlf() {
+        let input = "image:\r\n  tag: 0.1.0\r\n";
+        let expected = "image:\r\n  tag: 1.2.3\r\n";
+        assert_eq!(
+            set_scalar_at_path(input, "image.tag", "1.2.3").unwrap(),
+            expected
+        );
+    }
+}
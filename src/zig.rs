@@ -0,0 +1,159 @@
+//! Handles Zig package manifests (build.zig.zon)
+
+use crate::files::{ContentProcessor, FileFinder};
+use crate::SemVer;
+use std::path::PathBuf;
+
+/// Updates the `.version` field of the root object of `build.zig.zon`.
+/// Fields of nested objects (e.g. `.dependencies`) are left alone.
+pub struct ZigUpdater {}
+
+impl FileFinder for ZigUpdater {
+    fn find(&self, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+        let path = PathBuf::from(dir).join("build.zig.zon");
+        if path.is_file() {
+            Ok(vec![path])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+impl ContentProcessor for ZigUpdater {
+    type Err = std::io::Error; // does not really throw error
+
+    fn process(&self, old_contents: &str, version: SemVer) -> Result<String, Self::Err> {
+        match find_root_version(old_contents) {
+            Some((start, end)) => Ok(format!(
+                "{}{}{}",
+                &old_contents[..start],
+                version,
+                &old_contents[end..]
+            )),
+            None => Ok(old_contents.to_owned()),
+        }
+    }
+}
+
+/// Finds the byte span of the contents of the `.version` string of the root object.
+fn find_root_version(contents: &str) -> Option<(usize, usize)> {
+    let bytes = contents.as_bytes();
+    let mut depth = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'"' => pos = skip_string(bytes, pos),
+            b'/' if bytes.get(pos + 1) == Some(&b'/') => {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+            }
+            b'{' => depth += 1,
+            b'}' => depth -= 1,
+            b'.' if depth == 1 && is_field(bytes, pos, b".version") => {
+                return find_string_value(bytes, pos + b".version".len());
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+    None
+}
+
+/// Checks if the given field name starts at the given position,
+/// and is not just the prefix of a longer identifier.
+fn is_field(bytes: &[u8], pos: usize, field: &[u8]) -> bool {
+    bytes[pos..].starts_with(field)
+        && !bytes
+            .get(pos + field.len())
+            .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_')
+}
+
+/// Finds the contents of the string after the `=` that follows the given position.
+fn find_string_value(bytes: &[u8], mut pos: usize) -> Option<(usize, usize)> {
+    while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    if bytes.get(pos) != Some(&b'=') {
+        return None;
+    }
+    pos += 1;
+    while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    if bytes.get(pos) != Some(&b'"') {
+        return None;
+    }
+    let end = skip_string(bytes, pos);
+    if end < bytes.len() {
+        Some((pos + 1, end))
+    } else {
+        None
+    }
+}
+
+/// Returns the position of the closing quote of the string starting at the given position.
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let mut pos = start + 1;
+    while pos < bytes.len() && bytes[pos] != b'"' {
+        if bytes[pos] == b'\\' {
+            pos += 1;
+        }
+        pos += 1;
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_with_dependencies() {
+        let input = r#".{
+    // the .version = "0.0.0" in this comment is ignored
+    .name = .my_package,
+    .dependencies = .{
+        .zap = .{
+            .url = "https://github.com/zigzap/zap/archive/v0.1.7.tar.gz",
+            .version = "0.1.7",
+        },
+    },
+    .version = "0.1.0",
+    .paths = .{ "build.zig", "build.zig.zon", "src" },
+}
+"#;
+        let expected = r#".{
+    // the .version = "0.0.0" in this comment is ignored
+    .name = .my_package,
+    .dependencies = .{
+        .zap = .{
+            .url = "https://github.com/zigzap/zap/archive/v0.1.7.tar.gz",
+            .version = "0.1.7",
+        },
+    },
+    .version = "1.2.3",
+    .paths = .{ "build.zig", "build.zig.zon", "src" },
+}
+"#;
+        let result = ZigUpdater {}.process(input, SemVer::new(1, 2, 3)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_process_ignores_similar_fields() {
+        let input =
+            r#".{ .version_hint = "x", .minimum_zig_version = "0.11.0", .version="0.1.0" }"#;
+        let expected =
+            r#".{ .version_hint = "x", .minimum_zig_version = "0.11.0", .version="2.0.0" }"#;
+        let result = ZigUpdater {}.process(input, SemVer::new(2, 0, 0)).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_process_without_version() {
+        let input = ".{\n    .name = \"my_package\",\n}\n";
+        let result = ZigUpdater {}.process(input, SemVer::new(2, 0, 0)).unwrap();
+        assert_eq!(result, input);
+    }
+}
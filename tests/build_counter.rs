@@ -0,0 +1,36 @@
+mod common;
+
+use std::process::Command;
+
+#[test]
+fn plan_only_does_not_fail_on_an_invalid_build_counter() {
+    let dir = tempfile::tempdir().unwrap();
+    common::init_repo(
+        dir.path(),
+        &[
+            ("Cargo.toml", b"[package]\nversion = \"0.1.0\"\n"),
+            (".yart-build", b"not a number\n"),
+        ],
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yart"))
+        .args([
+            "-v",
+            "minor",
+            "--build-counter",
+            "--plan-only",
+            "--no-color",
+        ])
+        .arg("--dir")
+        .arg(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("leaving out the build counter"));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Cargo.toml"));
+}
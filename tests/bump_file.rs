@@ -0,0 +1,92 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+const CARGO_TOML: &str = r#"[package]
+name = "app"
+version = "0.1.0"
+edition = "2018"
+
+[dependencies]
+log = { version = "0.4.0" }
+"#;
+
+/// Runs `yart bump-file` with the given contents on stdin,
+/// in an empty directory that is not a git repository.
+fn bump_file(args: &[&str], contents: &str) -> Output {
+    let dir = tempfile::tempdir().unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_yart"))
+        .arg("bump-file")
+        .args(args)
+        .current_dir(dir.path())
+        .env("PATH", "")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(contents.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn bump_file_bumps_the_version_of_the_file() {
+    let output = bump_file(&["--as", "cargo", "-v", "minor"], CARGO_TOML);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        CARGO_TOML.replace("version = \"0.1.0\"", "version = \"0.2.0\"")
+    );
+}
+
+#[test]
+fn bump_file_sets_an_explicit_version() {
+    let output = bump_file(&["--as", "cargo", "--set-version", "1.0.0"], CARGO_TOML);
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        CARGO_TOML.replace("version = \"0.1.0\"", "version = \"1.0.0\"")
+    );
+}
+
+#[test]
+fn bump_file_fails_without_a_version_to_bump() {
+    let output = bump_file(
+        &["--as", "cargo", "-v", "patch"],
+        "[package]\nname = \"app\"\n",
+    );
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn bump_file_handles_overflow() {
+    let max_patch = CARGO_TOML.replace("version = \"0.1.0\"", "version = \"0.1.65535\"");
+
+    let output = bump_file(&["--as", "cargo", "-v", "patch"], &max_patch);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be bumped past 65535"));
+
+    let output = bump_file(
+        &["--as", "cargo", "-v", "patch", "--on-overflow", "saturate"],
+        &max_patch,
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), max_patch);
+}
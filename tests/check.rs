@@ -0,0 +1,95 @@
+mod common;
+
+use std::fs;
+use std::process::{Command, Output};
+
+fn check(dir: &std::path::Path, extra_args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_yart"))
+        .arg("--check")
+        .args(extra_args)
+        .arg("--dir")
+        .arg(dir)
+        .output()
+        .unwrap()
+}
+
+fn commit(dir: &std::path::Path, message: &str) {
+    fs::write(dir.join("notes.txt"), message).unwrap();
+    common::git(dir, &["add", "."]);
+    common::git(dir, &["commit", "--quiet", "-m", message]);
+}
+
+#[test]
+fn check_fails_without_commits_since_the_tag() {
+    let dir = tempfile::tempdir().unwrap();
+    common::init_repo(dir.path(), &[("README.md", b"# app\n")]);
+
+    let output = check(dir.path(), &[]);
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "No commits since v0.1.0, nothing to release\n"
+    );
+}
+
+#[test]
+fn check_succeeds_with_commits_since_the_tag() {
+    let dir = tempfile::tempdir().unwrap();
+    common::init_repo(dir.path(), &[("README.md", b"# app\n")]);
+    commit(dir.path(), "chore: update notes");
+    let head = common::git(dir.path(), &["rev-parse", "HEAD"]);
+
+    let output = check(dir.path(), &[]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "1 commit(s) since v0.1.0, a release is due\n"
+    );
+    // nothing is changed
+    assert_eq!(common::git(dir.path(), &["rev-parse", "HEAD"]), head);
+    assert_eq!(common::git(dir.path(), &["tag"]), "v0.1.0\n");
+}
+
+#[test]
+fn check_filters_by_commit_type() {
+    let dir = tempfile::tempdir().unwrap();
+    common::init_repo(dir.path(), &[("README.md", b"# app\n")]);
+    commit(dir.path(), "chore: update notes");
+
+    let output = check(dir.path(), &["--check-types", "feat,fix"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "None of the 1 commit(s) since v0.1.0 is of type feat/fix, nothing to release\n"
+    );
+
+    commit(dir.path(), "fix(notes): typo");
+    let output = check(dir.path(), &["--check-types", "feat,fix"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "1 feat/fix commit(s) since v0.1.0, a release is due\n"
+    );
+}
+
+#[test]
+fn check_errors_have_their_own_exit_code() {
+    // not a git repository
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = check(dir.path(), &[]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Error: "));
+}
@@ -0,0 +1,31 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Runs git in the given directory, failing the test if it fails.
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Creates a repository with the given files in a single commit, tagged `v0.1.0`.
+pub fn init_repo(dir: &Path, files: &[(&str, &[u8])]) {
+    for (name, contents) in files {
+        fs::write(dir.join(name), contents).unwrap();
+    }
+    git(dir, &["init", "--quiet"]);
+    git(dir, &["config", "user.name", "yart"]);
+    git(dir, &["config", "user.email", "yart@example.com"]);
+    git(dir, &["add", "."]);
+    git(dir, &["commit", "--quiet", "-m", "Initial commit"]);
+    git(dir, &["tag", "v0.1.0"]);
+}
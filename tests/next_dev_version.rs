@@ -0,0 +1,52 @@
+mod common;
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn next_dev_version_is_committed_after_the_tag() {
+    let dir = tempfile::tempdir().unwrap();
+    common::init_repo(
+        dir.path(),
+        &[(
+            "version.sbt",
+            b"ThisBuild / version := \"0.2.0-SNAPSHOT\"\n",
+        )],
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yart"))
+        .args([
+            "-v",
+            "minor",
+            "--next-dev-version",
+            "--no-push",
+            "--no-color",
+        ])
+        .arg("--dir")
+        .arg(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        common::git(dir.path(), &["log", "--format=%s", "-n", "3"]),
+        "Preparing next development version 0.3.0-SNAPSHOT\nReleasing version 0.2.0\nInitial commit\n"
+    );
+    assert_eq!(
+        common::git(dir.path(), &["rev-list", "-n", "1", "v0.2.0"]),
+        common::git(dir.path(), &["rev-parse", "HEAD~1"])
+    );
+    assert_eq!(
+        common::git(dir.path(), &["show", "v0.2.0:version.sbt"]),
+        "ThisBuild / version := \"0.2.0\"\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("version.sbt")).unwrap(),
+        "ThisBuild / version := \"0.3.0-SNAPSHOT\"\n"
+    );
+    assert_eq!(common::git(dir.path(), &["status", "--porcelain"]), "");
+}
@@ -0,0 +1,63 @@
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+const CARGO_TOML: &str = "[package]\nname = \"app\"\nversion = \"0.1.0\"\n";
+
+/// Runs yart without git on the PATH, so that any git invocation fails.
+fn run_without_git(dir: &str, args: &[&str], tags: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_yart"))
+        .args(["--dir", dir, "--stdin-tags", "--no-git", "--no-color"])
+        .args(args)
+        .env("PATH", "")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(tags.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn dry_run_without_git_previews_the_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let cargo_toml = dir.path().join("Cargo.toml");
+    fs::write(&cargo_toml, CARGO_TOML).unwrap();
+
+    let output = run_without_git(
+        dir.path().to_str().unwrap(),
+        &["-v", "minor", "--dry-run"],
+        "v0.1.0\n",
+    );
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{}", stderr);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "0.2.0\n");
+    assert!(stderr.contains("-version = \"0.1.0\"\n+version = \"0.2.0\"\n"));
+    assert_eq!(fs::read_to_string(&cargo_toml).unwrap(), CARGO_TOML);
+}
+
+#[test]
+fn no_git_updates_the_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let cargo_toml = dir.path().join("Cargo.toml");
+    fs::write(&cargo_toml, CARGO_TOML).unwrap();
+
+    let output = run_without_git(dir.path().to_str().unwrap(), &["-v", "patch"], "v0.1.0\n");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(&cargo_toml).unwrap(),
+        CARGO_TOML.replace("0.1.0", "0.1.1")
+    );
+}
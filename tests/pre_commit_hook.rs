@@ -0,0 +1,41 @@
+mod common;
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn pre_commit_hook_stages_only_the_files_it_changed() {
+    let dir = tempfile::tempdir().unwrap();
+    common::init_repo(
+        dir.path(),
+        &[
+            ("Cargo.toml", b"[package]\nversion = \"0.1.0\"\n"),
+            ("formatted.txt", b"before\n"),
+            ("notes.txt", b"before\n"),
+        ],
+    );
+    // an unrelated change that must not end up in the release commit
+    fs::write(dir.path().join("notes.txt"), "work in progress\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yart"))
+        .args(["-v", "minor", "--no-push", "--no-color"])
+        .args(["--pre-commit-hook", "echo after >> formatted.txt"])
+        .arg("--dir")
+        .arg(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        common::git(dir.path(), &["show", "--name-only", "--format=", "HEAD"]),
+        "Cargo.toml\nformatted.txt\n"
+    );
+    assert_eq!(
+        common::git(dir.path(), &["status", "--porcelain"]),
+        " M notes.txt\n"
+    );
+}
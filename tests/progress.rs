@@ -0,0 +1,52 @@
+mod common;
+
+use std::process::Command;
+
+#[test]
+fn progress_json_emits_the_events_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    common::init_repo(
+        dir.path(),
+        &[(
+            "Cargo.toml",
+            b"[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        )],
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yart"))
+        .args([
+            "-v",
+            "minor",
+            "--no-push",
+            "--no-color",
+            "--progress",
+            "json",
+        ])
+        .arg("--dir")
+        .arg(dir.path())
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{}", stderr);
+    let cargo_toml = dir.path().join("Cargo.toml");
+    let events: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            format!(
+                r#"{{"event":"file_discovered","path":"{}"}}"#,
+                cargo_toml.display()
+            ),
+            format!(
+                r#"{{"event":"file_changed","path":"{}"}}"#,
+                cargo_toml.display()
+            ),
+            r#"{"event":"committed","message":"Releasing version 0.2.0"}"#.to_owned(),
+            r#"{"event":"tag_created","tag":"v0.2.0"}"#.to_owned(),
+        ]
+    );
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn strict_accepts_a_manifest_already_at_the_next_version() {
+    let dir = tempfile::tempdir().unwrap();
+    common::init_repo(
+        dir.path(),
+        &[
+            (
+                "Cargo.toml",
+                b"[package]\nname = \"app\"\nversion = \"0.2.0\"\n",
+            ),
+            (
+                "pyproject.toml",
+                b"[project]\nname = \"app\"\nversion = \"0.1.0\"\n",
+            ),
+        ],
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yart"))
+        .args(["-v", "minor", "--strict", "--no-push", "--no-color"])
+        .arg("--dir")
+        .arg(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("pyproject.toml")).unwrap(),
+        "[project]\nname = \"app\"\nversion = \"0.2.0\"\n"
+    );
+    assert_eq!(
+        common::git(dir.path(), &["tag", "--list", "v0.2.0"]),
+        "v0.2.0\n"
+    );
+}
+
+#[test]
+fn strict_rejects_an_unhandled_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    common::init_repo(
+        dir.path(),
+        &[
+            (
+                "Cargo.toml",
+                b"[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+            ),
+            ("package.json", b"{\"version\": \"0.1.0\"}\n"),
+        ],
+    );
+    let head = common::git(dir.path(), &["rev-parse", "HEAD"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yart"))
+        .args(["-v", "minor", "--strict", "--no-push", "--no-color"])
+        .arg("--dir")
+        .arg(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("package.json"));
+    assert_eq!(common::git(dir.path(), &["rev-parse", "HEAD"]), head);
+}
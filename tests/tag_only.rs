@@ -0,0 +1,72 @@
+mod common;
+
+use std::process::Command;
+
+#[test]
+fn tag_only_tags_head_without_reading_files() {
+    let dir = tempfile::tempdir().unwrap();
+    // neither file can be read: the configuration is invalid and the manifest is not UTF-8
+    common::init_repo(
+        dir.path(),
+        &[
+            (".yart.toml", b"[unsupported]\n"),
+            ("Cargo.toml", b"[package]\nversion = \"0.1.0\xff\"\n"),
+        ],
+    );
+    let head = common::git(dir.path(), &["rev-parse", "HEAD"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_yart"))
+        .args(["-v", "minor", "--tag-only", "--no-push", "--no-color"])
+        .arg("--dir")
+        .arg(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "0.2.0\n");
+    assert_eq!(common::git(dir.path(), &["rev-parse", "HEAD"]), head);
+    assert_eq!(
+        common::git(dir.path(), &["rev-list", "-n", "1", "v0.2.0"]),
+        head
+    );
+}
+
+#[test]
+fn saturated_bump_does_not_move_the_existing_tag() {
+    let dir = tempfile::tempdir().unwrap();
+    common::init_repo(
+        dir.path(),
+        &[("Cargo.toml", b"[package]\nversion = \"0.1.65535\"\n")],
+    );
+    common::git(dir.path(), &["tag", "v0.1.65535"]);
+    common::git(
+        dir.path(),
+        &["commit", "--quiet", "--allow-empty", "-m", "Fix"],
+    );
+    let head = common::git(dir.path(), &["rev-parse", "HEAD"]);
+    let tagged = common::git(dir.path(), &["rev-list", "-n", "1", "v0.1.65535"]);
+
+    // there is no remote, so any push would fail the release
+    let output = Command::new(env!("CARGO_BIN_EXE_yart"))
+        .args(["-v", "patch", "--on-overflow", "saturate", "--no-color"])
+        .arg("--dir")
+        .arg(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("nothing to release"));
+    assert_eq!(common::git(dir.path(), &["rev-parse", "HEAD"]), head);
+    assert_eq!(
+        common::git(dir.path(), &["rev-list", "-n", "1", "v0.1.65535"]),
+        tagged
+    );
+}